@@ -0,0 +1,211 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Duration, Utc};
+use colored::*;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+use crate::annotate::merged_by;
+use crate::compression::read_settings;
+use crate::context::ContextManager;
+use crate::dates;
+use crate::lint::PERMISSION_LISTS;
+
+/// Contexts larger than this are flagged as bloated
+const LARGE_FILE_BYTES: u64 = 64 * 1024;
+/// Permission lists longer than this are flagged as hard to review
+const LONG_PERMISSION_LIST: usize = 100;
+/// Number of largest arrays shown in the report
+const TOP_ARRAYS: usize = 5;
+
+/// Collect the length of every array in a JSON document, keyed by dotted path
+fn collect_arrays(value: &Value, path: &str, out: &mut Vec<(String, usize)>) {
+    match value {
+        Value::Array(items) => {
+            out.push((path.to_string(), items.len()));
+            for (i, item) in items.iter().enumerate() {
+                collect_arrays(item, &format!("{path}[{i}]"), out);
+            }
+        }
+        Value::Object(map) => {
+            for (key, child) in map {
+                let child_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                collect_arrays(child, &child_path, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Return the entries of a permission list that appear more than once
+fn duplicate_permissions(list: &[Value]) -> Vec<(String, usize)> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for item in list {
+        if let Some(s) = item.as_str() {
+            *counts.entry(s).or_insert(0) += 1;
+        }
+    }
+
+    let mut duplicates: Vec<(String, usize)> = counts
+        .into_iter()
+        .filter(|(_, count)| *count > 1)
+        .map(|(s, count)| (s.to_string(), count))
+        .collect();
+    duplicates.sort();
+    duplicates
+}
+
+impl ContextManager {
+    /// Permission rules not referenced within `days` days, with when they last
+    /// were. Only per-rule evidence counts: the newest merge that brought the
+    /// rule in, as shown by `--annotate`. Rules added by hand or when the
+    /// context was created have no such record.
+    fn unreferenced_permissions(
+        &self,
+        name: &str,
+        json: &Value,
+        days: u32,
+    ) -> Result<Vec<(String, Option<DateTime<Utc>>)>> {
+        let history = self.merge_manager().load_history(name)?;
+        let cutoff = dates::now().with_timezone(&Utc) - Duration::days(i64::from(days));
+
+        let mut unreferenced = Vec::new();
+        for list in PERMISSION_LISTS {
+            let Some(rules) = json
+                .get("permissions")
+                .and_then(|p| p.get(*list))
+                .and_then(Value::as_array)
+            else {
+                continue;
+            };
+            for rule in rules.iter().filter_map(Value::as_str) {
+                let last = merged_by(&history, list, rule).map(|entry| entry.timestamp);
+                if last.map_or(true, |last| last < cutoff) {
+                    unreferenced.push((format!("{list}:{rule}"), last));
+                }
+            }
+        }
+        Ok(unreferenced)
+    }
+
+    /// Report size, array lengths and permission bloat for a context, and the
+    /// permissions not referenced within `unused_days` days
+    pub fn analyze_context(&self, name: &str, unused_days: u32) -> Result<()> {
+        let context_path = self.context_path(name);
+        if !context_path.exists() {
+            bail!("error: no context exists with the name \"{}\"", name);
+        }
+
+        let size = fs::metadata(&context_path)?.len();
+        let content = read_settings(&context_path)?;
+        let json: Value = serde_json::from_str(&content)?;
+
+        println!("📊 Analysis of context \"{}\":", name.green().bold());
+        println!();
+        println!("  📦 File size: {size} bytes");
+        if let Some(obj) = json.as_object() {
+            println!("  🔑 Top-level keys: {}", obj.len());
+        }
+
+        let mut arrays = Vec::new();
+        collect_arrays(&json, "", &mut arrays);
+        arrays.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        if !arrays.is_empty() {
+            println!("  📏 Largest arrays:");
+            for (path, len) in arrays.iter().take(TOP_ARRAYS) {
+                println!("    • {path}: {len} items");
+            }
+        }
+
+        let mut suggestions = Vec::new();
+
+        if size > LARGE_FILE_BYTES {
+            suggestions.push(format!(
+                "File is larger than {} KB; consider splitting it into smaller contexts",
+                LARGE_FILE_BYTES / 1024
+            ));
+        }
+
+        let mut lists: HashMap<&str, HashSet<&str>> = HashMap::new();
+        for kind in ["allow", "deny", "ask"] {
+            let Some(list) = json
+                .get("permissions")
+                .and_then(|p| p.get(kind))
+                .and_then(|a| a.as_array())
+            else {
+                continue;
+            };
+
+            let duplicates = duplicate_permissions(list);
+            if !duplicates.is_empty() {
+                println!("  🔁 Duplicate permissions.{kind} entries:");
+                for (perm, count) in &duplicates {
+                    println!("    • {perm} (x{count})");
+                }
+                suggestions.push(format!(
+                    "Remove {} duplicate entries from permissions.{kind}",
+                    duplicates.iter().map(|(_, c)| c - 1).sum::<usize>()
+                ));
+            }
+
+            if list.len() > LONG_PERMISSION_LIST {
+                suggestions.push(format!(
+                    "permissions.{kind} has {} entries; consider consolidating with wildcards",
+                    list.len()
+                ));
+            }
+
+            lists.insert(kind, list.iter().filter_map(|v| v.as_str()).collect());
+        }
+
+        if let (Some(allow), Some(deny)) = (lists.get("allow"), lists.get("deny")) {
+            let mut conflicts: Vec<&&str> = allow.intersection(deny).collect();
+            conflicts.sort();
+            if !conflicts.is_empty() {
+                println!("  ⚔️  Entries in both allow and deny:");
+                for perm in &conflicts {
+                    println!("    • {perm}");
+                }
+                suggestions.push(format!(
+                    "Resolve {} permissions listed in both allow and deny",
+                    conflicts.len()
+                ));
+            }
+        }
+
+        let unreferenced = self.unreferenced_permissions(name, &json, unused_days)?;
+        if !unreferenced.is_empty() {
+            println!("  💤 Permissions unreferenced for {unused_days} days:");
+            for (rule, last) in &unreferenced {
+                match last {
+                    Some(last) => println!(
+                        "    • {rule} (last merged in: {})",
+                        dates::format_datetime(last, self.date_style)
+                    ),
+                    None => println!("    • {rule} (no merge record)"),
+                }
+            }
+            suggestions.push(format!(
+                "Review {} permissions unreferenced for {unused_days} days and remove the ones no longer needed",
+                unreferenced.len()
+            ));
+        }
+
+        println!();
+        if suggestions.is_empty() {
+            println!("✅ No cleanup suggestions");
+        } else {
+            println!("💡 Suggestions:");
+            for suggestion in &suggestions {
+                println!("  • {suggestion}");
+            }
+        }
+
+        Ok(())
+    }
+}
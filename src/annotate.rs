@@ -0,0 +1,110 @@
+use anyhow::{bail, Result};
+use colored::*;
+use serde_json::Value;
+
+use crate::compression::read_settings;
+use crate::context::ContextManager;
+use crate::dates;
+use crate::lint::PERMISSION_LISTS;
+use crate::merge::MergeHistory;
+
+/// The newest merge that added `rule` to `list`. Permission merges record items
+/// as `allow:<rule>`, full merges as `permissions.allow:<rule>`.
+pub(crate) fn merged_by<'a>(
+    history: &'a [MergeHistory],
+    list: &str,
+    rule: &str,
+) -> Option<&'a MergeHistory> {
+    let short = format!("{list}:{rule}");
+    let full = format!("permissions.{short}");
+    history
+        .iter()
+        .filter(|entry| {
+            entry
+                .merged_items
+                .iter()
+                .any(|item| *item == short || *item == full)
+        })
+        .max_by_key(|entry| entry.timestamp)
+}
+
+impl ContextManager {
+    /// Print each permission rule next to the merge that brought it in, like
+    /// `git blame` for the permission lists
+    pub fn show_annotated(&self, name: &str) -> Result<()> {
+        let name = &self.resolve_alias(name);
+        let context_path = self.context_path(name);
+        if !context_path.exists() {
+            bail!("error: no context exists with the name \"{}\"", name);
+        }
+
+        let settings: Value = serde_json::from_str(&read_settings(&context_path)?)?;
+        let history = self.merge_manager().load_history(name)?;
+
+        let Some(permissions) = settings.get("permissions").and_then(Value::as_object) else {
+            println!("Context \"{}\" has no permissions", name);
+            return Ok(());
+        };
+
+        let mut unattributed = 0;
+        for list in PERMISSION_LISTS {
+            let Some(rules) = permissions.get(*list).and_then(Value::as_array) else {
+                continue;
+            };
+            println!("{} ({})", format!("permissions.{list}").cyan(), rules.len());
+
+            let rows: Vec<(Option<&MergeHistory>, String)> = rules
+                .iter()
+                .map(|rule| {
+                    let rule = rule
+                        .as_str()
+                        .map_or_else(|| rule.to_string(), str::to_string);
+                    (merged_by(&history, list, &rule), rule)
+                })
+                .collect();
+            let columns: Vec<(String, &str)> = rows
+                .iter()
+                .map(|(entry, _)| match entry {
+                    Some(entry) => (
+                        dates::format_datetime(&entry.timestamp, self.date_style),
+                        entry.source.as_str(),
+                    ),
+                    None => ("-".to_string(), "-"),
+                })
+                .collect();
+            let date_width = columns
+                .iter()
+                .map(|(date, _)| date.len())
+                .max()
+                .unwrap_or(0);
+            let source_width = columns
+                .iter()
+                .map(|(_, source)| source.len())
+                .max()
+                .unwrap_or(0);
+
+            for ((entry, rule), (date, source)) in rows.iter().zip(&columns) {
+                if entry.is_none() {
+                    unattributed += 1;
+                }
+                println!(
+                    "  {}  {}  {rule}",
+                    format!("{date:<date_width$}").dimmed(),
+                    format!("{source:<source_width$}").yellow()
+                );
+            }
+        }
+
+        if unattributed > 0 {
+            println!();
+            println!(
+                "{}",
+                format!(
+                    "{unattributed} rules have no recorded merge (added by hand or when the context was created)"
+                )
+                .dimmed()
+            );
+        }
+        Ok(())
+    }
+}
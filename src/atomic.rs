@@ -0,0 +1,75 @@
+use anyhow::{Context, Result};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Temp files written by this process so far, so concurrent writes never share one
+static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+/// The file a write to `path` should replace. A symlinked settings file (e.g. into
+/// a dotfiles repo) is written through, so the link itself survives.
+fn resolve_symlinks(path: &Path) -> PathBuf {
+    if let Ok(real) = fs::canonicalize(path) {
+        return real;
+    }
+    // A dangling link still names where the file belongs
+    match fs::read_link(path) {
+        Ok(target) => path.parent().unwrap_or(Path::new(".")).join(target),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+/// Create a temp file next to `path` that no other writer is using
+fn create_temp(dir: &Path, file_name: &str) -> io::Result<(PathBuf, File)> {
+    loop {
+        let temp_path = dir.join(format!(
+            ".{file_name}.{}-{}.cctx-tmp",
+            std::process::id(),
+            TEMP_FILES.fetch_add(1, Ordering::Relaxed)
+        ));
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)
+        {
+            Ok(file) => return Ok((temp_path, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Replace `path` with `content` so readers (and a crash) only ever see the old
+/// or the new file: write a hidden temp file next to it, fsync, then rename over it.
+/// The replaced file keeps its permissions.
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let target = resolve_symlinks(path);
+    let dir = target.parent().unwrap_or_else(|| Path::new("."));
+    let file_name = target
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or("settings.json");
+    let (temp_path, mut file) = create_temp(dir, file_name)
+        .with_context(|| format!("error: failed to write {:?}", path))?;
+
+    let result = (|| -> Result<()> {
+        file.write_all(content)?;
+        if let Ok(metadata) = fs::metadata(&target) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()?;
+        fs::rename(&temp_path, &target)?;
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result.with_context(|| format!("error: failed to write {:?}", path))?;
+
+    // Persist the rename itself; directories cannot be opened for syncing on Windows
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
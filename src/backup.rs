@@ -0,0 +1,201 @@
+use anyhow::{bail, Result};
+use chrono::NaiveDateTime;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::context::ContextManager;
+use crate::dates::{self, DateStyle};
+
+/// Timestamp format used as the backup id; backups taken within the same second
+/// (or under `--deterministic`) get a `-2`, `-3`, ... suffix
+const BACKUP_ID_FORMAT: &str = "%Y%m%d%H%M%S";
+/// Separates the backup id from the originating context in file names
+const BACKUP_SEPARATOR: &str = "--";
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_keep() -> usize {
+    20
+}
+
+/// Backups of settings.json taken before each switch, configured under `backups`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupConfig {
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Number of backups kept after each switch
+    #[serde(default = "default_keep")]
+    pub keep: usize,
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_enabled(),
+            keep: default_keep(),
+        }
+    }
+}
+
+/// A settings.json snapshot stored in the backups directory
+pub struct Backup {
+    pub id: String,
+    /// Context that was active when the backup was taken
+    pub context: String,
+    pub path: PathBuf,
+    pub size: u64,
+}
+
+/// Split a backup id into its timestamp and the counter of backups taken
+/// earlier in the same second
+fn split_id(id: &str) -> Option<(NaiveDateTime, u32)> {
+    let (timestamp, counter) = match id.split_once('-') {
+        Some((timestamp, counter)) => (timestamp, counter.parse().ok()?),
+        None => (id, 1),
+    };
+    let timestamp = NaiveDateTime::parse_from_str(timestamp, BACKUP_ID_FORMAT).ok()?;
+    Some((timestamp, counter))
+}
+
+impl Backup {
+    fn from_path(path: PathBuf) -> Option<Self> {
+        let stem = path.file_stem()?.to_str()?;
+        let (id, context) = stem.split_once(BACKUP_SEPARATOR)?;
+        split_id(id)?;
+        let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+        Some(Self {
+            id: id.to_string(),
+            context: context.to_string(),
+            path,
+            size,
+        })
+    }
+
+    /// Human readable timestamp of the backup
+    pub fn timestamp(&self, style: DateStyle) -> String {
+        split_id(&self.id)
+            .map(|(ts, _)| dates::format_naive_local(&ts, style))
+            .unwrap_or_else(|| self.id.clone())
+    }
+}
+
+impl ContextManager {
+    pub fn backups_dir(&self) -> PathBuf {
+        self.contexts_dir.join(".backups")
+    }
+
+    /// All backups, oldest first
+    pub fn list_backups(&self) -> Vec<Backup> {
+        let mut backups: Vec<Backup> = fs::read_dir(self.backups_dir())
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter_map(|entry| Backup::from_path(entry.path()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        backups.sort_by_key(|backup| split_id(&backup.id));
+        backups
+    }
+
+    /// Snapshot the current settings file before it is overwritten
+    pub(crate) fn backup_settings(&self, active_context: Option<&str>) -> Result<()> {
+        if !self.config.backups.enabled || !self.claude_settings_path.exists() {
+            return Ok(());
+        }
+
+        let backups_dir = self.backups_dir();
+        fs::create_dir_all(&backups_dir)?;
+
+        let timestamp = dates::now().format(BACKUP_ID_FORMAT).to_string();
+        let taken: Vec<String> = self.list_backups().into_iter().map(|b| b.id).collect();
+        let id = (1..)
+            .map(|n| match n {
+                1 => timestamp.clone(),
+                n => format!("{timestamp}-{n}"),
+            })
+            .find(|id| !taken.contains(id))
+            .unwrap_or(timestamp);
+        let context = active_context.unwrap_or("none");
+        fs::copy(
+            &self.claude_settings_path,
+            backups_dir.join(format!("{id}{BACKUP_SEPARATOR}{context}.json")),
+        )?;
+
+        self.prune_backups_quietly(self.config.backups.keep)?;
+        Ok(())
+    }
+
+    fn prune_backups_quietly(&self, keep: usize) -> Result<usize> {
+        let backups = self.list_backups();
+        let excess = backups.len().saturating_sub(keep);
+        for backup in backups.iter().take(excess) {
+            fs::remove_file(&backup.path)?;
+        }
+        Ok(excess)
+    }
+
+    /// Print all backups with their timestamp, originating context and size
+    pub fn show_backups(&self) -> Result<()> {
+        let backups = self.list_backups();
+        if backups.is_empty() {
+            println!("No backups found");
+            return Ok(());
+        }
+
+        println!("🗄️  Backups of {}:", self.claude_settings_path.display());
+        for backup in backups.iter().rev() {
+            println!(
+                "  {}  {}  {} ({} bytes)",
+                backup.id.cyan(),
+                backup.timestamp(self.date_style),
+                backup.context.green(),
+                backup.size
+            );
+        }
+        Ok(())
+    }
+
+    /// Delete all but the newest `keep` backups
+    pub fn prune_backups(&self, keep: usize) -> Result<()> {
+        self.ensure_writable("prune backups")?;
+
+        let removed = self.prune_backups_quietly(keep)?;
+        println!("Removed {removed} backups, kept the newest {keep}");
+        Ok(())
+    }
+
+    /// Restore settings.json from a backup
+    pub fn restore_backup(&self, id: &str) -> Result<()> {
+        self.ensure_writable("restore backups")?;
+
+        let Some(backup) = self.list_backups().into_iter().find(|b| b.id == id) else {
+            bail!("error: no backup exists with the id \"{}\"", id);
+        };
+
+        let mut state = self.load_state()?;
+        self.backup_settings(state.current.as_deref())?;
+
+        if let Some(parent) = self.claude_settings_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(&backup.path, &self.claude_settings_path)?;
+
+        // Point state at the originating context when it still exists
+        if self.list_contexts()?.contains(&backup.context) {
+            state.set_current(backup.context.clone());
+            self.save_state(&state)?;
+        }
+
+        println!(
+            "Restored backup {} taken while \"{}\" was active",
+            id.cyan(),
+            backup.context.green().bold()
+        );
+        Ok(())
+    }
+}
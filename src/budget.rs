@@ -0,0 +1,102 @@
+use anyhow::{bail, Result};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::context::ContextManager;
+
+fn default_flagged() -> Vec<String> {
+    vec!["Bash".to_string(), "Bash(*)".to_string()]
+}
+
+/// Thresholds that make switching into a context print a warning,
+/// configured under `budget` in the config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionBudget {
+    /// Maximum number of `permissions.allow` entries
+    pub max_allow: Option<usize>,
+    /// Allow entries considered unusually permissive
+    #[serde(default = "default_flagged")]
+    pub flagged: Vec<String>,
+}
+
+impl Default for PermissionBudget {
+    fn default() -> Self {
+        Self {
+            max_allow: None,
+            flagged: default_flagged(),
+        }
+    }
+}
+
+impl PermissionBudget {
+    /// Describe every way the settings exceed this budget
+    pub fn violations(&self, settings: &Value) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        let allow: Vec<&str> = settings
+            .get("permissions")
+            .and_then(|p| p.get("allow"))
+            .and_then(|a| a.as_array())
+            .map(|a| a.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default();
+
+        if let Some(max) = self.max_allow {
+            if allow.len() > max {
+                violations.push(format!("{} allow entries (budget: {})", allow.len(), max));
+            }
+        }
+
+        for entry in &allow {
+            if self.flagged.iter().any(|flagged| flagged == entry) {
+                violations.push(format!("allows {entry}"));
+            }
+        }
+
+        if settings
+            .get("permissions")
+            .and_then(|p| p.get("defaultMode"))
+            .and_then(|m| m.as_str())
+            == Some("bypassPermissions")
+        {
+            violations.push("defaultMode is bypassPermissions".to_string());
+        }
+
+        violations
+    }
+}
+
+impl ContextManager {
+    /// With --strict, refuse settings over the permission budget instead of
+    /// warning after the switch
+    pub(crate) fn enforce_permission_budget(&self, name: &str, settings: &Value) -> Result<()> {
+        if !self.strict {
+            return Ok(());
+        }
+        let violations = self.config.budget.violations(settings);
+        if !violations.is_empty() {
+            bail!(
+                "error: context \"{}\" exceeds the permission budget (--strict):\n  {}",
+                name,
+                violations.join("\n  ")
+            );
+        }
+        Ok(())
+    }
+
+    /// Print a warning banner when settings exceed the configured permission budget
+    pub fn warn_permission_budget(&self, name: &str, settings: &Value) -> Vec<String> {
+        let violations = self.config.budget.violations(settings);
+        if !violations.is_empty() {
+            eprintln!(
+                "{} Context \"{}\" exceeds the permission budget:",
+                "⚠️".yellow(),
+                name.yellow().bold()
+            );
+            for violation in &violations {
+                eprintln!("  • {violation}");
+            }
+        }
+        violations
+    }
+}
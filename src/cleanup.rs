@@ -0,0 +1,167 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, Local};
+use colored::*;
+use dialoguer::{MultiSelect, Select};
+use std::fs;
+use std::io::IsTerminal;
+
+use crate::context::ContextManager;
+use crate::dates;
+use crate::metadata::metadata_path;
+use crate::progress;
+use crate::signing::signature_path;
+
+/// A context that has not been switched to recently
+struct StaleContext {
+    name: String,
+    last_used: Option<DateTime<Local>>,
+    size: u64,
+    last_merge: Option<String>,
+}
+
+impl ContextManager {
+    /// Directory archived contexts are moved to (hidden, so never listed)
+    pub fn archive_dir(&self) -> std::path::PathBuf {
+        self.contexts_dir.join(".archive")
+    }
+
+    /// Move a context into the archive directory
+    pub fn archive_context(&self, name: &str) -> Result<()> {
+        self.ensure_writable("archive contexts")?;
+
+        let archive_dir = self.archive_dir();
+        fs::create_dir_all(&archive_dir)?;
+        let context_path = self.context_path(name);
+        fs::rename(
+            &context_path,
+            archive_dir.join(context_path.file_name().unwrap_or_default()),
+        )?;
+        // Signature and metadata go along, so an archived context can be brought back whole
+        for path in [signature_path(&context_path), metadata_path(&context_path)] {
+            if path.exists() {
+                fs::rename(
+                    &path,
+                    archive_dir.join(path.file_name().unwrap_or_default()),
+                )?;
+            }
+        }
+        // Snapshots too, replacing those of a context archived earlier under this name
+        let snapshots = self.snapshots_dir(name);
+        if snapshots.exists() {
+            let archived = archive_dir.join(".snapshots").join(name);
+            if archived.exists() {
+                fs::remove_dir_all(&archived)?;
+            }
+            fs::create_dir_all(archive_dir.join(".snapshots"))?;
+            fs::rename(&snapshots, &archived)?;
+        }
+
+        let mut state = self.load_state()?;
+        state.forget(name);
+        self.save_state(&state)?;
+
+        println!("Context \"{}\" archived", name.yellow());
+        Ok(())
+    }
+
+    /// List contexts not switched to in `months` months and offer to archive or delete them
+    pub fn suggest_cleanup(&self, months: u32) -> Result<()> {
+        let state = self.load_state()?;
+        let cutoff = dates::now() - Duration::days(i64::from(months) * 30);
+        let merge_manager = self.merge_manager();
+
+        let mut stale = Vec::new();
+        for name in self.list_contexts()? {
+            if state.current.as_ref() == Some(&name) {
+                continue;
+            }
+
+            let last_used = state
+                .last_used
+                .get(&name)
+                .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+                .map(|ts| ts.with_timezone(&Local));
+            if last_used.is_some_and(|ts| ts >= cutoff) {
+                continue;
+            }
+
+            let size = fs::metadata(self.context_path(&name))
+                .map(|m| m.len())
+                .unwrap_or(0);
+            let last_merge = merge_manager.load_history(&name)?.last().map(|entry| {
+                let when = dates::format_datetime(&entry.timestamp, self.date_style);
+                format!("{when} from {}", entry.source)
+            });
+
+            stale.push(StaleContext {
+                name,
+                last_used,
+                size,
+                last_merge,
+            });
+        }
+
+        if stale.is_empty() {
+            println!("✅ No contexts unused for {months} months");
+            return Ok(());
+        }
+
+        println!("🧹 Contexts not switched to in {months} months:");
+        for ctx in &stale {
+            let last_used = ctx
+                .last_used
+                .map(|ts| dates::format_datetime(&ts, self.date_style))
+                .unwrap_or_else(|| "never".to_string());
+            println!(
+                "  • {} (last used: {}, {} bytes, last merge: {})",
+                ctx.name.bold(),
+                last_used,
+                ctx.size,
+                ctx.last_merge.as_deref().unwrap_or("none")
+            );
+        }
+
+        if !std::io::stderr().is_terminal() {
+            return Ok(());
+        }
+
+        println!();
+        let names: Vec<&str> = stale.iter().map(|ctx| ctx.name.as_str()).collect();
+        let selected = MultiSelect::new()
+            .with_prompt("Select contexts to clean up (space to toggle)")
+            .items(&names)
+            .interact()?;
+        if selected.is_empty() {
+            return Ok(());
+        }
+
+        let action = Select::new()
+            .with_prompt(format!(
+                "What should happen to {} contexts?",
+                selected.len()
+            ))
+            .items(&["Archive", "Delete", "Cancel"])
+            .default(0)
+            .interact()?;
+
+        if action == 1 {
+            self.check_health("delete contexts in bulk")?;
+        }
+
+        if action == 2 {
+            return Ok(());
+        }
+
+        let bar = progress::bar(self.progress, selected.len(), "Cleaning up");
+        for i in selected {
+            bar.suspend(|| match action {
+                0 => self.archive_context(names[i]),
+                _ => self.delete_context(names[i]),
+            })?;
+            bar.inc(1);
+        }
+        bar.finish_and_clear();
+
+        Ok(())
+    }
+}
@@ -1,6 +1,14 @@
 use clap::Parser;
 use clap_complete::Shell;
 
+use crate::context::SettingsLevel;
+use crate::env_export::EnvShell;
+use crate::import_dir::OnConflict;
+use crate::integration::Integration;
+use crate::listing::Column;
+use crate::report::ReportFormat;
+use crate::statusline::StatuslineAction;
+
 #[derive(Parser)]
 #[command(name = "cctx")]
 #[command(about = "Claude Code context switcher", version)]
@@ -25,6 +33,43 @@ pub struct Cli {
     #[arg(short = 'n', long = "new")]
     pub new: bool,
 
+    /// With --new, copy a template context, filling its {{var}} placeholders
+    #[arg(
+        long = "template",
+        requires = "new",
+        conflicts_with = "stdin",
+        value_name = "CONTEXT"
+    )]
+    pub template: Option<String>,
+
+    /// Value for a template placeholder, as key=value (repeatable); missing values are prompted for
+    #[arg(long = "var", requires = "template", value_name = "KEY=VALUE")]
+    pub var: Vec<String>,
+
+    /// With --new, read the context's JSON from stdin instead of the current settings
+    #[arg(long = "stdin", requires = "new")]
+    pub stdin: bool,
+
+    /// With --new, switch to the context right after creating it (after --edit, if given)
+    #[arg(long = "use", requires = "new")]
+    pub use_context: bool,
+
+    /// Create a context from the current settings with --allow, --deny and --set applied
+    #[arg(long = "fork", value_name = "NAME")]
+    pub fork: Option<String>,
+
+    /// With --fork, add a rule to permissions.allow (and drop it from deny); repeatable
+    #[arg(long = "allow", value_name = "RULE", requires = "fork")]
+    pub allow: Vec<String>,
+
+    /// With --fork, add a rule to permissions.deny (and drop it from allow); repeatable
+    #[arg(long = "deny", value_name = "RULE", requires = "fork")]
+    pub deny: Vec<String>,
+
+    /// With --fork, set a value such as model=claude-opus-4-1 or env.DEBUG=1; repeatable
+    #[arg(long = "set", value_name = "KEY=VALUE", requires = "fork")]
+    pub set: Vec<String>,
+
     /// Edit context with $EDITOR
     #[arg(short = 'e', long = "edit")]
     pub edit: bool,
@@ -33,6 +78,47 @@ pub struct Cli {
     #[arg(short = 's', long = "show")]
     pub show: bool,
 
+    /// With --show, print entry counts per section instead of the full context
+    #[arg(long = "summary", requires = "show")]
+    pub summary: bool,
+
+    /// With --show on a terminal, print permissions as stored instead of grouped by tool
+    #[arg(long = "flat", requires = "show", conflicts_with = "summary")]
+    pub flat: bool,
+
+    /// With --show, print each permission rule with the merge source and date it came from
+    #[arg(long = "annotate", requires = "show", conflicts_with_all = ["summary", "flat"])]
+    pub annotate: bool,
+
+    /// Report size, array lengths, duplicate and unreferenced permissions for a context
+    #[arg(long = "analyze")]
+    pub analyze: bool,
+
+    /// With --analyze, report permissions not referenced within this many days
+    #[arg(
+        long = "unused-days",
+        value_name = "DAYS",
+        default_value_t = 90,
+        requires = "analyze"
+    )]
+    pub unused_days: u32,
+
+    /// Check a context (or all contexts) for invalid JSON and settings Claude Code would reject
+    #[arg(long = "lint")]
+    pub lint: bool,
+
+    /// Sign a context with the SSH key from the cctx config
+    #[arg(long = "sign")]
+    pub sign: bool,
+
+    /// Verify a context's SSH signature
+    #[arg(long = "verify")]
+    pub verify: bool,
+
+    /// Refuse to switch to or import contexts without a valid signature
+    #[arg(long = "require-signed")]
+    pub require_signed: bool,
+
     /// Export context to stdout
     #[arg(long = "export")]
     pub export: bool,
@@ -41,29 +127,388 @@ pub struct Cli {
     #[arg(long = "import")]
     pub import: bool,
 
+    /// With --import, merge the JSON into an existing context (recorded in its
+    /// merge history as import:stdin, import:clipboard or import:<path>)
+    #[arg(long = "merge", requires = "import", conflicts_with = "suffix")]
+    pub merge: bool,
+
+    /// Import context from a URL (retries with backoff, cached with ETags)
+    #[arg(long = "import-url", value_name = "URL")]
+    pub import_url: Option<String>,
+
+    /// Import every *.json file in a directory as contexts named after the files
+    #[arg(long = "import-dir", value_name = "DIR")]
+    pub import_dir: Option<String>,
+
+    /// What --import-dir does with files whose context already exists
+    #[arg(long = "on-conflict", value_enum, default_value_t = OnConflict::Skip, requires = "import_dir")]
+    pub on_conflict: OnConflict,
+
+    /// Only use cached downloads for URL operations
+    #[arg(long = "offline")]
+    pub offline: bool,
+
+    /// Read --import input from a file instead of stdin ('-' for stdin)
+    #[arg(long = "from-file", requires = "import", value_name = "PATH")]
+    pub from_file: Option<String>,
+
+    /// Upload a redacted copy of a context as a secret gist (or configured paste service) and print its URL
+    #[arg(long = "share")]
+    pub share: bool,
+
+    /// Read --import input from, or write --export output to, the system clipboard
+    #[arg(long = "clipboard", conflicts_with = "from_file")]
+    pub clipboard: bool,
+
+    /// Suffix appended to imported names that collide with existing contexts
+    #[arg(long = "suffix")]
+    pub suffix: Option<String>,
+
     /// Unset current context (removes settings file)
     #[arg(short = 'u', long = "unset")]
     pub unset: bool,
 
+    /// Re-apply the previous context instead of leaving no settings file (with --unset)
+    #[arg(long = "restore-previous", requires = "unset")]
+    pub restore_previous: bool,
+
+    /// List contexts unused for a while and offer to archive or delete them
+    #[arg(long = "suggest-cleanup")]
+    pub suggest_cleanup: bool,
+
+    /// Months without a switch before --suggest-cleanup lists a context
+    #[arg(
+        long = "older-than-months",
+        default_value_t = 3,
+        requires = "suggest_cleanup"
+    )]
+    pub older_than_months: u32,
+
+    /// List backups of the settings file taken before each switch
+    #[arg(long = "backups")]
+    pub backups: bool,
+
+    /// Delete old backups (with --backups)
+    #[arg(long = "prune", requires = "backups")]
+    pub prune: bool,
+
+    /// Number of newest backups kept by --prune
+    #[arg(long = "keep", default_value_t = 10, requires = "prune")]
+    pub keep: usize,
+
+    /// Restore the settings file from a backup id (with --backups)
+    #[arg(long = "restore", value_name = "ID", requires = "backups")]
+    pub restore: Option<String>,
+
+    /// Write launcher script commands (raycast or alfred) built on --porcelain
+    #[arg(long = "integration", value_enum, value_name = "LAUNCHER")]
+    pub integration: Option<Integration>,
+
+    /// Directory --integration writes its scripts to
+    #[arg(
+        long = "out-dir",
+        default_value = ".",
+        requires = "integration",
+        value_name = "DIR"
+    )]
+    pub out_dir: String,
+
+    /// Serve a JSON API on localhost (list, current, switch, diff) for GUIs and launchers
+    #[arg(long = "serve")]
+    pub serve: bool,
+
+    /// Port for --serve (default: $CCTX_PORT or 7878)
+    #[arg(long = "port", requires = "serve")]
+    pub port: Option<u16>,
+
+    /// Save an immutable snapshot of a context (default: current)
+    #[arg(long = "snapshot", conflicts_with_all = ["snapshots", "restore_snapshot"])]
+    pub snapshot: bool,
+
+    /// Label for --snapshot (default: a timestamp)
+    #[arg(long = "label", requires = "snapshot")]
+    pub label: Option<String>,
+
+    /// List the snapshots of a context (default: current)
+    #[arg(long = "snapshots", conflicts_with = "restore_snapshot")]
+    pub snapshots: bool,
+
+    /// Replace a context with its snapshot LABEL (default: current)
+    #[arg(long = "restore-snapshot", value_name = "LABEL")]
+    pub restore_snapshot: Option<String>,
+
+    /// Snapshot a context (default: current) and record a review request in its
+    /// metadata
+    #[arg(long = "review-start", conflicts_with = "review_approve")]
+    pub review_start: bool,
+
+    /// Approve a context (default: current) at its current content; switching to
+    /// it after it changes warns
+    #[arg(long = "review-approve")]
+    pub review_approve: bool,
+
+    /// Name recorded by --review-start/--review-approve (default: git user.name, then $USER)
+    #[arg(long = "reviewer", value_name = "NAME")]
+    pub reviewer: Option<String>,
+
+    /// Start recording mutating commands to a replayable script (JSON Lines)
+    #[arg(long = "record", value_name = "SCRIPT")]
+    pub record: Option<String>,
+
+    /// Stop the active recording
+    #[arg(long = "record-stop")]
+    pub record_stop: bool,
+
+    /// Run the commands of a recorded script in order
+    #[arg(long = "replay", value_name = "SCRIPT")]
+    pub replay: Option<String>,
+
+    /// Switch every level of a group from the config (user, project, local) at once
+    #[arg(long = "group", value_name = "GROUP")]
+    pub group: Option<String>,
+
+    /// Review and resolve conflicted copies left by sync tools (Dropbox, Syncthing)
+    #[arg(long = "conflicts")]
+    pub conflicts: bool,
+
+    /// Find contexts with identical settings and offer to keep one name per group,
+    /// archiving the others and making their names aliases (--yes keeps the
+    /// current or most recently used one)
+    #[arg(long = "dedupe")]
+    pub dedupe: bool,
+
+    /// List the directories below the project root with their own .claude
+    /// directory and their current context, or apply CONTEXT to the ones picked
+    /// (every one with --yes); --local applies at the local level
+    #[arg(long = "projects")]
+    pub projects: bool,
+
+    /// Inspect quarantined (corrupted) files in the contexts directory
+    #[arg(long = "doctor")]
+    pub doctor: bool,
+
+    /// With --doctor, rewrite contexts whose permission lists or env values have
+    /// the wrong shape (e.g. an object where a list belongs)
+    #[arg(long = "fix", requires = "doctor")]
+    pub fix: bool,
+
+    /// Print a local-only report (markdown or json) of contexts, usage, merges and compliance
+    #[arg(long = "report", value_enum, value_name = "FORMAT", num_args = 0..=1, default_missing_value = "markdown")]
+    pub report: Option<ReportFormat>,
+
+    /// Summarize every level: current context, drift, budget violations and backups
+    #[arg(long = "status")]
+    pub status: bool,
+
+    /// Run a command with a context applied, then restore the previous settings
+    #[arg(long = "run", value_name = "CONTEXT")]
+    pub run: Option<String>,
+
+    /// Run the command after `--` only if this context is applied
+    #[arg(long = "require", value_name = "CONTEXT", conflicts_with = "run")]
+    pub require: Option<String>,
+
+    /// Apply the --require context first instead of refusing to run
+    #[arg(long = "switch", requires = "require")]
+    pub switch: bool,
+
+    /// Do not export the context's env block into the --run command
+    #[arg(long = "no-env", requires = "run")]
+    pub no_env: bool,
+
+    /// Explain what a permission rule such as "Bash(npm:*)" matches
+    #[arg(long = "expand", value_name = "RULE")]
+    pub expand: Option<String>,
+
+    /// Check a command (after `--`) against a context's allow/ask/deny rules
+    #[arg(long = "check", value_name = "CONTEXT")]
+    pub check: Option<String>,
+
+    /// Tool checked by --check (e.g. Bash, Read, WebFetch)
+    #[arg(long = "tool", default_value = "Bash", requires = "check")]
+    pub tool: String,
+
+    /// Tool input checked by --check, instead of a command after `--`
+    #[arg(long = "input", visible_alias = "command", requires = "check")]
+    pub input: Option<String>,
+
+    /// Command and arguments for --run, --require and --check (after `--`)
+    #[arg(last = true, value_name = "COMMAND")]
+    pub command: Vec<String>,
+
+    /// Run the interactive setup that writes the cctx config
+    #[arg(long = "setup")]
+    pub setup: bool,
+
+    /// Refuse any operation that modifies files (for demos and locked-down CI)
+    #[arg(
+        long = "read-only",
+        env = "CCTX_READONLY",
+        value_parser = clap::builder::FalseyValueParser::new()
+    )]
+    pub read_only: bool,
+
+    /// Skip confirmations: --unset removing a project or local settings file, and
+    /// switching to a context very different from the live settings
+    #[arg(long = "yes")]
+    pub yes: bool,
+
+    /// Treat warnings as errors for CI and pre-commit hooks: lint warnings, permission
+    /// budget violations, drift (--status, --diff-all-against) and env conflicts
+    /// in --merge-full exit non-zero
+    #[arg(
+        long = "strict",
+        env = "CCTX_STRICT",
+        value_parser = clap::builder::FalseyValueParser::new()
+    )]
+    pub strict: bool,
+
+    /// Run destructive operations (unset, bulk delete) even if the store looks inconsistent
+    #[arg(long = "force")]
+    pub force: bool,
+
+    /// Move contexts from legacy layouts (~/.claude/settings.<name>.json, ~/.cctx) into the contexts directory
+    #[arg(long = "migrate")]
+    pub migrate: bool,
+
+    /// Print the current context as one compact line for shell prompts (`u:work`)
+    #[arg(long = "prompt")]
+    pub prompt: bool,
+
+    /// With --prompt, summarize every level at once (`u:work p:strict l:--`)
+    #[arg(long = "all-levels", requires = "prompt")]
+    pub all_levels: bool,
+
+    /// Print a Claude Code settings snippet (SessionStart hook and status line)
+    /// that records the active contexts in every session
+    #[arg(long = "hook-snippet")]
+    pub hook_snippet: bool,
+
+    /// Run as a Claude Code SessionStart hook: export CCTX_CONTEXT and
+    /// CCTX_CONTEXTS into the session and print the active contexts
+    #[arg(long = "session-start")]
+    pub session_start: bool,
+
+    /// Install or uninstall a Claude Code status line showing the active contexts,
+    /// in the given context or else the live settings
+    #[arg(long = "statusline", value_enum, value_name = "ACTION")]
+    pub statusline: Option<StatuslineAction>,
+
     /// Generate shell completions
     #[arg(long = "completions")]
     pub completions: Option<Shell>,
 
-    /// Show only current context (no highlighting when listing)
+    /// Generate a sandbox of contexts, corrupted files and history for reproducing bugs
+    #[cfg(feature = "dev")]
+    #[arg(long = "dev-fixtures", value_name = "DIR")]
+    pub dev_fixtures: Option<String>,
+
+    /// Print context names and aliases for shell completion, one per line
+    #[arg(long = "complete-contexts", hide = true)]
+    pub complete_contexts: bool,
+
+    /// Stable machine-readable output for scripts (no colors, progress or hints)
+    #[arg(long = "porcelain")]
+    pub porcelain: bool,
+
+    /// Reproducible output for golden-file tests: a frozen clock (SOURCE_DATE_EPOCH
+    /// or a fixed date), UTC, the C locale, and no colors or progress
+    #[arg(
+        long = "deterministic",
+        env = "CCTX_DETERMINISTIC",
+        value_parser = clap::builder::FalseyValueParser::new()
+    )]
+    pub deterministic: bool,
+
+    /// Show timestamps as RFC 3339 in UTC instead of "2 days ago"
+    #[arg(long = "utc", conflicts_with = "iso")]
+    pub utc: bool,
+
+    /// Show timestamps as RFC 3339 in local time instead of "2 days ago"
+    #[arg(long = "iso")]
+    pub iso: bool,
+
+    /// Show only current context (no highlighting when listing); with
+    /// --merge-from, print only the number of merged items
     #[arg(short = 'q', long = "quiet")]
     pub quiet: bool,
 
+    /// With --merge-from or --import --merge, list every merged item instead of the
+    /// first few; with --current, also show the previous context and last switch
+    #[arg(long = "verbose", conflicts_with = "quiet")]
+    pub verbose: bool,
+
+    /// Show at most N contexts per page of the listing
+    #[arg(long = "limit", value_name = "N")]
+    pub limit: Option<usize>,
+
+    /// Page of the listing to show with --limit (default: 1)
+    #[arg(long = "page", value_name = "N", requires = "limit")]
+    pub page: Option<usize>,
+
+    /// Show the listing as a table of these columns (name, lint, owner,
+    /// description, tags, mtime, size, last-used)
+    #[arg(
+        long = "columns",
+        value_enum,
+        value_delimiter = ',',
+        value_name = "LIST"
+    )]
+    pub columns: Vec<Column>,
+
+    /// Print long listings directly instead of through $PAGER
+    #[arg(long = "no-pager")]
+    pub no_pager: bool,
+
+    /// Only list or pick contexts whose name contains this text or matches this glob
+    #[arg(long = "filter", value_name = "PATTERN")]
+    pub filter: Option<String>,
+
+    /// Only list or pick contexts tagged with this tag in their metadata
+    #[arg(long = "tag")]
+    pub tag: Option<String>,
+
+    /// Settings level to manage (user, project or local)
+    #[arg(long = "level", value_enum, group = "level_select")]
+    pub level: Option<SettingsLevel>,
+
+    /// Manage user-level contexts (~/.claude/settings.json, the default)
+    #[arg(long = "user", group = "level_select")]
+    pub user: bool,
+
     /// Manage project-level contexts (./.claude/settings.json)
-    #[arg(long = "in-project")]
+    #[arg(long = "in-project", visible_alias = "project", group = "level_select")]
     pub in_project: bool,
 
     /// Manage local project contexts (./.claude/settings.local.json)
-    #[arg(long = "local")]
+    #[arg(long = "local", group = "level_select")]
     pub local: bool,
 
-    /// Merge permissions from another context or settings file
-    #[arg(long = "merge-from")]
-    pub merge_from: Option<String>,
+    /// Apply contexts to a named settings target from the cctx config
+    #[arg(long = "target", conflicts_with = "level_select")]
+    pub target: Option<String>,
+
+    /// Use the current directory as project root instead of the enclosing git repo
+    #[arg(long = "no-discover")]
+    pub no_discover: bool,
+
+    /// Use PATH as the project root instead of the working directory (for
+    /// services and scripts started elsewhere)
+    #[arg(
+        long = "dir",
+        env = "CCTX_DIR",
+        value_name = "PATH",
+        conflicts_with = "no_discover"
+    )]
+    pub dir: Option<std::path::PathBuf>,
+
+    /// Create ./.claude/settings for --in-project/--local if it does not exist yet
+    #[arg(long = "create")]
+    pub create: bool,
+
+    /// Merge permissions from another context or settings file (interactive if no source)
+    #[arg(long = "merge-from", num_args = 0..=1)]
+    pub merge_from: Option<Option<String>>,
 
     /// Remove previously merged permissions from a specific source
     #[arg(long = "unmerge")]
@@ -73,7 +518,59 @@ pub struct Cli {
     #[arg(long = "merge-history")]
     pub merge_history: bool,
 
+    /// Show --merge-history as items contributed per source
+    #[arg(long = "graph", requires = "merge_history")]
+    pub graph: bool,
+
+    /// Squash --merge-history into one entry per source (unmerge keeps working)
+    #[arg(long = "compact", requires = "merge_history", conflicts_with = "graph")]
+    pub compact: bool,
+
+    /// Only show --merge-history entries at or after this date (YYYY-MM-DD or RFC 3339)
+    #[arg(
+        long = "since",
+        value_name = "DATE",
+        requires = "merge_history",
+        conflicts_with = "compact"
+    )]
+    pub since: Option<String>,
+
     /// Merge all settings (not just permissions) from source
     #[arg(long = "merge-full")]
     pub merge_full: bool,
+
+    /// Print what --merge-from <SOURCE> would add to [name] (the merge picker's preview)
+    #[arg(long = "merge-preview", value_name = "SOURCE", hide = true)]
+    pub merge_preview: Option<String>,
+
+    /// Take the source's values for env vars set differently in both (with --merge-full)
+    #[arg(long = "env-overwrite", requires = "merge_full")]
+    pub env_overwrite: bool,
+
+    /// Print a context's (default: current) env block as shell assignments, for
+    /// `eval "$(cctx --env-export)"`
+    #[arg(long = "env-export")]
+    pub env_export: bool,
+
+    /// Shell syntax for --env-export (default: from $SHELL)
+    #[arg(long = "shell", value_enum, requires = "env_export")]
+    pub shell: Option<EnvShell>,
+
+    /// Diff a context (default: current) against the user's ~/.claude/settings.json
+    #[arg(long = "diff-user")]
+    pub diff_user: bool,
+
+    /// Summarize how every context differs from a baseline context (entries added,
+    /// removed and changed per context); narrow with --filter/--tag
+    #[arg(long = "diff-all-against", value_name = "BASE")]
+    pub diff_all_against: Option<String>,
+
+    /// Record the level a context (default: current) is meant for; switching it at
+    /// another level then warns
+    #[arg(long = "intended-level", value_enum, value_name = "LEVEL")]
+    pub intended_level: Option<SettingsLevel>,
+
+    /// Print a colored diff of the target after --merge-from/--unmerge
+    #[arg(long = "show-diff")]
+    pub show_diff: bool,
 }
@@ -0,0 +1,43 @@
+use anyhow::{bail, Context, Result};
+use arboard::Clipboard;
+use colored::*;
+
+use crate::compression::read_settings;
+use crate::context::ContextManager;
+
+/// Text currently on the system clipboard
+pub(crate) fn clipboard_text() -> Result<String> {
+    Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .context("error: failed to read text from the clipboard")
+}
+
+impl ContextManager {
+    /// Import one or more contexts from JSON on the system clipboard
+    pub fn import_context_from_clipboard(&self, name: &str, suffix: Option<&str>) -> Result<()> {
+        self.ensure_writable("import contexts")?;
+
+        let name = &self.validate_name(name)?;
+        self.import_documents(name, suffix, &clipboard_text()?)?;
+        Ok(())
+    }
+
+    /// Copy a context's JSON to the system clipboard
+    pub fn export_context_to_clipboard(&self, name: &str) -> Result<()> {
+        let context_path = self.context_path(name);
+        if !context_path.exists() {
+            bail!("error: no context exists with the name \"{}\"", name);
+        }
+
+        let content = read_settings(&context_path)?;
+        Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(content))
+            .context("error: failed to copy to the clipboard")?;
+
+        eprintln!(
+            "📋 Context \"{}\" copied to the clipboard",
+            name.green().bold()
+        );
+        Ok(())
+    }
+}
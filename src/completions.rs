@@ -5,17 +5,15 @@ use clap_complete::{generate, Generator};
 use std::io;
 
 use crate::cli::Cli;
-use crate::context::ContextManager;
 
 pub fn print_completions<G: Generator>(gen: G, cmd: &mut clap::Command) {
     generate(gen, cmd, cmd.get_name().to_string(), &mut io::stdout());
 }
 
+/// Context names are fetched at completion time with the hidden
+/// `--complete-contexts` helper, which a running `--serve` daemon answers from
+/// memory, so the scripts never go stale and never embed the names.
 pub fn print_enhanced_completions(shell: Shell) -> Result<()> {
-    let manager = ContextManager::new()?;
-    let contexts = manager.list_contexts()?;
-    let context_list = contexts.join(" ");
-
     match shell {
         Shell::Bash => {
             println!(
@@ -47,6 +45,10 @@ pub fn print_enhanced_completions(shell: Shell) -> Result<()> {
             opts="-d -c -r -n -e -s -u -q -h -V --delete --current --rename --new --edit --show --export --import --unset --completions --quiet --help --version"
             if [[ ${{cur}} == -* || ${{COMP_CWORD}} -eq 1 ]] ; then
                 COMPREPLY=( $(compgen -W "${{opts}}" -- "${{cur}}") )
+                # `-` switches back to the previous context
+                if [[ -z ${{cur}} || ${{cur}} == "-" ]]; then
+                    _cctx_contexts previous
+                fi
                 return 0
             fi
             case "${{prev}}" in
@@ -55,11 +57,12 @@ pub fn print_enhanced_completions(shell: Shell) -> Result<()> {
                     return 0
                     ;;
                 -d|--delete|-e|--edit|-s|--show|--export)
-                    COMPREPLY=($(compgen -W "{context_list}" -- "${{cur}}"))
+                    _cctx_contexts
                     return 0
                     ;;
                 *)
-                    COMPREPLY=($(compgen -W "{context_list} ${{opts}}" -- "${{cur}}"))
+                    _cctx_contexts previous
+                    COMPREPLY+=($(compgen -W "${{opts}}" -- "${{cur}}"))
                     return 0
                     ;;
             esac
@@ -67,6 +70,22 @@ pub fn print_enhanced_completions(shell: Shell) -> Result<()> {
     esac
 }}
 
+# Matched by prefix rather than with compgen -W, which would expand `$` and
+# split names containing spaces. `-` (the previous context) is only offered
+# where a context is switched to, i.e. when called with "previous".
+_cctx_contexts() {{
+    local c tab=$'\t'
+    while IFS= read -r c; do
+        c="${{c%%${{tab}}*}}"
+        if [[ "${{c}}" == "-" && "$1" != "previous" ]]; then
+            continue
+        fi
+        if [[ "${{c}}" == "${{cur}}"* ]]; then
+            COMPREPLY+=("$(printf '%q' "${{c}}")")
+        fi
+    done < <(cctx --complete-contexts 2>/dev/null)
+}}
+
 if [[ "${{BASH_VERSINFO[0]}}" -eq 4 && "${{BASH_VERSINFO[1]}}" -ge 4 || "${{BASH_VERSINFO[0]}}" -gt 4 ]]; then
     complete -F _cctx -o nosort -o bashdefault -o default cctx
 else
@@ -75,13 +94,19 @@ fi"#
             );
         }
         Shell::Fish => {
+            // Command substitution splits on newlines only, so names with
+            // spaces or quotes stay one candidate each
+            let context_list = "'(cctx --complete-contexts 2>/dev/null)'";
+            // Options take a real context name, never the `-` previous token
+            let named_list =
+                "'(cctx --complete-contexts 2>/dev/null | string match -rv \\'^-\\t\\')'";
             println!("complete -c cctx -l completions -d 'Generate shell completions' -r -f -a \"bash\t''
 elvish\t''
 fish\t''
 powershell\t''
 zsh\t''\"");
             for opt in ["-d", "-e", "-s", "--delete", "--edit", "--show", "--export"] {
-                println!("complete -c cctx {opt} -d 'Context name' -r -f -a \"{context_list}\"");
+                println!("complete -c cctx {opt} -d 'Context name' -r -f -a {named_list}");
             }
             println!(
                 "complete -c cctx -s d -l delete -d 'Delete context mode'
@@ -97,24 +122,9 @@ complete -c cctx -s q -l quiet -d 'Show only current context (no highlighting wh
 complete -c cctx -s h -l help -d 'Print help'
 complete -c cctx -s V -l version -d 'Print version'"
             );
-            if !contexts.is_empty() {
-                println!("complete -c cctx -f -a \"{context_list}\"");
-            }
+            println!("complete -c cctx -f -a {context_list}");
         }
         Shell::Zsh => {
-            let context_completions = if contexts.is_empty() {
-                String::new()
-            } else {
-                format!(
-                    "local contexts=({})\n    _describe 'contexts' contexts",
-                    contexts
-                        .iter()
-                        .map(|c| format!("'{c}'"))
-                        .collect::<Vec<_>>()
-                        .join(" ")
-                )
-            };
-
             println!(
                 r#"#compdef cctx
 
@@ -156,12 +166,25 @@ _cctx() {{
 '--help[Print help]' \
 '-V[Print version]' \
 '--version[Print version]' \
-'::context:_cctx_contexts' \
+'::context:_cctx_contexts previous' \
 && ret=0
 }}
 
 _cctx_contexts() {{
-    {context_completions}
+    local -a contexts
+    local line name tab=$'\t'
+    for line in ${{(f)"$(cctx --complete-contexts 2>/dev/null)"}}; do
+        # _describe splits entries on `:`; the description follows a tab
+        name="${{${{line%%${{tab}}*}}//:/\\:}}"
+        # `-` is only a valid target for switching
+        [[ "$name" == "-" && "$1" != "previous" ]] && continue
+        if [[ "$line" == *${{tab}}* ]]; then
+            contexts+=("$name:${{line#*${{tab}}}}")
+        else
+            contexts+=("$name")
+        fi
+    done
+    _describe 'contexts' contexts
 }}
 
 (( $+functions[_cctx_commands] )) ||
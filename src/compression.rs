@@ -0,0 +1,57 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::recording;
+
+/// Extension appended to compressed contexts (`work.json` -> `work.json.zst`)
+pub const COMPRESSED_EXTENSION: &str = "zst";
+
+/// zstd level used for new files; favors speed, huge permission lists still shrink a lot
+const COMPRESSION_LEVEL: i32 = 3;
+
+pub fn is_compressed(path: &Path) -> bool {
+    path.extension().and_then(|s| s.to_str()) == Some(COMPRESSED_EXTENSION)
+}
+
+/// Compressed counterpart of a plain context file
+pub fn compressed_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(COMPRESSED_EXTENSION);
+    path.with_file_name(name)
+}
+
+/// Context name for a `name.json` or `name.json.zst` file
+pub fn context_name(path: &Path) -> Option<&str> {
+    let filename = path.file_name()?.to_str()?;
+    filename
+        .strip_suffix(&format!(".json.{COMPRESSED_EXTENSION}"))
+        .or_else(|| filename.strip_suffix(".json"))
+}
+
+/// Read a settings file, decompressing it when stored as `.zst`
+pub fn read_settings(path: &Path) -> Result<String> {
+    if !is_compressed(path) {
+        return Ok(fs::read_to_string(path)?);
+    }
+
+    let bytes = fs::read(path)?;
+    let content = zstd::decode_all(bytes.as_slice())
+        .with_context(|| format!("Failed to decompress {:?}", path))?;
+    String::from_utf8(content).with_context(|| format!("{:?} is not valid UTF-8", path))
+}
+
+/// Write a settings file, compressing it when the path ends in `.zst`. The
+/// change is appended to the recording active for its contexts directory.
+pub fn write_settings(path: &Path, content: &str) -> Result<()> {
+    let before = read_settings(path).ok();
+    if is_compressed(path) {
+        let compressed = zstd::encode_all(content.as_bytes(), COMPRESSION_LEVEL)
+            .with_context(|| format!("Failed to compress {:?}", path))?;
+        fs::write(path, compressed)?;
+    } else {
+        fs::write(path, content)?;
+    }
+    recording::record_change(path, before.as_deref(), content)
+}
@@ -0,0 +1,201 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::backup::BackupConfig;
+use crate::budget::PermissionBudget;
+use crate::context::SettingsLevel;
+use crate::dates::DateStyle;
+use crate::filter::ContextFilter;
+use crate::groups::ContextGroup;
+use crate::lint::LintConfig;
+use crate::merge::{HistoryRetention, MergeStrategy, MergeSummary};
+use crate::naming::NamingRules;
+use crate::share::ShareConfig;
+use crate::signing::SigningConfig;
+use crate::similarity::SwitchConfig;
+
+/// An alternate settings.json location contexts can be applied to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Target {
+    pub settings_path: String,
+}
+
+/// User configuration stored in `~/.claude/settings/.cctx-config.json`
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Config {
+    #[serde(default)]
+    pub targets: BTreeMap<String, Target>,
+    #[serde(default)]
+    pub naming: NamingRules,
+    #[serde(default)]
+    pub merge: MergeConfig,
+    #[serde(default)]
+    pub signing: SigningConfig,
+    #[serde(default)]
+    pub budget: PermissionBudget,
+    #[serde(default)]
+    pub ui: UiConfig,
+    #[serde(default)]
+    pub backups: BackupConfig,
+    /// Write `.cctx-current` whenever the active context changes
+    #[serde(default)]
+    pub status_file: bool,
+    #[serde(default)]
+    pub share: ShareConfig,
+    /// Store new contexts zstd-compressed as `name.json.zst`
+    #[serde(default)]
+    pub compress: bool,
+    #[serde(default)]
+    pub groups: BTreeMap<String, ContextGroup>,
+    #[serde(default)]
+    pub lint: LintConfig,
+    /// Short names for contexts (`"prod": "company-prod-readonly"`)
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+    #[serde(default)]
+    pub switch: SwitchConfig,
+}
+
+/// Which picker interactive selection uses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Picker {
+    /// fzf when installed, otherwise the built-in picker
+    #[default]
+    Auto,
+    Fzf,
+    Builtin,
+}
+
+/// How the fzf picker is run, configured under `ui.fzf`
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct FzfConfig {
+    /// fzf binary to run instead of `fzf` from PATH
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Extra arguments passed to fzf (e.g. `["--height", "40%"]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+}
+
+impl FzfConfig {
+    pub fn binary(&self) -> &str {
+        self.path.as_deref().unwrap_or("fzf")
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Presentation preferences, configured under `ui` in the config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UiConfig {
+    /// Level used when no level flag is given (user unless configured)
+    pub default_level: Option<SettingsLevel>,
+    #[serde(default = "default_true")]
+    pub color: bool,
+    #[serde(default = "default_true")]
+    pub emoji: bool,
+    #[serde(default)]
+    pub picker: Picker,
+    #[serde(default)]
+    pub fzf: FzfConfig,
+    /// How timestamps are shown (relative, iso or utc)
+    #[serde(default)]
+    pub date_format: DateStyle,
+    /// Narrow shell completion candidates the way `--filter`/`--tag` narrow listings
+    #[serde(default, skip_serializing_if = "ContextFilter::is_empty")]
+    pub completion_filter: ContextFilter,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            default_level: None,
+            color: true,
+            emoji: true,
+            picker: Picker::Auto,
+            fzf: FzfConfig::default(),
+            date_format: DateStyle::Relative,
+            completion_filter: ContextFilter::default(),
+        }
+    }
+}
+
+/// Merge behavior settings
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct MergeConfig {
+    /// Dotted paths of list-valued settings merged with deduplication
+    /// (defaults to the permission lists)
+    pub array_paths: Option<Vec<String>>,
+    /// Per-key conflict resolution for full merges (keep-target, overwrite, append)
+    #[serde(default)]
+    pub strategies: BTreeMap<String, MergeStrategy>,
+    /// When older merge history entries are compacted
+    #[serde(default)]
+    pub history: HistoryRetention,
+    /// How many merged items a merge prints (counts, short, full)
+    #[serde(default)]
+    pub summary: MergeSummary,
+}
+
+impl Config {
+    /// Location of the config file inside the user-level contexts directory
+    pub fn default_path(home_dir: &Path) -> PathBuf {
+        home_dir
+            .join(".claude")
+            .join("settings")
+            .join(".cctx-config.json")
+    }
+
+    pub fn load(config_path: &PathBuf) -> Result<Self> {
+        if config_path.exists() {
+            let content = fs::read_to_string(config_path)
+                .with_context(|| format!("Failed to read config from {:?}", config_path))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse config from {:?}", config_path))
+        } else {
+            Ok(Config::default())
+        }
+    }
+
+    /// Load the config from its default location in the home directory
+    pub fn load_default() -> Result<Self> {
+        let home_dir = home_dir()?;
+        Self::load(&Self::default_path(&home_dir))
+    }
+
+    pub fn save(&self, config_path: &PathBuf) -> Result<()> {
+        if let Some(parent) = config_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        fs::write(config_path, content)
+            .with_context(|| format!("Failed to write config to {:?}", config_path))
+    }
+}
+
+/// The home directory, which must be absolute: an empty or relative `$HOME` (as
+/// some service managers set it) would scatter settings relative to wherever
+/// cctx happens to run
+pub fn home_dir() -> Result<PathBuf> {
+    match dirs::home_dir() {
+        Some(home) if home.is_absolute() => Ok(home),
+        _ => bail!("error: cannot determine the home directory; set HOME to an absolute path"),
+    }
+}
+
+/// Expand a leading `~` to the home directory
+pub fn expand_tilde(path: &str, home_dir: &Path) -> PathBuf {
+    if path == "~" {
+        home_dir.to_path_buf()
+    } else if let Some(rest) = path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")) {
+        home_dir.join(rest)
+    } else {
+        PathBuf::from(path)
+    }
+}
@@ -0,0 +1,155 @@
+use anyhow::Result;
+use colored::*;
+use dialoguer::Select;
+use regex::Regex;
+use std::fs;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::compression::{read_settings, write_settings};
+use crate::context::ContextManager;
+use crate::diff::print_unified_diff;
+
+/// File names sync tools give to conflicting versions, capturing the original context name
+fn conflict_patterns() -> &'static [Regex] {
+    static PATTERNS: OnceLock<Vec<Regex>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            // Dropbox and Nextcloud: "work (conflicted copy 2024-05-01).json",
+            // "work (Alice's conflicted copy 2024-05-01).json"
+            r"^(.+?) \([^)]*conflict[^)]*\)\.json$",
+            // Syncthing: "work.sync-conflict-20240501-101500-ABCDEFG.json"
+            r"^(.+?)\.sync-conflict-[^.]*\.json$",
+        ]
+        .iter()
+        .map(|pattern| Regex::new(pattern).expect("valid conflict pattern"))
+        .collect()
+    })
+}
+
+/// The context a conflicted copy belongs to, if the file name is a sync conflict
+pub fn conflict_original(filename: &str) -> Option<String> {
+    conflict_patterns()
+        .iter()
+        .find_map(|re| re.captures(filename))
+        .map(|caps| caps[1].to_string())
+}
+
+/// A conflicted copy left in the contexts directory by a sync tool
+pub struct ConflictedCopy {
+    pub path: PathBuf,
+    pub original: String,
+}
+
+impl ContextManager {
+    /// Conflicted copies in the contexts directory, sorted by file name
+    pub fn conflicted_copies(&self) -> Vec<ConflictedCopy> {
+        let mut copies: Vec<ConflictedCopy> = fs::read_dir(&self.contexts_dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter_map(|entry| {
+                        let path = entry.path();
+                        let filename = path.file_name()?.to_str()?;
+                        let original = conflict_original(filename)?;
+                        Some(ConflictedCopy { path, original })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        copies.sort_by(|a, b| a.path.cmp(&b.path));
+        copies
+    }
+
+    /// Show each conflicted copy as a diff against its context and offer to resolve it
+    pub fn review_conflicts(&self) -> Result<()> {
+        let copies = self.conflicted_copies();
+        if copies.is_empty() {
+            println!("✅ No conflicted copies in {}", self.contexts_dir.display());
+            return Ok(());
+        }
+
+        let interactive = std::io::stderr().is_terminal();
+        for copy in &copies {
+            let filename = copy.path.file_name().unwrap_or_default().to_string_lossy();
+            let original_path = self.context_path(&copy.original);
+            let theirs = fs::read_to_string(&copy.path)?;
+
+            println!();
+            println!(
+                "{} {} conflicts with context \"{}\"",
+                "⚠️".yellow(),
+                filename.bold(),
+                copy.original.green().bold()
+            );
+
+            let ours = if original_path.exists() {
+                read_settings(&original_path)?
+            } else {
+                println!("  Context \"{}\" no longer exists", copy.original);
+                String::new()
+            };
+            print_unified_diff(
+                &ours,
+                &theirs,
+                &format!("{}.json", copy.original),
+                &filename,
+            );
+
+            if interactive {
+                self.resolve_conflict(copy, &theirs)?;
+            }
+        }
+
+        if !interactive {
+            println!();
+            println!("💡 Run cctx --conflicts in a terminal to resolve them");
+        }
+        Ok(())
+    }
+
+    fn resolve_conflict(&self, copy: &ConflictedCopy, theirs: &str) -> Result<()> {
+        let action = Select::new()
+            .with_prompt(format!("Resolve conflict for \"{}\"", copy.original))
+            .items(&[
+                "Keep the context (delete the conflicted copy)",
+                "Use the conflicted copy",
+                "Merge the conflicted copy into the context",
+                "Skip",
+            ])
+            .default(3)
+            .interact()?;
+
+        if action == 3 {
+            return Ok(());
+        }
+        self.ensure_writable("resolve conflicts")?;
+
+        let original_path = self.context_path(&copy.original);
+        match action {
+            0 => fs::remove_file(&copy.path)?,
+            1 => {
+                self.ensure_protected_kept(&copy.original, &serde_json::from_str(theirs)?)?;
+                write_settings(&original_path, theirs)?;
+                fs::remove_file(&copy.path)?;
+            }
+            _ => {
+                let mut target: serde_json::Value = if original_path.exists() {
+                    serde_json::from_str(&read_settings(&original_path)?)?
+                } else {
+                    serde_json::json!({})
+                };
+                let source: serde_json::Value = serde_json::from_str(theirs)?;
+                let filename = copy.path.file_name().unwrap_or_default().to_string_lossy();
+                self.merge_manager()
+                    .merge_full(&mut target, &source, &filename)?;
+                write_settings(&original_path, &serde_json::to_string_pretty(&target)?)?;
+                fs::remove_file(&copy.path)?;
+            }
+        }
+
+        println!("✅ Conflict for \"{}\" resolved", copy.original.green());
+        Ok(())
+    }
+}
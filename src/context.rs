@@ -1,13 +1,59 @@
 use anyhow::{bail, Context, Result};
 use colored::*;
+use std::collections::BTreeMap;
 use std::fs;
+use std::io::{self, IsTerminal, Write};
 use std::path::PathBuf;
 use std::process::Command;
 
-use crate::merge::MergeManager;
+use crate::atomic::write_atomic;
+use crate::compression::{
+    compressed_path, context_name, is_compressed, read_settings, write_settings,
+};
+use crate::config::{expand_tilde, home_dir, Config};
+use crate::conflicts::conflict_original;
+use crate::dates::{self, DateStyle};
+use crate::diff::print_unified_diff;
+use crate::filter::ContextFilter;
+use crate::ignore::IgnorePatterns;
+use crate::includes::{changed_fragments, INCLUDE_KEY};
+use crate::lint::{lint_settings, Severity};
+use crate::listing::{print_paged, ListView};
+use crate::managed::{ensure_unmergeable, managed_settings_path, MANAGED_SOURCE};
+use crate::merge::{
+    env_conflicts, print_env_conflicts, MergeManager, MergeStrategy, MergeSummary,
+    SHORT_SUMMARY_ITEMS,
+};
+use crate::metadata::{metadata_path, Provenance};
+use crate::parallel::parallel_map;
+use crate::pretty::{print_grouped, print_summary, write_pretty, STREAM_THRESHOLD};
+use crate::progress;
+use crate::quarantine::{warn_corrupt, warn_unreadable, CorruptFiles};
+use crate::recording;
+use crate::scaffold;
+use crate::signing::signature_path;
 use crate::state::State;
 
-#[derive(Debug, Clone)]
+/// Read import input from a file, or from stdin for `None` and `-`
+pub(crate) fn read_import_input(from_file: Option<&str>) -> Result<String> {
+    match from_file {
+        Some(path) if path != "-" => fs::read_to_string(path)
+            .with_context(|| format!("error: failed to read import file {:?}", path)),
+        _ => {
+            use std::io::Read;
+            let mut stdin = std::io::stdin();
+            if stdin.is_terminal() {
+                bail!("error: no input on stdin; pipe JSON into cctx or use --from-file <path>");
+            }
+            let mut buffer = String::new();
+            stdin.read_to_string(&mut buffer)?;
+            Ok(buffer)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, clap::ValueEnum, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum SettingsLevel {
     User,    // ~/.claude/settings.json (default)
     Project, // ./.claude/settings.json (explicit)
@@ -19,16 +65,230 @@ pub struct ContextManager {
     pub claude_settings_path: PathBuf,
     pub state_path: PathBuf,
     pub settings_level: SettingsLevel,
+    pub project_root: PathBuf,
+    pub config: Config,
+    /// Refuse to switch to contexts without a valid signature
+    pub require_signed: bool,
+    /// Refuse every operation that would modify files
+    pub read_only: bool,
+    /// Skip the health check before destructive operations
+    pub force: bool,
+    /// Answer yes to confirmations (unsetting, switching to a very different context)
+    pub assume_yes: bool,
+    /// Treat warnings as errors: lint warnings, budget violations, drift and merge conflicts
+    pub strict: bool,
+    /// Show progress bars and spinners for long operations
+    pub progress: bool,
+    /// Ask on the terminal when a confirmation is needed
+    pub interactive: bool,
+    /// How timestamps are shown
+    pub date_style: DateStyle,
+    /// Options this manager was created with, so managers opened for other
+    /// levels find the same project
+    pub options: ManagerOptions,
 }
 
-impl ContextManager {
-    pub fn new() -> Result<Self> {
-        Self::new_with_level(SettingsLevel::User)
+/// Options controlling how a `ContextManager` locates and initializes its files
+#[derive(Debug, Clone)]
+pub struct ManagerOptions {
+    /// Walk up to the git repo root when resolving project/local levels
+    pub discover: bool,
+    /// Allow creating `.claude/settings` where no `.claude` directory exists yet
+    pub create: bool,
+    /// Named target from the config whose settings.json should be managed instead
+    pub target: Option<String>,
+    /// Only read existing files: never create directories and refuse mutations
+    pub read_only: bool,
+    /// Verify context signatures before switching
+    pub require_signed: bool,
+    /// Proceed with destructive operations even when the health check fails
+    pub force: bool,
+    /// Skip confirmation prompts
+    pub yes: bool,
+    /// Fail on warnings instead of printing them
+    pub strict: bool,
+    /// Show progress bars and spinners for long operations
+    pub progress: bool,
+    /// Ask on the terminal when a confirmation is needed; off for `--serve`,
+    /// where nobody is there to answer
+    pub interactive: bool,
+    /// Timestamp style overriding `ui.date_format` from the config
+    pub date_style: Option<DateStyle>,
+    /// Project directory to use as is, instead of discovering it from the
+    /// working directory
+    pub project_dir: Option<PathBuf>,
+}
+
+impl Default for ManagerOptions {
+    fn default() -> Self {
+        Self {
+            discover: true,
+            create: false,
+            target: None,
+            read_only: false,
+            require_signed: false,
+            force: false,
+            yes: false,
+            strict: false,
+            progress: false,
+            interactive: true,
+            date_style: None,
+            project_dir: None,
+        }
+    }
+}
+
+/// Collections at least this large show a progress bar while being checked
+const PROGRESS_THRESHOLD: usize = 200;
+
+/// Result of checking a context file while listing
+enum ContextCheck {
+    Valid,
+    Corrupt(String),
+    Unreadable(String),
+}
+
+/// Characters Windows does not allow in file names
+const WINDOWS_RESERVED_CHARS: &[char] = &['<', '>', ':', '"', '|', '?', '*'];
+
+/// Device names Windows reserves regardless of extension
+const WINDOWS_RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// A name that matches no listed context, typed so `--serve` can answer 404
+#[derive(Debug)]
+pub struct UnknownContext(pub String);
+
+impl std::fmt::Display for UnknownContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "error: no context exists with the name \"{}\"", self.0)
+    }
+}
+
+impl std::error::Error for UnknownContext {}
+
+/// A context that passed every switch check, with its includes resolved
+pub(crate) struct PreparedContext {
+    pub name: String,
+    /// What gets written to the settings file
+    pub content: String,
+    pub settings: serde_json::Value,
+    /// Fingerprints of the included fragments, by path
+    pub fragments: BTreeMap<String, String>,
+}
+
+/// Check that a context name maps to a single, portable file name.
+///
+/// Names must also work on Windows so context directories can be synced
+/// between machines, so path separators of both kinds are rejected everywhere.
+pub fn is_valid_context_name(name: &str) -> bool {
+    if name.is_empty() || name == "-" || name == "." || name == ".." {
+        return false;
+    }
+    if name.contains('/') || name.contains('\\') {
+        return false;
+    }
+    // Tabs and newlines would break --porcelain output and the fzf picker
+    if name.chars().any(char::is_control) {
+        return false;
+    }
+    if cfg!(windows)
+        && (name.contains(WINDOWS_RESERVED_CHARS)
+            || name.ends_with('.')
+            || name.ends_with(' ')
+            || WINDOWS_RESERVED_NAMES
+                .iter()
+                .any(|reserved| reserved.eq_ignore_ascii_case(name)))
+    {
+        return false;
     }
+    true
+}
+
+/// Resolve the editor command: a per-context override, then $EDITOR, then
+/// $VISUAL, then a platform default
+fn editor_command(preferred: Option<&str>) -> Command {
+    let editor = match preferred {
+        Some(editor) => editor.to_string(),
+        None => std::env::var("EDITOR")
+            .or_else(|_| std::env::var("VISUAL"))
+            .unwrap_or_else(|_| {
+                if cfg!(windows) {
+                    "notepad".to_string()
+                } else {
+                    "vi".to_string()
+                }
+            }),
+    };
+
+    // Editors like "code --wait" carry arguments, but a full path such as
+    // "C:\Program Files\Editor\editor.exe" may itself contain spaces
+    if PathBuf::from(&editor).is_file() {
+        return Command::new(editor);
+    }
+
+    let mut parts = editor.split_whitespace();
+    let mut cmd = Command::new(parts.next().unwrap_or("vi"));
+    cmd.args(parts);
+    cmd
+}
 
-    pub fn new_with_level(level: SettingsLevel) -> Result<Self> {
-        let home_dir = dirs::home_dir().context("Failed to get home directory")?;
-        let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+/// Resolve the directory holding project-level `.claude` settings.
+///
+/// Walks up from the current directory to the enclosing git repository or
+/// worktree root, so cctx behaves the same from any subdirectory. Falls back
+/// to the current directory when discovery is disabled or no repo is found,
+/// and fails when there is no usable current directory.
+pub fn find_project_root(discover: bool) -> Result<PathBuf> {
+    let current_dir = std::env::current_dir().context(
+        "error: cannot determine the current directory; pass --dir <path> to choose the project directory",
+    )?;
+    if !discover {
+        return Ok(current_dir);
+    }
+
+    for dir in current_dir.ancestors() {
+        // `.git` is a directory in a normal checkout and a file in a worktree
+        if dir.join(".git").exists() {
+            return Ok(dir.to_path_buf());
+        }
+    }
+
+    Ok(current_dir)
+}
+
+impl ContextManager {
+    pub fn new_with_options(level: SettingsLevel, options: &ManagerOptions) -> Result<Self> {
+        let home_dir = home_dir()?;
+        let project_root = match &options.project_dir {
+            Some(dir) => std::path::absolute(dir)
+                .ok()
+                .filter(|dir| dir.is_dir())
+                .with_context(|| format!("error: --dir {} is not a directory", dir.display()))?,
+            // The user level does not live in a project, so it keeps working
+            // without a current directory (e.g. when started by a service manager)
+            None => match (find_project_root(options.discover), &level) {
+                (Ok(root), _) => root,
+                (Err(_), SettingsLevel::User) => home_dir.clone(),
+                (Err(e), _) => return Err(e),
+            },
+        };
+        let config = Config::load(&Config::default_path(&home_dir))?;
+
+        // Avoid littering random directories (e.g. $HOME or /tmp) with .claude/settings
+        if !matches!(level, SettingsLevel::User)
+            && !options.create
+            && !options.read_only
+            && !project_root.join(".claude").is_dir()
+        {
+            bail!(
+                "error: no .claude directory found in {}\n\
+                 Run inside a project, or pass --create to initialize one here",
+                project_root.display()
+            );
+        }
 
         let (claude_settings_path, contexts_dir, state_path) = match level {
             SettingsLevel::User => {
@@ -39,14 +299,14 @@ impl ContextManager {
                 (claude_settings_path, contexts_dir, state_path)
             }
             SettingsLevel::Project => {
-                let claude_dir = current_dir.join(".claude");
+                let claude_dir = project_root.join(".claude");
                 let contexts_dir = claude_dir.join("settings");
                 let claude_settings_path = claude_dir.join("settings.json");
                 let state_path = contexts_dir.join(".cctx-state.json");
                 (claude_settings_path, contexts_dir, state_path)
             }
             SettingsLevel::Local => {
-                let claude_dir = current_dir.join(".claude");
+                let claude_dir = project_root.join(".claude");
                 let contexts_dir = claude_dir.join("settings");
                 let claude_settings_path = claude_dir.join("settings.local.json");
                 let state_path = contexts_dir.join(".cctx-state.local.json");
@@ -54,21 +314,51 @@ impl ContextManager {
             }
         };
 
+        // A named target applies contexts to an alternate settings.json and tracks
+        // its own current/previous state
+        let (claude_settings_path, state_path) = if let Some(name) = &options.target {
+            let target = config.targets.get(name).with_context(|| {
+                format!(
+                    "error: no target named \"{}\" in {:?}",
+                    name,
+                    Config::default_path(&home_dir)
+                )
+            })?;
+            (
+                expand_tilde(&target.settings_path, &home_dir),
+                contexts_dir.join(format!(".cctx-state.{name}.json")),
+            )
+        } else {
+            (claude_settings_path, state_path)
+        };
+
         // Create directories if they don't exist
-        fs::create_dir_all(&contexts_dir)?;
+        if !options.read_only {
+            fs::create_dir_all(&contexts_dir)?;
+        }
 
         Ok(Self {
             contexts_dir,
             claude_settings_path,
             state_path,
             settings_level: level,
+            project_root,
+            require_signed: options.require_signed || config.signing.require_signed,
+            read_only: options.read_only,
+            force: options.force,
+            assume_yes: options.yes,
+            strict: options.strict,
+            progress: options.progress,
+            interactive: options.interactive,
+            date_style: options.date_style.unwrap_or(config.ui.date_format),
+            config,
+            options: options.clone(),
         })
     }
 
-    /// Check if project-level contexts are available in current directory
-    pub fn has_project_contexts() -> bool {
-        let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-        let project_contexts_dir = current_dir.join(".claude").join("settings");
+    /// Check if project-level contexts are available in the project root
+    pub fn has_project_contexts(&self) -> bool {
+        let project_contexts_dir = self.project_root.join(".claude").join("settings");
 
         if let Ok(entries) = fs::read_dir(&project_contexts_dir) {
             for entry in entries.flatten() {
@@ -85,33 +375,121 @@ impl ContextManager {
         false
     }
 
-    /// Check if local contexts are available in current directory  
-    pub fn has_local_contexts() -> bool {
-        let current_dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-        current_dir
+    /// Check if local contexts are available in the project root
+    pub fn has_local_contexts(&self) -> bool {
+        self.project_root
             .join(".claude")
             .join("settings.local.json")
             .exists()
     }
 
+    /// Fail with a clear message when a mutating operation runs in read-only mode
+    pub fn ensure_writable(&self, operation: &str) -> Result<()> {
+        if self.read_only {
+            bail!(
+                "error: cannot {} in read-only mode (--read-only or CCTX_READONLY is set)",
+                operation
+            );
+        }
+        Ok(())
+    }
+
+    /// Resolve an alias to its context; real context names win over aliases
+    pub fn resolve_alias(&self, name: &str) -> String {
+        if self.context_path(name).exists() {
+            return name.to_string();
+        }
+        self.aliases()
+            .remove(name)
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// Aliases configured in the config, and those `--dedupe` recorded for this
+    /// level's contexts; a configured alias wins
+    pub(crate) fn aliases(&self) -> BTreeMap<String, String> {
+        let mut aliases = self.level_aliases();
+        aliases.extend(self.config.aliases.clone());
+        aliases
+    }
+
+    /// File a context is stored in: the existing plain or compressed file, or
+    /// the configured format for a context that doesn't exist yet
     pub fn context_path(&self, name: &str) -> PathBuf {
-        self.contexts_dir.join(format!("{name}.json"))
+        let plain = self.contexts_dir.join(format!("{name}.json"));
+        let compressed = compressed_path(&plain);
+        if plain.exists() || (!compressed.exists() && !self.config.compress) {
+            plain
+        } else {
+            compressed
+        }
     }
 
-    fn load_state(&self) -> Result<State> {
+    pub(crate) fn load_state(&self) -> Result<State> {
         State::load(&self.state_path)
     }
 
-    fn save_state(&self, state: &State) -> Result<()> {
-        state.save(&self.state_path)
+    pub(crate) fn save_state(&self, state: &State) -> Result<()> {
+        state.save(&self.state_path)?;
+        if self.config.status_file {
+            self.write_status_file(state)?;
+        }
+        Ok(())
+    }
+
+    /// What tells this level's state file apart from others in the same contexts
+    /// directory: empty for user and project, `.local`, or `.<target>`
+    pub(crate) fn state_suffix(&self) -> &str {
+        let state_name = self
+            .state_path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        state_name
+            .trim_start_matches(".cctx-state")
+            .trim_end_matches(".json")
+    }
+
+    /// Status file next to the state file (`.cctx-state.local.json` -> `.cctx-current.local`)
+    pub fn status_file_path(&self) -> PathBuf {
+        self.contexts_dir
+            .join(format!(".cctx-current{}", self.state_suffix()))
+    }
+
+    /// Write the stable `key=value` status file read by status-line scripts.
+    /// It is replaced atomically, so readers never see a partly written file.
+    fn write_status_file(&self, state: &State) -> Result<()> {
+        let level = format!("{:?}", self.settings_level).to_lowercase();
+        let content = format!(
+            "context={}\nlevel={}\nupdated={}\n",
+            state.current.as_deref().unwrap_or_default(),
+            level,
+            dates::now().to_rfc3339()
+        );
+        write_atomic(&self.status_file_path(), content.as_bytes())
     }
 
     pub fn list_contexts(&self) -> Result<Vec<String>> {
-        let mut contexts = Vec::new();
+        let (contexts, corrupt) = self.scan_contexts()?;
+        for (path, reason) in corrupt {
+            warn_corrupt(&path, &reason);
+        }
+        Ok(contexts)
+    }
+
+    /// Valid context names, and the context files that fail to parse with the reason
+    pub(crate) fn scan_contexts(&self) -> Result<(Vec<String>, CorruptFiles)> {
+        let mut candidates = Vec::new();
+        let ignore = IgnorePatterns::load(&self.contexts_dir);
 
         if let Ok(entries) = fs::read_dir(&self.contexts_dir) {
             for entry in entries {
-                let entry = entry?;
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(e) => {
+                        warn_unreadable(&self.contexts_dir, &e);
+                        continue;
+                    }
+                };
                 let path = entry.path();
 
                 // Skip hidden files and non-JSON files
@@ -119,18 +497,66 @@ impl ContextManager {
                     if filename.starts_with('.') {
                         continue;
                     }
+                    // Matched before parsing so ignored files are never reported
+                    if ignore.is_ignored(filename) {
+                        continue;
+                    }
                 }
 
-                if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                    if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+                if context_name(&path).is_none() {
+                    continue;
+                }
+
+                // Conflicted copies from sync tools are reviewed with --conflicts
+                if path
+                    .file_name()
+                    .and_then(|s| s.to_str())
+                    .and_then(conflict_original)
+                    .is_some()
+                {
+                    continue;
+                }
+
+                candidates.push(path);
+            }
+        }
+
+        // Parsing dominates for large collections, so check files in parallel
+        let bar = progress::bar(
+            self.progress && candidates.len() >= PROGRESS_THRESHOLD,
+            candidates.len(),
+            "Checking contexts",
+        );
+        let checks = parallel_map(&candidates, |path| {
+            let check = match read_settings(path) {
+                Ok(content) => match serde_json::from_str::<serde_json::Value>(&content) {
+                    Ok(_) => ContextCheck::Valid,
+                    Err(e) => ContextCheck::Corrupt(e.to_string()),
+                },
+                Err(e) => ContextCheck::Unreadable(e.to_string()),
+            };
+            bar.inc(1);
+            check
+        });
+        bar.finish_and_clear();
+
+        // A single broken context must not break every command
+        let mut contexts = Vec::new();
+        let mut corrupt = Vec::new();
+        for (path, check) in candidates.into_iter().zip(checks) {
+            match check {
+                ContextCheck::Valid => {
+                    if let Some(name) = context_name(&path) {
                         contexts.push(name.to_string());
                     }
                 }
+                ContextCheck::Corrupt(reason) => corrupt.push((path, reason)),
+                ContextCheck::Unreadable(reason) => warn_unreadable(&path, &reason),
             }
         }
 
         contexts.sort();
-        Ok(contexts)
+        Ok((contexts, corrupt))
     }
 
     pub fn get_current_context(&self) -> Result<Option<String>> {
@@ -138,36 +564,157 @@ impl ContextManager {
         Ok(state.current)
     }
 
+    /// The named context, or the current one when no name is given
+    pub fn named_or_current(&self, name: Option<&str>) -> Result<String> {
+        match name {
+            Some(name) => Ok(name.to_string()),
+            None => self
+                .get_current_context()?
+                .context("error: no current context set"),
+        }
+    }
+
+    /// Whether `name` is a context `list_contexts` would show, without scanning
+    /// and parsing the whole directory
+    pub(crate) fn is_listed_context(&self, name: &str) -> bool {
+        // Names come from the command line and --serve, so never leave the contexts dir
+        if !is_valid_context_name(name) {
+            return false;
+        }
+        let path = self.context_path(name);
+        let Some(file_name) = path.file_name().and_then(|s| s.to_str()) else {
+            return false;
+        };
+        path.parent() == Some(self.contexts_dir.as_path())
+            && path.is_file()
+            && !file_name.starts_with('.')
+            && conflict_original(file_name).is_none()
+            && !IgnorePatterns::load(&self.contexts_dir).is_ignored(file_name)
+    }
+
     pub fn switch_context(&self, name: &str) -> Result<()> {
-        let contexts = self.list_contexts()?;
-        if !contexts.contains(&name.to_string()) {
-            bail!("error: no context exists with the name \"{}\"", name);
+        let state = self.load_state()?;
+        self.switch_context_from(name, state)
+    }
+
+    /// Resolve `name` and run every check a context must pass before it is
+    /// applied: signature, requirements, intended level, review, lint and
+    /// permission budget. Shared by switching and `--run`.
+    pub(crate) fn prepare_context(&self, name: &str) -> Result<PreparedContext> {
+        let name = self.resolve_alias(name);
+
+        if !self.is_listed_context(&name) {
+            return Err(UnknownContext(name).into());
         }
 
-        let mut state = self.load_state()?;
-        state.set_current(name.to_string());
+        if self.require_signed {
+            self.verify_context(&name)?;
+        }
+        self.check_requirements(&name)?;
+        self.check_intended_level(&name)?;
+        self.check_review(&name)?;
+
+        // Refuse anything that would leave Claude with a broken configuration
+        let mut content = read_settings(&self.context_path(&name))?;
+        let mut settings: serde_json::Value =
+            serde_json::from_str(&content).with_context(|| {
+                format!("error: context \"{name}\" is not valid JSON; run `cctx --lint {name}`")
+            })?;
+        if self.require_signed && settings.get(INCLUDE_KEY).is_some() {
+            bail!(
+                "error: context \"{name}\" includes fragments, which --require-signed cannot verify; inline them into the signed context"
+            );
+        }
+        let fragments = self.resolve_includes(&mut settings)?;
+        if !fragments.is_empty() {
+            content = serde_json::to_string_pretty(&settings)?;
+        }
+        if self.config.lint.check_on_switch {
+            let errors = lint_settings(&settings)
+                .into_iter()
+                .filter(|issue| self.strict || issue.severity == Severity::Error)
+                .count();
+            if errors > 0 {
+                bail!(
+                    "error: context \"{}\" has {} lint {}; run `cctx --lint {}` for details",
+                    name,
+                    errors,
+                    if self.strict { "issues" } else { "errors" },
+                    name
+                );
+            }
+        }
+        self.enforce_permission_budget(&name, &settings)?;
 
-        // Copy context settings to Claude settings
-        let context_path = self.context_path(name);
-        let content = fs::read_to_string(&context_path)?;
+        Ok(PreparedContext {
+            name,
+            content,
+            settings,
+            fragments,
+        })
+    }
+
+    /// Switch with state the caller already loaded. Settings that already match
+    /// are not rewritten (or backed up), and state is only saved when it changes.
+    fn switch_context_from(&self, name: &str, mut state: State) -> Result<()> {
+        self.ensure_writable("switch contexts")?;
+
+        let PreparedContext {
+            name,
+            content,
+            settings,
+            fragments,
+        } = self.prepare_context(name)?;
+        let name = &name;
+
+        let live = fs::read(&self.claude_settings_path).ok();
+        let settings_unchanged = live.as_deref() == Some(content.as_bytes());
+        let already_current = state.current.as_deref() == Some(name.as_str());
+
+        for fragment in changed_fragments(&state.fragments, &fragments) {
+            eprintln!("warning: fragment {fragment} changed since it was last applied");
+        }
+        let fragments_unchanged = fragments
+            .iter()
+            .all(|(path, hash)| state.fragments.get(path) == Some(hash));
+
+        if !settings_unchanged {
+            // Unreadable live settings have nothing worth comparing against
+            let live =
+                live.and_then(|live| serde_json::from_slice::<serde_json::Value>(&live).ok());
+            if let Some(live) = live {
+                self.confirm_dissimilar_switch(name, &live, &settings)?;
+            }
 
-        // Create .claude directory if it doesn't exist
-        if let Some(parent) = self.claude_settings_path.parent() {
-            fs::create_dir_all(parent)?;
+            self.backup_settings(state.current.as_deref())?;
+
+            // Create .claude directory if it doesn't exist
+            if let Some(parent) = self.claude_settings_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            write_atomic(&self.claude_settings_path, content.as_bytes())?;
         }
 
-        fs::write(&self.claude_settings_path, content)?;
-        self.save_state(&state)?;
+        // State is saved only once the new settings are in place, so a crash
+        // never leaves it naming a context that was not applied. Re-applying
+        // the current context would only move its last-used time.
+        if !(settings_unchanged && already_current && fragments_unchanged) {
+            state.set_current(name.to_string());
+            state.fragments.extend(fragments);
+            self.save_state(&state)?;
+        }
 
         println!("Switched to context \"{}\"", name.green().bold());
+
+        self.warn_permission_budget(name, &settings);
         Ok(())
     }
 
     pub fn switch_to_previous(&self) -> Result<()> {
         let state = self.load_state()?;
 
-        if let Some(previous) = state.previous {
-            self.switch_context(&previous)?;
+        if let Some(previous) = state.previous.clone() {
+            self.switch_context_from(&previous, state)?;
         } else {
             bail!("error: no previous context");
         }
@@ -175,10 +722,12 @@ impl ContextManager {
         Ok(())
     }
 
-    pub fn create_context(&self, name: &str) -> Result<()> {
-        if name.is_empty() || name == "-" || name == "." || name == ".." || name.contains('/') {
-            bail!("error: invalid context name \"{}\"", name);
-        }
+    /// Create a context from the current settings (empty without any). Returns
+    /// the name it was stored under, after naming rules
+    pub fn create_context(&self, name: &str) -> Result<String> {
+        self.ensure_writable("create contexts")?;
+
+        let name = &self.validate_name(name)?;
 
         let contexts = self.list_contexts()?;
         if contexts.contains(&name.to_string()) {
@@ -189,7 +738,14 @@ impl ContextManager {
 
         if self.claude_settings_path.exists() {
             // Copy current Claude settings
-            fs::copy(&self.claude_settings_path, &context_path)?;
+            let content = fs::read_to_string(&self.claude_settings_path)?;
+            write_settings(&context_path, &content)?;
+            // Lets audits tell captured contexts from deliberately authored ones
+            let mut metadata = self.load_metadata(name)?;
+            metadata.provenance = Some(Provenance::captured(&self.claude_settings_path));
+            // A capture belongs to the level it was taken from
+            metadata.level = Some(self.settings_level.clone());
+            self.save_metadata(name, &metadata)?;
             println!(
                 "Context \"{}\" created from current settings",
                 name.green().bold()
@@ -197,17 +753,19 @@ impl ContextManager {
         } else {
             // Create empty settings
             let empty_settings = serde_json::json!({});
-            fs::write(
+            write_settings(
                 &context_path,
-                serde_json::to_string_pretty(&empty_settings)?,
+                &serde_json::to_string_pretty(&empty_settings)?,
             )?;
             println!("Context \"{}\" created (empty)", name.green().bold());
         }
 
-        Ok(())
+        Ok(name.to_string())
     }
 
     pub fn delete_context(&self, name: &str) -> Result<()> {
+        self.ensure_writable("delete contexts")?;
+
         let state = self.load_state()?;
 
         if state.current.as_ref() == Some(&name.to_string()) {
@@ -219,12 +777,21 @@ impl ContextManager {
             bail!("error: no context exists with the name \"{}\"", name);
         }
 
-        fs::remove_file(context_path)?;
+        fs::remove_file(&context_path)?;
+        let sig_path = signature_path(&context_path);
+        if sig_path.exists() {
+            fs::remove_file(sig_path)?;
+        }
+        let meta_path = metadata_path(&context_path);
+        if meta_path.exists() {
+            fs::remove_file(meta_path)?;
+        }
 
-        // Update state if this was the previous context
-        if state.previous.as_ref() == Some(&name.to_string()) {
+        // Drop it as previous context and from usage data
+        if state.previous.as_ref() == Some(&name.to_string()) || state.last_used.contains_key(name)
+        {
             let mut new_state = state;
-            new_state.previous = None;
+            new_state.forget(name);
             self.save_state(&new_state)?;
         }
 
@@ -233,14 +800,9 @@ impl ContextManager {
     }
 
     pub fn rename_context(&self, old_name: &str, new_name: &str) -> Result<()> {
-        if new_name.is_empty()
-            || new_name == "-"
-            || new_name == "."
-            || new_name == ".."
-            || new_name.contains('/')
-        {
-            bail!("error: invalid context name \"{}\"", new_name);
-        }
+        self.ensure_writable("rename contexts")?;
+
+        let new_name = &self.validate_name(new_name)?;
 
         let contexts = self.list_contexts()?;
         if !contexts.contains(&old_name.to_string()) {
@@ -252,8 +814,54 @@ impl ContextManager {
         }
 
         let old_path = self.context_path(old_name);
-        let new_path = self.context_path(new_name);
-        fs::rename(old_path, new_path)?;
+        let mut new_path = self.contexts_dir.join(format!("{new_name}.json"));
+        if is_compressed(&old_path) {
+            new_path = compressed_path(&new_path);
+        }
+        fs::rename(&old_path, &new_path)?;
+        let old_sig = signature_path(&old_path);
+        if old_sig.exists() {
+            fs::rename(old_sig, signature_path(&new_path))?;
+        }
+        let old_meta = metadata_path(&old_path);
+        if old_meta.exists() {
+            fs::rename(old_meta, metadata_path(&new_path))?;
+        }
+        let old_snapshots = self.snapshots_dir(old_name);
+        if old_snapshots.exists() {
+            let new_snapshots = self.snapshots_dir(new_name);
+            if !new_snapshots.exists() {
+                fs::rename(old_snapshots, new_snapshots)?;
+            }
+        }
+
+        // Keep unmerge working for contexts that merged from the old name, here
+        // and at the other contexts directory through `<level>:<name>` sources
+        let (prefixes, other_level): (&[&str], _) = match self.settings_level {
+            SettingsLevel::User => (&["user"], SettingsLevel::Project),
+            SettingsLevel::Project | SettingsLevel::Local => {
+                (&["project", "local"], SettingsLevel::User)
+            }
+        };
+        let mut referencing = self
+            .merge_manager()
+            .rename_context(old_name, new_name, prefixes)?;
+        // Outside a project there is no other directory to update
+        if let Ok(other) = ContextManager::new_with_options(other_level, &self.level_options()) {
+            let renames: Vec<(String, String)> = prefixes
+                .iter()
+                .map(|prefix| {
+                    (
+                        format!("{prefix}:{old_name}"),
+                        format!("{prefix}:{new_name}"),
+                    )
+                })
+                .collect();
+            let level = format!("{:?}", other.settings_level).to_lowercase();
+            for context in other.merge_manager().repoint_sources(&renames)? {
+                referencing.push(format!("{level}:{context}"));
+            }
+        }
 
         // Update state if needed
         let mut state = self.load_state()?;
@@ -269,6 +877,11 @@ impl ContextManager {
             updated = true;
         }
 
+        if state.last_used.contains_key(old_name) {
+            state.rename(old_name, new_name);
+            updated = true;
+        }
+
         if updated {
             self.save_state(&state)?;
         }
@@ -278,16 +891,52 @@ impl ContextManager {
             old_name,
             new_name.green().bold()
         );
+        if !referencing.is_empty() {
+            println!(
+                "  Updated merge history references in: {}",
+                referencing.join(", ")
+            );
+        }
         Ok(())
     }
 
-    pub fn show_context(&self, name: &str) -> Result<()> {
+    /// Print a context. On a terminal permissions are grouped by tool unless
+    /// `flat` is set; otherwise the JSON is printed as stored.
+    pub fn show_context(&self, name: &str, summary: bool, flat: bool) -> Result<()> {
+        let name = &self.resolve_alias(name);
         let context_path = self.context_path(name);
         if !context_path.exists() {
             bail!("error: no context exists with the name \"{}\"", name);
         }
 
-        let content = fs::read_to_string(context_path)?;
+        // On stderr so the JSON on stdout can still be redirected to a file
+        if let Some(ownership) = self.ownership(name) {
+            eprintln!("{}", ownership.dimmed());
+        }
+
+        let content = read_settings(&context_path)?;
+        if summary {
+            let json: serde_json::Value = serde_json::from_str(&content)?;
+            print_summary(name, content.len() as u64, &json);
+            return Ok(());
+        }
+
+        if !flat && io::stdout().is_terminal() {
+            let json: serde_json::Value = serde_json::from_str(&content)?;
+            return print_grouped(&json);
+        }
+
+        if content.len() > STREAM_THRESHOLD {
+            // Validate without building a tree, then re-indent the text as it is written
+            serde_json::from_str::<serde::de::IgnoredAny>(&content)?;
+            let mut out = io::BufWriter::new(io::stdout().lock());
+            return match write_pretty(&content, &mut out).and_then(|_| out.flush()) {
+                // `cctx -s huge | head` closes the pipe early
+                Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(()),
+                result => Ok(result?),
+            };
+        }
+
         let json: serde_json::Value = serde_json::from_str(&content)?;
         let pretty = serde_json::to_string_pretty(&json)?;
 
@@ -296,67 +945,336 @@ impl ContextManager {
     }
 
     pub fn edit_context(&self, name: &str) -> Result<()> {
+        self.ensure_writable("edit contexts")?;
+        let name = &self.resolve_alias(name);
+
         let context_path = self.context_path(name);
         if !context_path.exists() {
             bail!("error: no context exists with the name \"{}\"", name);
         }
 
-        let editor = std::env::var("EDITOR")
-            .or_else(|_| std::env::var("VISUAL"))
-            .unwrap_or_else(|_| "vi".to_string());
+        // Empty contexts are edited as a commented scaffold and compressed ones as
+        // plain JSON; both drafts are converted back when the editor exits
+        let content = read_settings(&context_path)?;
+        let draft_content = if scaffold::is_blank(&content) {
+            Some(scaffold::scaffold(name))
+        } else if is_compressed(&context_path) {
+            Some(content.clone())
+        } else {
+            None
+        };
+        let draft = match draft_content {
+            Some(draft_content) => {
+                let draft = self.contexts_dir.join(format!(".{name}.edit.jsonc"));
+                fs::write(&draft, draft_content)?;
+                Some(draft)
+            }
+            None => None,
+        };
 
-        let status = Command::new(&editor).arg(&context_path).status()?;
+        let metadata = self.load_metadata(name)?;
+        let status = editor_command(metadata.editor.as_deref())
+            .args(&metadata.editor_args)
+            .arg(draft.as_ref().unwrap_or(&context_path))
+            .status()?;
 
         if !status.success() {
+            if let Some(draft) = &draft {
+                fs::remove_file(draft)?;
+            }
             bail!("error: editor exited with non-zero status");
         }
 
+        if let Some(draft) = draft {
+            let edited = fs::read_to_string(&draft)?;
+            let json: serde_json::Value = serde_json::from_str(&scaffold::strip_jsonc(&edited))
+                .with_context(|| {
+                    format!(
+                        "error: edited context is not valid JSON; your draft was kept at {}",
+                        draft.display()
+                    )
+                })?;
+            self.ensure_protected_kept(name, &json)
+                .with_context(|| format!("error: your draft was kept at {}", draft.display()))?;
+            write_settings(&context_path, &serde_json::to_string_pretty(&json)?)?;
+            fs::remove_file(&draft)?;
+        } else {
+            // Edited in place, so put the previous settings back on a violation
+            let edited = read_settings(&context_path)?;
+            if let Ok(json) = serde_json::from_str(&edited) {
+                if let Err(err) = self.ensure_protected_kept(name, &json) {
+                    write_settings(&context_path, &content)?;
+                    return Err(err.context("error: the edit was reverted"));
+                }
+            }
+            recording::record_change(&context_path, Some(&content), &edited)?;
+        }
+
         Ok(())
     }
 
     pub fn export_context(&self, name: &str) -> Result<()> {
+        let name = &self.resolve_alias(name);
         let context_path = self.context_path(name);
         if !context_path.exists() {
             bail!("error: no context exists with the name \"{}\"", name);
         }
 
-        let content = fs::read_to_string(context_path)?;
+        let content = read_settings(&context_path)?;
         print!("{content}");
         Ok(())
     }
 
-    pub fn import_context(&self, name: &str) -> Result<()> {
-        if name.is_empty() || name == "-" || name == "." || name == ".." || name.contains('/') {
-            bail!("error: invalid context name \"{}\"", name);
+    /// Import one or more contexts from stdin or a file.
+    ///
+    /// A single JSON document is stored verbatim as `name`. A stream of
+    /// documents (e.g. JSON Lines) is stored as `name-1`, `name-2`, ...
+    pub fn import_context(
+        &self,
+        name: &str,
+        suffix: Option<&str>,
+        from_file: Option<&str>,
+    ) -> Result<()> {
+        self.ensure_writable("import contexts")?;
+
+        let name = &self.validate_name(name)?;
+        let buffer = read_import_input(from_file)?;
+        self.import_documents(name, suffix, &buffer)?;
+        Ok(())
+    }
+
+    /// Prepare `--import --merge`: imported JSON is merged into an existing context
+    /// instead of refusing the name. Returns the context to merge `buffer` into, or
+    /// `None` when the name did not exist yet and was imported as usual.
+    pub fn import_merge_target(&self, name: &str, buffer: &str) -> Result<Option<String>> {
+        self.ensure_writable("import contexts")?;
+
+        let name = self.resolve_alias(name);
+        if !self.list_contexts()?.contains(&name) {
+            let name = &self.validate_name(&name)?;
+            self.import_documents(name, None, buffer)?;
+            return Ok(None);
         }
 
-        let contexts = self.list_contexts()?;
-        if contexts.contains(&name.to_string()) {
-            bail!("error: context \"{}\" already exists", name);
+        if buffer.trim().is_empty() {
+            bail!("error: import input is empty; expected a JSON object");
+        }
+        let documents = serde_json::Deserializer::from_str(buffer)
+            .into_iter::<serde::de::IgnoredAny>()
+            .count();
+        if documents != 1 {
+            bail!(
+                "error: --import --merge expects one JSON object, got {} documents",
+                documents
+            );
         }
 
-        use std::io::Read;
-        let mut buffer = String::new();
-        std::io::stdin().read_to_string(&mut buffer)?;
+        Ok(Some(name))
+    }
+
+    /// Create a context from a single JSON object on stdin (`-n <name> --stdin`).
+    /// Returns the name it was stored under.
+    pub fn create_context_from_stdin(&self, name: &str) -> Result<String> {
+        self.ensure_writable("create contexts")?;
 
-        // Validate JSON
-        let _: serde_json::Value =
+        let name = &self.validate_name(name)?;
+        if self.list_contexts()?.contains(name) {
+            bail!("error: context \"{}\" already exists", name);
+        }
+
+        let buffer = read_import_input(None)?;
+        let settings: serde_json::Value =
             serde_json::from_str(&buffer).context("error: invalid JSON input")?;
+        if let Some(issue) = lint_settings(&settings)
+            .into_iter()
+            .find(|issue| issue.severity == Severity::Error)
+        {
+            bail!("error: {}", issue.message);
+        }
 
-        let context_path = self.context_path(name);
-        fs::write(&context_path, buffer)?;
+        write_settings(&self.context_path(name), &buffer)?;
+        println!("Context \"{}\" created from stdin", name.green().bold());
+        Ok(name.to_string())
+    }
+
+    /// Validate imported text and store each JSON document it contains.
+    ///
+    /// Returns the names the documents were stored under (skipped ones are omitted).
+    pub(crate) fn import_documents(
+        &self,
+        name: &str,
+        suffix: Option<&str>,
+        buffer: &str,
+    ) -> Result<Vec<String>> {
+        if buffer.trim().is_empty() {
+            bail!("error: import input is empty; expected a JSON object");
+        }
+
+        let documents = serde_json::Deserializer::from_str(buffer)
+            .into_iter::<serde_json::Value>()
+            .collect::<Result<Vec<_>, _>>()
+            .context("error: invalid JSON input")?;
+
+        if documents.len() == 1 {
+            return Ok(self
+                .write_import(name, suffix, buffer)?
+                .into_iter()
+                .collect());
+        }
+
+        let mut imported = Vec::new();
+        for (i, document) in documents.iter().enumerate() {
+            let doc_name = format!("{}-{}", name, i + 1);
+            imported.extend(self.write_import(
+                &doc_name,
+                suffix,
+                &serde_json::to_string_pretty(document)?,
+            )?);
+        }
+
+        Ok(imported)
+    }
+
+    /// Store imported content, resolving name collisions first
+    fn write_import(
+        &self,
+        name: &str,
+        suffix: Option<&str>,
+        content: &str,
+    ) -> Result<Option<String>> {
+        let Some(name) = self.resolve_import_name(name, suffix)? else {
+            println!("Skipped import of \"{}\"", name.yellow());
+            return Ok(None);
+        };
+
+        // An overwritten context keeps what the managed policy requires
+        self.ensure_protected_kept(&name, &serde_json::from_str(content)?)?;
+        write_settings(&self.context_path(&name), content)?;
 
         println!("Context \"{}\" imported", name.green().bold());
+        Ok(Some(name))
+    }
+
+    /// Pick the name an import is written to when `name` may already exist.
+    ///
+    /// With a suffix the import is renamed automatically (`name<suffix>`,
+    /// `name<suffix>-2`, ...), and the new name must be a valid context name.
+    /// Otherwise the user is asked to rename, overwrite
+    /// or skip when a terminal is available. Returns `None` to skip.
+    fn resolve_import_name(&self, name: &str, suffix: Option<&str>) -> Result<Option<String>> {
+        let contexts = self.list_contexts()?;
+        if !contexts.contains(&name.to_string()) {
+            return Ok(Some(name.to_string()));
+        }
+
+        if let Some(suffix) = suffix {
+            let base = format!("{name}{suffix}");
+            let mut candidate = base.clone();
+            let mut counter = 2;
+            while contexts.contains(&candidate) {
+                candidate = format!("{base}-{counter}");
+                counter += 1;
+            }
+            return Ok(Some(self.validate_name(&candidate)?));
+        }
+
+        if !std::io::IsTerminal::is_terminal(&std::io::stderr()) {
+            bail!(
+                "error: context \"{}\" already exists (use --suffix to rename automatically)",
+                name
+            );
+        }
+
+        self.prompt_import_collision(name, &contexts)
+    }
+
+    /// Whether the live settings file was put there by cctx: a context is
+    /// current, or the file matches one of the contexts
+    fn settings_look_managed(&self, state: &State) -> Result<bool> {
+        if state.current.is_some() {
+            return Ok(true);
+        }
+        let parse = |path: &std::path::Path| -> Option<serde_json::Value> {
+            serde_json::from_str(&read_settings(path).ok()?).ok()
+        };
+        let Some(live) = parse(&self.claude_settings_path) else {
+            return Ok(false);
+        };
+        Ok(self
+            .list_contexts()?
+            .iter()
+            .any(|name| parse(&self.context_path(name)).as_ref() == Some(&live)))
+    }
+
+    /// Project and local settings files are often written by hand, so removing
+    /// one names the exact file and needs confirmation (or `yes`)
+    fn confirm_unset(&self, state: &State) -> Result<()> {
+        if matches!(self.settings_level, SettingsLevel::User) || !self.claude_settings_path.exists()
+        {
+            return Ok(());
+        }
+
+        if !self.force && !self.settings_look_managed(state)? {
+            bail!(
+                "error: {:?} does not look managed by cctx (no current context and it matches no context); use --force to remove it anyway",
+                self.claude_settings_path
+            );
+        }
+        if self.assume_yes {
+            return Ok(());
+        }
+        if !io::stdin().is_terminal() {
+            bail!(
+                "error: refusing to remove {:?} without confirmation; pass --yes",
+                self.claude_settings_path
+            );
+        }
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(format!(
+                "Remove {}? (a backup is kept)",
+                self.claude_settings_path.display()
+            ))
+            .default(false)
+            .interact_opt()?
+            .unwrap_or(false);
+        if !confirmed {
+            bail!("error: unset cancelled");
+        }
         Ok(())
     }
 
+    /// Check the store before unsetting. A deleted active context is what makes
+    /// unsetting necessary, so that alone does not stop it.
+    fn check_health_for_unset(&self, state: &State) -> Result<()> {
+        match &state.current {
+            Some(current) if !self.context_path(current).exists() => {
+                eprintln!(
+                    "{} Context \"{}\" no longer exists; unsetting it",
+                    "⚠️".yellow(),
+                    current
+                );
+                Ok(())
+            }
+            _ => self.check_health("unset the current context"),
+        }
+    }
+
     pub fn unset_context(&self) -> Result<()> {
+        self.ensure_writable("unset the current context")?;
+
+        let mut state = self.load_state()?;
+        self.check_health_for_unset(&state)?;
+        self.confirm_unset(&state)?;
         if self.claude_settings_path.exists() {
+            self.backup_settings(state.current.as_deref())?;
             fs::remove_file(&self.claude_settings_path)?;
         }
 
-        let mut state = self.load_state()?;
-        if let Some(_current) = state.unset_current() {
+        if let Some(current) = state.unset_current() {
+            // A deleted context cannot be switched back to with `cctx -`
+            if !self.context_path(&current).exists() {
+                state.forget(&current);
+            }
             self.save_state(&state)?;
         }
 
@@ -364,7 +1282,38 @@ impl ContextManager {
         Ok(())
     }
 
-    pub fn list_contexts_with_current(&self, quiet: bool) -> Result<()> {
+    /// Unset the current context and re-apply the one active before it.
+    ///
+    /// The unset context becomes the previous one, so `cctx -` returns to it.
+    pub fn unset_and_restore_previous(&self) -> Result<()> {
+        self.ensure_writable("unset the current context")?;
+
+        let state = self.load_state()?;
+        self.check_health_for_unset(&state)?;
+        let Some(previous) = state.previous.clone() else {
+            bail!("error: no previous context to restore; use `cctx -u` to just unset");
+        };
+        if state.current.as_deref() == Some(previous.as_str()) {
+            bail!(
+                "error: the previous context \"{}\" is already current",
+                previous
+            );
+        }
+
+        let unset = state.current.clone();
+        self.switch_context_from(&previous, state)?;
+        if let Some(unset) = unset {
+            println!("Unset \"{}\"; run `cctx -` to return to it", unset.yellow());
+        }
+        Ok(())
+    }
+
+    pub fn list_contexts_with_current(
+        &self,
+        quiet: bool,
+        filter: &ContextFilter,
+        view: &ListView,
+    ) -> Result<()> {
         let contexts = self.list_contexts()?;
         let current = self.get_current_context()?;
 
@@ -376,65 +1325,153 @@ impl ContextManager {
             return Ok(());
         }
 
+        let emoji = self.config.ui.emoji;
+        let hint = if emoji { "💡" } else { "hint:" };
+
         // Show helpful information for user-level contexts
         if matches!(self.settings_level, SettingsLevel::User) {
             // Show available project contexts as suggestion
-            if Self::has_project_contexts() {
+            if self.has_project_contexts() {
                 println!(
                     "{} Project contexts available: run 'cctx --in-project' to manage",
-                    "💡".yellow()
+                    hint.yellow()
                 );
             }
-            if Self::has_local_contexts() {
+            if self.has_local_contexts() {
                 println!(
                     "{} Local contexts available: run 'cctx --local' to manage",
-                    "💡".yellow()
+                    hint.yellow()
+                );
+            }
+            let legacy = self.legacy_context_count();
+            if legacy > 0 {
+                println!(
+                    "{} {} contexts found in a legacy layout: run 'cctx --migrate' to move them here",
+                    hint.yellow(),
+                    legacy
                 );
             }
         }
 
         // Show current settings level (condensed)
         let level_emoji = match self.settings_level {
-            SettingsLevel::User => "👤",
-            SettingsLevel::Project => "📁",
-            SettingsLevel::Local => "💻",
+            _ if !emoji => "",
+            SettingsLevel::User => "👤 ",
+            SettingsLevel::Project => "📁 ",
+            SettingsLevel::Local => "💻 ",
         };
 
         if contexts.is_empty() {
             println!(
-                "{} {} contexts: No contexts found. Create one with: cctx -n <name>",
+                "{}{} contexts: No contexts found. Create one with: cctx -n <name>",
                 level_emoji,
                 format!("{:?}", self.settings_level).cyan()
             );
             return Ok(());
         }
 
-        println!(
-            "{} {} contexts:",
+        let contexts = self.filter_contexts(contexts, filter);
+        if contexts.is_empty() {
+            println!(
+                "{}{} contexts: none match {}",
+                level_emoji,
+                format!("{:?}", self.settings_level).cyan(),
+                filter.describe()
+            );
+            return Ok(());
+        }
+
+        let (contexts, footer) = view.paginate(contexts)?;
+        let mut lines = vec![format!(
+            "{}{} contexts:",
             level_emoji,
             format!("{:?}", self.settings_level).cyan().bold()
-        );
+        )];
+
+        if !view.columns.is_empty() {
+            lines.extend(self.column_lines(&contexts, current.as_ref(), &view.columns)?);
+            lines.extend(footer.map(|footer| footer.dimmed().to_string()));
+            return print_paged(&lines, view.pager);
+        }
 
         // List contexts with current highlighted
+        let badges = self.lint_badges(&contexts);
         for ctx in contexts {
+            let badge = badges
+                .get(&ctx)
+                .map(|badge| format!("{} ", badge.marker()))
+                .unwrap_or_default();
+            let ownership = self
+                .ownership(&ctx)
+                .map(|summary| format!(" ({summary})").dimmed().to_string())
+                .unwrap_or_default();
             if Some(&ctx) == current.as_ref() {
-                println!("  {} {}", ctx.green().bold(), "(current)".dimmed());
+                lines.push(format!(
+                    "  {}{} {}{}",
+                    badge,
+                    ctx.green().bold(),
+                    "(current)".dimmed(),
+                    ownership
+                ));
             } else {
-                println!("  {ctx}");
+                lines.push(format!("  {badge}{ctx}{ownership}"));
             }
         }
+        lines.extend(footer.map(|footer| footer.dimmed().to_string()));
+
+        print_paged(&lines, view.pager)
+    }
 
+    /// Machine-readable listing: one `<name>\t<current|previous|->` line per context
+    pub fn print_porcelain_list(&self, filter: &ContextFilter) -> Result<()> {
+        let state = self.load_state()?;
+        for ctx in self.filter_contexts(self.list_contexts()?, filter) {
+            let status = if state.current.as_ref() == Some(&ctx) {
+                "current"
+            } else if state.previous.as_ref() == Some(&ctx) {
+                "previous"
+            } else {
+                "-"
+            };
+            println!("{ctx}\t{status}");
+        }
         Ok(())
     }
 
-    /// Merge permissions from another context or settings file
-    pub fn merge_from(&self, target_context: &str, source: &str) -> Result<()> {
-        // Load target context
-        let target_path = if target_context == "current" {
+    /// Merge manager for this level, honoring configured array paths and strategies
+    ///
+    /// Its history belongs to context files, which the project and local levels share.
+    pub fn merge_manager(&self) -> MergeManager {
+        let manager = MergeManager::new(self.contexts_dir.clone())
+            .with_strategies(self.config.merge.strategies.clone())
+            .with_date_style(self.date_style)
+            .with_emoji(self.config.ui.emoji)
+            .with_retention(self.config.merge.history);
+        match &self.config.merge.array_paths {
+            Some(paths) => manager.with_array_paths(paths.clone()),
+            None => manager,
+        }
+    }
+
+    /// Merge manager for a merge target: merges into "current" change this level's
+    /// live settings file, so their history is kept per level rather than with
+    /// the context file
+    fn target_merge_manager(&self, target_context: &str) -> MergeManager {
+        let manager = self.merge_manager();
+        if target_context == "current" {
+            manager.with_history_suffix(self.state_suffix())
+        } else {
+            manager
+        }
+    }
+
+    /// Resolve the file a merge writes into ("current" means the active settings file)
+    pub(crate) fn merge_target_path(&self, target_context: &str) -> Result<PathBuf> {
+        if target_context == "current" {
             if !self.claude_settings_path.exists() {
                 bail!("error: no current context is set");
             }
-            self.claude_settings_path.clone()
+            Ok(self.claude_settings_path.clone())
         } else {
             let path = self.context_path(target_context);
             if !path.exists() {
@@ -443,46 +1480,174 @@ impl ContextManager {
                     target_context
                 );
             }
-            path
+            Ok(path)
+        }
+    }
+
+    /// Options for a read-only manager at another level of the same project,
+    /// keeping `--dir` and discovery but not a named target
+    fn level_options(&self) -> ManagerOptions {
+        ManagerOptions {
+            read_only: true,
+            target: None,
+            ..self.options.clone()
+        }
+    }
+
+    /// Live settings file a merge source names: "current" for this level's, and
+    /// "user", "project" or "local" for that level's. A context at this level
+    /// named "user", "project" or "local" takes precedence.
+    pub(crate) fn live_settings_source(&self, source: &str) -> Result<Option<PathBuf>> {
+        let level = match source {
+            "current" => return Ok(Some(self.claude_settings_path.clone())),
+            MANAGED_SOURCE if !self.context_path(source).exists() => {
+                return Ok(Some(managed_settings_path()))
+            }
+            "user" | "project" | "local" if self.context_path(source).exists() => return Ok(None),
+            "user" => SettingsLevel::User,
+            "project" => SettingsLevel::Project,
+            "local" => SettingsLevel::Local,
+            _ => return Ok(None),
+        };
+        let options = self.level_options();
+        let manager = ContextManager::new_with_options(level, &options)?;
+        Ok(Some(manager.claude_settings_path))
+    }
+
+    /// Merging the live settings file into itself would only duplicate history
+    fn ensure_distinct_merge(&self, target_context: &str, source: &str) -> Result<()> {
+        if target_context == "current"
+            && self.live_settings_source(source)?.as_ref() == Some(&self.claude_settings_path)
+        {
+            bail!("error: cannot merge the live settings file into itself; name a target context");
+        }
+        Ok(())
+    }
+
+    /// Context named `<level>:<name>` at another settings level, unless a context
+    /// at this level has that exact name
+    fn level_qualified_source(&self, source: &str) -> Option<(SettingsLevel, String)> {
+        let (level, name) = source.split_once(':')?;
+        let level = match level {
+            "user" => SettingsLevel::User,
+            "project" => SettingsLevel::Project,
+            "local" => SettingsLevel::Local,
+            _ => return None,
         };
+        (!self.context_path(source).exists()).then(|| (level, name.to_string()))
+    }
 
-        // Load source settings
-        let source_content = if source == "user" {
-            // Merge from user-level settings.json
-            let home_dir = dirs::home_dir().context("Failed to get home directory")?;
-            let user_settings = home_dir.join(".claude").join("settings.json");
-            if !user_settings.exists() {
-                bail!("error: user settings file not found at {:?}", user_settings);
+    /// Read a merge source: "current" (this level's live settings file), "user",
+    /// "project" or "local" (that level's live settings file), a path ending in
+    /// .json, `user:`/`project:`/`local:` followed by a context name at that
+    /// level, or a context name
+    fn read_merge_source(&self, source: &str) -> Result<String> {
+        if let Some(live_path) = self.live_settings_source(source)? {
+            // Merge from a live settings file, including changes made since switching
+            if !live_path.exists() {
+                bail!("error: no settings file at {:?} to merge from", live_path);
             }
-            fs::read_to_string(&user_settings)?
+            read_settings(&live_path)
+        } else if let Some((level, name)) = self.level_qualified_source(source) {
+            // Merge from a context at another level without knowing its path
+            let options = self.level_options();
+            let manager = ContextManager::new_with_options(level.clone(), &options)?;
+            let name = manager.resolve_alias(&name);
+            let source_path = manager.context_path(&name);
+            if !source_path.exists() {
+                bail!(
+                    "error: no {} context exists with the name \"{}\"",
+                    format!("{level:?}").to_lowercase(),
+                    name
+                );
+            }
+            read_settings(&source_path)
         } else if source.ends_with(".json") {
             // Merge from a file path
             let source_path = PathBuf::from(source);
             if !source_path.exists() {
                 bail!("error: source file not found at {:?}", source_path);
             }
-            fs::read_to_string(&source_path)?
+            Ok(fs::read_to_string(&source_path)?)
         } else {
             // Merge from another context
+            let source = &self.resolve_alias(source);
             let source_path = self.context_path(source);
             if !source_path.exists() {
                 bail!("error: no context exists with the name \"{}\"", source);
             }
-            fs::read_to_string(&source_path)?
+            read_settings(&source_path)
+        }
+    }
+
+    /// Compute the items a merge would add without writing anything
+    pub fn preview_merge(
+        &self,
+        target_context: &str,
+        source: &str,
+        full: bool,
+    ) -> Result<Vec<String>> {
+        self.ensure_distinct_merge(target_context, source)?;
+        let target_path = self.merge_target_path(target_context)?;
+        let mut target_json: serde_json::Value =
+            serde_json::from_str(&read_settings(&target_path)?)?;
+        let source_json: serde_json::Value =
+            serde_json::from_str(&self.read_merge_source(source)?)?;
+        self.ensure_mergeable(target_context, source, &target_json, &source_json)?;
+
+        let merge_manager = self.target_merge_manager(target_context);
+        let entry = if full {
+            merge_manager.merge_full(&mut target_json, &source_json, source)?
+        } else {
+            merge_manager.merge_permissions(&mut target_json, &source_json, source)?
         };
 
+        Ok(entry.merged_items)
+    }
+
+    /// Merge permissions from another context or settings file
+    pub fn merge_from(
+        &self,
+        target_context: &str,
+        source: &str,
+        show_diff: bool,
+        summary: MergeSummary,
+    ) -> Result<()> {
+        self.ensure_writable("merge settings")?;
+        self.ensure_distinct_merge(target_context, source)?;
+
+        let source_content = self.read_merge_source(source)?;
+        self.merge_content(target_context, source, &source_content, show_diff, summary)
+    }
+
+    /// Merge permissions from settings that were already read, recording them
+    /// in the merge history under `source`
+    pub(crate) fn merge_content(
+        &self,
+        target_context: &str,
+        source: &str,
+        source_content: &str,
+        show_diff: bool,
+        summary: MergeSummary,
+    ) -> Result<()> {
+        let target_path = self.merge_target_path(target_context)?;
+
         // Parse JSON
         let mut target_json: serde_json::Value =
-            serde_json::from_str(&fs::read_to_string(&target_path)?)?;
-        let source_json: serde_json::Value = serde_json::from_str(&source_content)?;
+            serde_json::from_str(&read_settings(&target_path)?)?;
+        let before = serde_json::to_string_pretty(&target_json)?;
+        let source_json: serde_json::Value = serde_json::from_str(source_content)?;
+        self.ensure_mergeable(target_context, source, &target_json, &source_json)?;
 
         // Perform merge
-        let merge_manager = MergeManager::new(self.contexts_dir.clone());
-        let history_entry =
+        let merge_manager = self.target_merge_manager(target_context);
+        let mut history_entry =
             merge_manager.merge_permissions(&mut target_json, &source_json, source)?;
+        history_entry.protected = source == MANAGED_SOURCE;
 
         // Save updated target
-        fs::write(&target_path, serde_json::to_string_pretty(&target_json)?)?;
+        let after = serde_json::to_string_pretty(&target_json)?;
+        write_settings(&target_path, &after)?;
 
         // Update history
         let context_name = if target_context == "current" {
@@ -503,15 +1668,23 @@ impl ContextManager {
             target_context.green().bold()
         );
 
-        if !history_entry.merged_items.is_empty() {
+        if show_diff {
+            println!();
+            print_unified_diff(&before, &after, "before", "after");
+        } else if summary != MergeSummary::Counts && !history_entry.merged_items.is_empty() {
             println!("\n📋 Merged items:");
-            for (i, item) in history_entry.merged_items.iter().enumerate() {
-                if i < 5 {
-                    println!("  • {}", item);
-                } else if i == 5 {
-                    println!("  ... and {} more", history_entry.merged_items.len() - 5);
-                    break;
-                }
+            let shown = match summary {
+                MergeSummary::Full => history_entry.merged_items.len(),
+                _ => SHORT_SUMMARY_ITEMS,
+            };
+            for item in history_entry.merged_items.iter().take(shown) {
+                println!("  • {}", item);
+            }
+            if history_entry.merged_items.len() > shown {
+                println!(
+                    "  ... and {} more (--verbose lists all)",
+                    history_entry.merged_items.len() - shown
+                );
             }
         }
 
@@ -519,27 +1692,20 @@ impl ContextManager {
     }
 
     /// Remove previously merged permissions
-    pub fn unmerge_from(&self, target_context: &str, source: &str) -> Result<()> {
+    pub fn unmerge_from(&self, target_context: &str, source: &str, show_diff: bool) -> Result<()> {
+        self.ensure_writable("unmerge settings")?;
+
         // Load target context
-        let target_path = if target_context == "current" {
-            if !self.claude_settings_path.exists() {
-                bail!("error: no current context is set");
-            }
-            self.claude_settings_path.clone()
-        } else {
-            let path = self.context_path(target_context);
-            if !path.exists() {
-                bail!(
-                    "error: no context exists with the name \"{}\"",
-                    target_context
-                );
-            }
-            path
-        };
+        let target_path = self.merge_target_path(target_context)?;
 
         // Load and parse target JSON
         let mut target_json: serde_json::Value =
-            serde_json::from_str(&fs::read_to_string(&target_path)?)?;
+            serde_json::from_str(&read_settings(&target_path)?)?;
+        self.ensure_canonical_shape(
+            &format!("unmerge target \"{target_context}\""),
+            &target_json,
+        )?;
+        let before = serde_json::to_string_pretty(&target_json)?;
 
         // Get context name for history
         let context_name = if target_context == "current" {
@@ -550,11 +1716,13 @@ impl ContextManager {
         };
 
         // Perform unmerge
-        let merge_manager = MergeManager::new(self.contexts_dir.clone());
+        let merge_manager = self.target_merge_manager(target_context);
+        ensure_unmergeable(&merge_manager, &context_name, source)?;
         merge_manager.unmerge_permissions(&mut target_json, &context_name, source)?;
 
         // Save updated target
-        fs::write(&target_path, serde_json::to_string_pretty(&target_json)?)?;
+        let after = serde_json::to_string_pretty(&target_json)?;
+        write_settings(&target_path, &after)?;
 
         println!(
             "✅ Removed all permissions previously merged from '{}' in '{}'",
@@ -562,64 +1730,77 @@ impl ContextManager {
             target_context.green().bold()
         );
 
+        if show_diff {
+            println!();
+            print_unified_diff(&before, &after, "before", "after");
+        }
+
         Ok(())
     }
 
     /// Merge all settings from another context or settings file (full merge)
-    pub fn merge_from_full(&self, target_context: &str, source: &str) -> Result<()> {
-        // Load target context
-        let target_path = if target_context == "current" {
-            if !self.claude_settings_path.exists() {
-                bail!("error: no current context is set");
-            }
-            self.claude_settings_path.clone()
-        } else {
-            let path = self.context_path(target_context);
-            if !path.exists() {
-                bail!(
-                    "error: no context exists with the name \"{}\"",
-                    target_context
-                );
-            }
-            path
-        };
+    pub fn merge_from_full(
+        &self,
+        target_context: &str,
+        source: &str,
+        show_diff: bool,
+        env_overwrite: bool,
+        summary: MergeSummary,
+    ) -> Result<()> {
+        self.ensure_writable("merge settings")?;
+        self.ensure_distinct_merge(target_context, source)?;
+
+        let source_content = self.read_merge_source(source)?;
+        self.merge_content_full(
+            target_context,
+            source,
+            &source_content,
+            show_diff,
+            env_overwrite,
+            summary,
+        )
+    }
 
-        // Load source settings
-        let source_content = if source == "user" {
-            // Merge from user-level settings.json
-            let home_dir = dirs::home_dir().context("Failed to get home directory")?;
-            let user_settings = home_dir.join(".claude").join("settings.json");
-            if !user_settings.exists() {
-                bail!("error: user settings file not found at {:?}", user_settings);
-            }
-            fs::read_to_string(&user_settings)?
-        } else if source.ends_with(".json") {
-            // Merge from a file path
-            let source_path = PathBuf::from(source);
-            if !source_path.exists() {
-                bail!("error: source file not found at {:?}", source_path);
-            }
-            fs::read_to_string(&source_path)?
-        } else {
-            // Merge from another context
-            let source_path = self.context_path(source);
-            if !source_path.exists() {
-                bail!("error: no context exists with the name \"{}\"", source);
-            }
-            fs::read_to_string(&source_path)?
-        };
+    /// Full merge of settings that were already read, recording them in the
+    /// merge history under `source`
+    pub(crate) fn merge_content_full(
+        &self,
+        target_context: &str,
+        source: &str,
+        source_content: &str,
+        show_diff: bool,
+        env_overwrite: bool,
+        summary: MergeSummary,
+    ) -> Result<()> {
+        let target_path = self.merge_target_path(target_context)?;
 
         // Parse JSON
         let mut target_json: serde_json::Value =
-            serde_json::from_str(&fs::read_to_string(&target_path)?)?;
-        let source_json: serde_json::Value = serde_json::from_str(&source_content)?;
+            serde_json::from_str(&read_settings(&target_path)?)?;
+        let before = serde_json::to_string_pretty(&target_json)?;
+        let source_json: serde_json::Value = serde_json::from_str(source_content)?;
+        self.ensure_mergeable(target_context, source, &target_json, &source_json)?;
 
         // Perform full merge
-        let merge_manager = MergeManager::new(self.contexts_dir.clone());
-        let history_entry = merge_manager.merge_full(&mut target_json, &source_json, source)?;
+        let mut merge_manager = self.target_merge_manager(target_context);
+        if env_overwrite {
+            merge_manager = merge_manager.with_strategy("env", MergeStrategy::Overwrite);
+        }
+        let conflicts = env_conflicts(&target_json, &source_json);
+        if self.strict && !env_overwrite && !conflicts.is_empty() {
+            print_env_conflicts(&conflicts);
+            bail!(
+                "error: {} env vars differ in '{}' (--strict); pass --env-overwrite to take the source values",
+                conflicts.len(),
+                source
+            );
+        }
+        let mut history_entry = merge_manager.merge_full(&mut target_json, &source_json, source)?;
+        history_entry.protected = source == MANAGED_SOURCE;
 
         // Save updated target
-        fs::write(&target_path, serde_json::to_string_pretty(&target_json)?)?;
+        let after = serde_json::to_string_pretty(&target_json)?;
+        write_settings(&target_path, &after)?;
 
         // Update history
         let context_name = if target_context == "current" {
@@ -640,7 +1821,10 @@ impl ContextManager {
             target_context.green().bold()
         );
 
-        if !history_entry.merged_items.is_empty() {
+        if show_diff {
+            println!();
+            print_unified_diff(&before, &after, "before", "after");
+        } else if summary != MergeSummary::Counts && !history_entry.merged_items.is_empty() {
             println!("\n📋 Merged items:");
 
             // Group items by type for better display
@@ -660,9 +1844,19 @@ impl ContextManager {
 
             if !permissions_items.is_empty() {
                 println!("  🔒 Permissions: {} items", permissions_items.len());
+                if summary == MergeSummary::Full {
+                    for item in &permissions_items {
+                        println!("     • {}", item);
+                    }
+                }
             }
             if !env_items.is_empty() {
                 println!("  🌍 Environment: {} variables", env_items.len());
+                if summary == MergeSummary::Full {
+                    for item in &env_items {
+                        println!("     • {}", item);
+                    }
+                }
             }
             if !other_items.is_empty() {
                 let items_str: Vec<String> = other_items.iter().map(|s| s.to_string()).collect();
@@ -670,31 +1864,47 @@ impl ContextManager {
             }
         }
 
+        if !conflicts.is_empty() {
+            println!();
+            if merge_manager.strategy_for("env") == MergeStrategy::Overwrite {
+                println!(
+                    "🔁 Replaced {} env vars with the values from '{}':",
+                    conflicts.len(),
+                    source.green()
+                );
+            } else {
+                println!(
+                    "⚠️  Kept {} env vars that differ in '{}' (use --env-overwrite to take the source values):",
+                    conflicts.len(),
+                    source.yellow()
+                );
+            }
+            print_env_conflicts(&conflicts);
+        }
+
         Ok(())
     }
 
     /// Remove all settings that were previously merged from a specific source (full unmerge)
-    pub fn unmerge_from_full(&self, target_context: &str, source: &str) -> Result<()> {
+    pub fn unmerge_from_full(
+        &self,
+        target_context: &str,
+        source: &str,
+        show_diff: bool,
+    ) -> Result<()> {
+        self.ensure_writable("unmerge settings")?;
+
         // Load target context
-        let target_path = if target_context == "current" {
-            if !self.claude_settings_path.exists() {
-                bail!("error: no current context is set");
-            }
-            self.claude_settings_path.clone()
-        } else {
-            let path = self.context_path(target_context);
-            if !path.exists() {
-                bail!(
-                    "error: no context exists with the name \"{}\"",
-                    target_context
-                );
-            }
-            path
-        };
+        let target_path = self.merge_target_path(target_context)?;
 
         // Load and parse target JSON
         let mut target_json: serde_json::Value =
-            serde_json::from_str(&fs::read_to_string(&target_path)?)?;
+            serde_json::from_str(&read_settings(&target_path)?)?;
+        self.ensure_canonical_shape(
+            &format!("unmerge target \"{target_context}\""),
+            &target_json,
+        )?;
+        let before = serde_json::to_string_pretty(&target_json)?;
 
         // Get context name for history
         let context_name = if target_context == "current" {
@@ -705,11 +1915,13 @@ impl ContextManager {
         };
 
         // Perform full unmerge
-        let merge_manager = MergeManager::new(self.contexts_dir.clone());
+        let merge_manager = self.target_merge_manager(target_context);
+        ensure_unmergeable(&merge_manager, &context_name, source)?;
         merge_manager.unmerge_full(&mut target_json, &context_name, source)?;
 
         // Save updated target
-        fs::write(&target_path, serde_json::to_string_pretty(&target_json)?)?;
+        let after = serde_json::to_string_pretty(&target_json)?;
+        write_settings(&target_path, &after)?;
 
         println!(
             "✅ Removed all settings previously merged from '{}' in '{}'",
@@ -717,20 +1929,52 @@ impl ContextManager {
             target_context.green().bold()
         );
 
+        if show_diff {
+            println!();
+            print_unified_diff(&before, &after, "before", "after");
+        }
+
         Ok(())
     }
 
-    /// Display merge history for a context
-    pub fn show_merge_history(&self, context_name: Option<&str>) -> Result<()> {
-        let name = if let Some(n) = context_name {
-            n.to_string()
+    /// Display merge history for a context, or without a name the history of
+    /// merges into this level's live settings file
+    pub fn show_merge_history(
+        &self,
+        context_name: Option<&str>,
+        graph: bool,
+        compact: bool,
+        since: Option<&str>,
+    ) -> Result<()> {
+        let since = match since {
+            Some(raw) => Some(dates::parse_since(raw).with_context(|| {
+                format!("error: invalid --since \"{raw}\"; use YYYY-MM-DD or an RFC 3339 time")
+            })?),
+            None => None,
+        };
+
+        let (name, merge_manager) = if let Some(n) = context_name {
+            (self.resolve_alias(n), self.merge_manager())
         } else {
-            self.get_current_context()?
-                .ok_or_else(|| anyhow::anyhow!("error: no current context set"))?
+            let current = self
+                .get_current_context()?
+                .ok_or_else(|| anyhow::anyhow!("error: no current context set"))?;
+            (current, self.target_merge_manager("current"))
         };
 
-        let merge_manager = MergeManager::new(self.contexts_dir.clone());
-        merge_manager.display_history(&name)?;
+        if compact {
+            self.ensure_writable("compact merge history")?;
+            let (before, after) = merge_manager.compact(&name)?;
+            println!(
+                "Compacted merge history of \"{}\" from {} to {} entries",
+                name.green().bold(),
+                before,
+                after
+            );
+            return Ok(());
+        }
+
+        merge_manager.display_history(&name, graph, since)?;
 
         Ok(())
     }
@@ -0,0 +1,136 @@
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+/// Time `--deterministic` pins the clock to unless `SOURCE_DATE_EPOCH` is set
+pub const DETERMINISTIC_EPOCH: &str = "2024-01-15T09:30:00+00:00";
+
+static FROZEN_NOW: OnceLock<DateTime<Utc>> = OnceLock::new();
+
+/// Make every later [`now`] return `at`, so recorded and displayed times do not
+/// depend on when a command runs
+pub fn freeze_clock(at: DateTime<Utc>) {
+    let _ = FROZEN_NOW.set(at);
+}
+
+/// Time to freeze the clock at for `--deterministic`: `SOURCE_DATE_EPOCH` (seconds
+/// since the Unix epoch) when set, as reproducible builds use it, otherwise a fixed date
+pub fn deterministic_epoch() -> DateTime<Utc> {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|secs| secs.trim().parse::<i64>().ok())
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .unwrap_or_else(|| {
+            DateTime::parse_from_rfc3339(DETERMINISTIC_EPOCH)
+                .map(|ts| ts.with_timezone(&Utc))
+                .unwrap_or_default()
+        })
+}
+
+/// The current time, or the frozen time under `--deterministic`
+pub fn now() -> DateTime<Local> {
+    match FROZEN_NOW.get() {
+        Some(at) => at.with_timezone(&Local),
+        None => Local::now(),
+    }
+}
+
+/// How timestamps are shown in history, backups and cleanup suggestions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateStyle {
+    /// "2 days ago"
+    #[default]
+    Relative,
+    /// RFC 3339 in local time
+    Iso,
+    /// RFC 3339 in UTC
+    Utc,
+}
+
+/// Format a timestamp in the given style
+pub fn format_datetime<Tz: TimeZone>(ts: &DateTime<Tz>, style: DateStyle) -> String {
+    match style {
+        DateStyle::Relative => relative(ts.with_timezone(&Utc), now().with_timezone(&Utc)),
+        DateStyle::Iso => ts
+            .with_timezone(&Local)
+            .to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+        DateStyle::Utc => ts
+            .with_timezone(&Utc)
+            .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+    }
+}
+
+/// Format a stored RFC 3339 timestamp, falling back to the raw text if it does not parse
+pub fn format_rfc3339(raw: &str, style: DateStyle) -> String {
+    DateTime::parse_from_rfc3339(raw)
+        .map(|ts| format_datetime(&ts, style))
+        .unwrap_or_else(|_| raw.to_string())
+}
+
+/// Format a timestamp recorded without an offset in local time
+pub fn format_naive_local(ts: &NaiveDateTime, style: DateStyle) -> String {
+    match Local.from_local_datetime(ts).earliest() {
+        Some(ts) => format_datetime(&ts, style),
+        None => ts.format("%Y-%m-%d %H:%M:%S").to_string(),
+    }
+}
+
+/// "3 hours ago" / "in 2 days" relative to `now`
+fn relative(ts: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let seconds = (now - ts).num_seconds();
+    let future = seconds < 0;
+    let seconds = seconds.unsigned_abs();
+
+    let (amount, unit) = match seconds {
+        0..=44 => return "just now".to_string(),
+        45..=3_599 => (seconds.div_ceil(60).max(1), "minute"),
+        3_600..=86_399 => (seconds / 3_600, "hour"),
+        86_400..=2_591_999 => (seconds / 86_400, "day"),
+        2_592_000..=31_535_999 => (seconds / 2_592_000, "month"),
+        _ => (seconds / 31_536_000, "year"),
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+
+    if future {
+        format!("in {amount} {unit}{plural}")
+    } else {
+        format!("{amount} {unit}{plural} ago")
+    }
+}
+
+/// Format a timestamp as RFC 3339 in UTC, the form stored in files
+pub fn to_utc_rfc3339(ts: &DateTime<Utc>) -> String {
+    ts.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
+
+/// Parse `--since`: an RFC 3339 timestamp or a `YYYY-MM-DD` date (midnight UTC)
+pub fn parse_since(raw: &str) -> Option<DateTime<Utc>> {
+    if let Ok(ts) = DateTime::parse_from_rfc3339(raw) {
+        return Some(ts.with_timezone(&Utc));
+    }
+    chrono::NaiveDate::parse_from_str(raw, "%Y-%m-%d")
+        .ok()?
+        .and_hms_opt(0, 0, 0)
+        .map(|ts| ts.and_utc())
+}
+
+/// Serde representation of a timestamp as RFC 3339 in UTC. Timestamps written
+/// with a local offset by older releases are read and converted.
+pub mod utc_rfc3339 {
+    use chrono::{DateTime, Utc};
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(ts: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&super::to_utc_rfc3339(ts))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<DateTime<Utc>, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        DateTime::parse_from_rfc3339(&raw)
+            .map(|ts| ts.with_timezone(&Utc))
+            .map_err(|e| D::Error::custom(format!("invalid timestamp \"{raw}\": {e}")))
+    }
+}
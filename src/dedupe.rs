@@ -0,0 +1,174 @@
+use anyhow::Result;
+use colored::*;
+use dialoguer::Select;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, IsTerminal};
+
+use crate::compression::read_settings;
+use crate::context::ContextManager;
+use crate::state::State;
+
+impl ContextManager {
+    /// Archived names `--dedupe` recorded in this level's metadata files, each
+    /// mapped to the context it now resolves to
+    pub(crate) fn level_aliases(&self) -> BTreeMap<String, String> {
+        let mut aliases = BTreeMap::new();
+        let Ok(entries) = fs::read_dir(&self.contexts_dir) else {
+            return aliases;
+        };
+        for entry in entries.flatten() {
+            let file_name = entry.file_name();
+            let Some(name) = file_name
+                .to_str()
+                .and_then(|file_name| file_name.strip_prefix('.'))
+                .and_then(|file_name| file_name.strip_suffix(".meta.json"))
+            else {
+                continue;
+            };
+            if !self.context_path(name).exists() {
+                continue;
+            }
+            let Ok(metadata) = self.load_metadata(name) else {
+                continue;
+            };
+            for alias in metadata.aliases {
+                aliases.insert(alias, name.to_string());
+            }
+        }
+        aliases
+    }
+
+    /// Groups of contexts with the same settings, compared as parsed JSON so
+    /// formatting, key order and compression do not matter. Unreadable
+    /// contexts are left out.
+    fn duplicate_groups(&self) -> Result<Vec<Vec<String>>> {
+        let mut by_content: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for name in self.list_contexts()? {
+            let Some(settings) = read_settings(&self.context_path(&name))
+                .ok()
+                .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+            else {
+                continue;
+            };
+            // Object keys serialize sorted, so equal settings give equal text
+            by_content
+                .entry(serde_json::to_string(&settings)?)
+                .or_default()
+                .push(name);
+        }
+        Ok(by_content
+            .into_values()
+            .filter(|names| names.len() > 1)
+            .collect())
+    }
+
+    /// The name worth keeping: the current context, else the most recently used
+    fn preferred_name(group: &[String], state: &State) -> usize {
+        if let Some(i) = group
+            .iter()
+            .position(|name| state.current.as_ref() == Some(name))
+        {
+            return i;
+        }
+        group
+            .iter()
+            .enumerate()
+            // Reversed, so ties go to the first name
+            .rev()
+            .max_by_key(|(_, name)| state.last_used.get(*name))
+            .map_or(0, |(i, _)| i)
+    }
+
+    /// Report contexts with identical settings and offer to keep one name per
+    /// group, archiving the others and turning their names into aliases
+    pub fn dedupe(&self) -> Result<()> {
+        let groups = self.duplicate_groups()?;
+        if groups.is_empty() {
+            println!("✅ No contexts with identical settings");
+            return Ok(());
+        }
+
+        println!("Contexts with identical settings:");
+        for group in &groups {
+            println!("  • {}", group.join(", ").yellow());
+        }
+
+        let interactive = io::stdin().is_terminal();
+        if self.read_only || !(interactive || self.assume_yes) {
+            println!();
+            println!("💡 Run `cctx --dedupe` on a terminal, or with --yes, to consolidate them");
+            return Ok(());
+        }
+        self.ensure_writable("consolidate contexts")?;
+
+        let state = self.load_state()?;
+        let mut consolidated: Vec<(String, Vec<String>)> = Vec::new();
+        for group in groups {
+            let preferred = Self::preferred_name(&group, &state);
+            let keep = if self.assume_yes {
+                Some(preferred)
+            } else {
+                let mut items: Vec<String> = group
+                    .iter()
+                    .map(|name| format!("keep \"{name}\""))
+                    .collect();
+                items.push("leave them all".to_string());
+                println!();
+                Select::new()
+                    .with_prompt(format!("Consolidate {}", group.join(", ")))
+                    .items(&items)
+                    .default(preferred)
+                    .interact_opt()?
+                    .filter(|i| *i < group.len())
+            };
+            let Some(keep) = keep else {
+                continue;
+            };
+            let keeper = group[keep].clone();
+            let others = group.into_iter().filter(|name| *name != keeper).collect();
+            consolidated.push((keeper, others));
+        }
+        if consolidated.is_empty() {
+            return Ok(());
+        }
+
+        for (keeper, others) in &consolidated {
+            // The kept context answers to the archived names and keeps what they
+            // were described as; aliases live with it, so they stay at this level
+            let mut metadata = self.load_metadata(keeper)?;
+            for other in others {
+                metadata.absorb(other, self.load_metadata(other).unwrap_or_default());
+            }
+            self.save_metadata(keeper, &metadata)?;
+            for other in others {
+                self.archive_context(other)?;
+            }
+            let aliases = if others.len() == 1 {
+                "is now an alias"
+            } else {
+                "are now aliases"
+            };
+            println!(
+                "Kept \"{}\"; {} {aliases} for it",
+                keeper.green().bold(),
+                others.join(", ")
+            );
+        }
+
+        // Archiving forgets usage, but the live settings did not change
+        let mut state = self.load_state()?;
+        if let Some((keeper, _)) = consolidated.iter().find(|(_, others)| {
+            state
+                .current
+                .as_ref()
+                .is_some_and(|current| others.contains(current))
+        }) {
+            state.current = Some(keeper.clone());
+            self.save_state(&state)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+use anyhow::{bail, Context, Result};
+use colored::*;
+use serde_json::Value;
+use similar::{ChangeTag, TextDiff};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::compression::read_settings;
+use crate::config::home_dir;
+use crate::context::ContextManager;
+use crate::filter::ContextFilter;
+
+/// Lines of unchanged context shown around each change
+const CONTEXT_LINES: usize = 3;
+
+/// Print a colored unified diff between two texts
+pub fn print_unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) {
+    if old == new {
+        println!("{}", "(no changes)".dimmed());
+        return;
+    }
+
+    println!("{}", format!("--- {old_label}").red().bold());
+    println!("{}", format!("+++ {new_label}").green().bold());
+
+    let diff = TextDiff::from_lines(old, new);
+    let mut unified = diff.unified_diff();
+    unified.context_radius(CONTEXT_LINES);
+
+    for hunk in unified.iter_hunks() {
+        println!("{}", hunk.header().to_string().cyan());
+        for change in hunk.iter_changes() {
+            let line = change.value().trim_end_matches('\n');
+            match change.tag() {
+                ChangeTag::Delete => println!("{}", format!("-{line}").red()),
+                ChangeTag::Insert => println!("{}", format!("+{line}").green()),
+                ChangeTag::Equal => println!(" {line}"),
+            }
+        }
+    }
+}
+
+/// Scalar values by dotted path, and list entries as (path, entry) pairs so
+/// lists compare by membership rather than position
+#[derive(Default)]
+struct Flattened<'a> {
+    scalars: BTreeMap<String, &'a Value>,
+    entries: BTreeSet<(String, String)>,
+}
+
+fn flatten<'a>(value: &'a Value, path: &str, out: &mut Flattened<'a>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, child) in map {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                flatten(child, &path, out);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                let entry = item
+                    .as_str()
+                    .map_or_else(|| item.to_string(), str::to_string);
+                out.entries.insert((path.to_string(), entry));
+            }
+        }
+        scalar => {
+            out.scalars.insert(path.to_string(), scalar);
+        }
+    }
+}
+
+/// Top-level key a change belongs to; permission lists are told apart
+fn section(path: &str) -> String {
+    let mut parts = path.splitn(3, '.');
+    match (parts.next(), parts.next()) {
+        (Some("permissions"), Some(list)) => format!("permissions.{list}"),
+        (Some(key), _) => key.to_string(),
+        _ => path.to_string(),
+    }
+}
+
+/// How far a context has drifted from a baseline
+#[derive(Default)]
+struct Drift {
+    added: usize,
+    removed: usize,
+    changed: usize,
+    sections: BTreeSet<String>,
+}
+
+impl Drift {
+    fn between(base: &Value, context: &Value) -> Self {
+        let (mut old, mut new) = (Flattened::default(), Flattened::default());
+        flatten(base, "", &mut old);
+        flatten(context, "", &mut new);
+
+        let mut drift = Drift::default();
+        for (path, value) in &new.scalars {
+            match old.scalars.get(path) {
+                None => drift.added += 1,
+                Some(previous) if previous != value => drift.changed += 1,
+                Some(_) => continue,
+            }
+            drift.sections.insert(section(path));
+        }
+        for path in old.scalars.keys().filter(|p| !new.scalars.contains_key(*p)) {
+            drift.removed += 1;
+            drift.sections.insert(section(path));
+        }
+        for (path, _) in new.entries.difference(&old.entries) {
+            drift.added += 1;
+            drift.sections.insert(section(path));
+        }
+        for (path, _) in old.entries.difference(&new.entries) {
+            drift.removed += 1;
+            drift.sections.insert(section(path));
+        }
+        drift
+    }
+
+    fn is_empty(&self) -> bool {
+        self.added + self.removed + self.changed == 0
+    }
+}
+
+impl ContextManager {
+    /// Parse a context with its includes resolved, as Claude Code would see it
+    pub(crate) fn resolved_context(&self, name: &str) -> Result<Value> {
+        let mut context: Value = serde_json::from_str(&read_settings(&self.context_path(name))?)
+            .with_context(|| format!("error: context \"{name}\" is not valid JSON"))?;
+        self.resolve_includes(&mut context)?;
+        Ok(context)
+    }
+
+    /// Compare every context to a baseline and print one line per context with
+    /// the entries added, removed and changed, and the keys they are under
+    pub fn diff_all_against(&self, base: &str, filter: &ContextFilter) -> Result<()> {
+        let base = self.resolve_alias(base);
+        let contexts = self.list_contexts()?;
+        if !contexts.contains(&base) {
+            bail!("error: no context exists with the name \"{}\"", base);
+        }
+        let baseline = self.resolved_context(&base)?;
+
+        let others: Vec<String> = self
+            .filter_contexts(contexts, filter)
+            .into_iter()
+            .filter(|name| *name != base)
+            .collect();
+        if others.is_empty() {
+            println!("No other contexts to compare with \"{}\"", base);
+            return Ok(());
+        }
+
+        println!("Compared to {} (base):", base.green().bold());
+        let width = others.iter().map(String::len).max().unwrap_or(0);
+        let (mut identical, mut drifted) = (0, 0);
+        for name in &others {
+            let context = match self.resolved_context(name) {
+                Ok(context) => context,
+                Err(e) => {
+                    println!("  {:<width$}  {}", name, format!("{e:#}").red());
+                    continue;
+                }
+            };
+            let drift = Drift::between(&baseline, &context);
+            if drift.is_empty() {
+                identical += 1;
+                println!("  {:<width$}  {}", name, "identical".dimmed());
+                continue;
+            }
+            drifted += 1;
+            println!(
+                "  {:<width$}  {} {} {}  {}",
+                name,
+                format!("+{:<3}", drift.added).green(),
+                format!("-{:<3}", drift.removed).red(),
+                format!("~{:<3}", drift.changed).yellow(),
+                drift
+                    .sections
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+                    .dimmed()
+            );
+        }
+
+        println!();
+        println!("{drifted} contexts differ from {base}, {identical} identical");
+        if self.strict && drifted > 0 {
+            bail!(
+                "error: {} contexts drifted from \"{}\" (--strict)",
+                drifted,
+                base
+            );
+        }
+        Ok(())
+    }
+
+    /// Diff `~/.claude/settings.json` against a context: what the context
+    /// changes relative to the user's personal defaults
+    pub fn diff_against_user(&self, name: &str) -> Result<()> {
+        let name = self.resolve_alias(name);
+        let context_path = self.context_path(&name);
+        if !context_path.exists() {
+            bail!("error: no context exists with the name \"{}\"", name);
+        }
+
+        let user_path = home_dir()?.join(".claude").join("settings.json");
+        let user = if user_path.exists() {
+            serde_json::from_str(&read_settings(&user_path)?)
+                .with_context(|| format!("error: {:?} is not valid JSON", user_path))?
+        } else {
+            Value::Object(Default::default())
+        };
+        let context = self.resolved_context(&name)?;
+
+        // Compare normalized JSON so formatting and key order are not reported
+        let level = format!("{:?}", self.settings_level).to_lowercase();
+        print_unified_diff(
+            &serde_json::to_string_pretty(&user)?,
+            &serde_json::to_string_pretty(&context)?,
+            "~/.claude/settings.json",
+            &format!("{name} ({level})"),
+        );
+        Ok(())
+    }
+}
+
+/// Plain unified diff between two texts, for output that is not a terminal
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(CONTEXT_LINES)
+        .header(old_label, new_label)
+        .to_string()
+}
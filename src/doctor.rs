@@ -0,0 +1,139 @@
+use anyhow::Result;
+use colored::*;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::compression::read_settings;
+use crate::context::ContextManager;
+use crate::quarantine::{quarantine, CorruptFiles, CORRUPT_EXTENSION};
+use crate::repair::shape_problems;
+use crate::state::State;
+
+impl ContextManager {
+    /// Files in the contexts directory that were quarantined as corrupted
+    pub fn quarantined_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = fs::read_dir(&self.contexts_dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| {
+                        path.extension().and_then(|s| s.to_str()) == Some(CORRUPT_EXTENSION)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        files.sort();
+        files
+    }
+
+    /// Contexts, the state file and merge histories that fail to parse
+    fn corrupted_files(&self) -> Result<CorruptFiles> {
+        let (_, mut corrupted) = self.scan_contexts()?;
+        if let Some(reason) = State::parse_error(&self.state_path) {
+            corrupted.push((self.state_path.clone(), reason));
+        }
+        corrupted.extend(self.merge_manager().corrupt_history_files());
+        corrupted.sort();
+        Ok(corrupted)
+    }
+
+    /// Report corrupted files, quarantining them with `fix`, inspect quarantined
+    /// files and explain how to recover them, then check that contexts use the
+    /// canonical shape, repairing them with `fix`
+    pub fn doctor(&self, fix: bool) -> Result<()> {
+        println!("🩺 Checking {}", self.contexts_dir.display());
+
+        self.doctor_corrupted(fix)?;
+        self.doctor_quarantined();
+        println!();
+        self.doctor_shapes(fix)
+    }
+
+    fn doctor_shapes(&self, fix: bool) -> Result<()> {
+        if fix {
+            match self.repair_shapes()? {
+                0 => println!("✅ All contexts use the canonical settings shape"),
+                repaired => println!("🔧 Repaired {repaired} files"),
+            }
+            return Ok(());
+        }
+
+        let mut malformed = 0;
+        for name in self.list_contexts()? {
+            let content = read_settings(&self.context_path(&name))?;
+            let Ok(settings) = serde_json::from_str::<serde_json::Value>(&content) else {
+                continue;
+            };
+            let problems = shape_problems(&settings);
+            if problems.is_empty() {
+                continue;
+            }
+            malformed += 1;
+            println!("{} {}:", "⚠️".yellow(), name.bold());
+            for problem in problems {
+                println!("  • {problem}");
+            }
+        }
+
+        if malformed == 0 {
+            println!("✅ All contexts use the canonical settings shape");
+        } else {
+            println!();
+            println!("💡 Run `cctx --doctor --fix` to apply these repairs");
+        }
+        Ok(())
+    }
+
+    fn doctor_corrupted(&self, fix: bool) -> Result<()> {
+        let corrupted = self.corrupted_files()?;
+        if corrupted.is_empty() {
+            println!("✅ No corrupted files");
+            return Ok(());
+        }
+
+        if fix {
+            self.ensure_writable("quarantine corrupted files")?;
+            for (path, reason) in &corrupted {
+                let _ = quarantine(path, reason);
+            }
+            return Ok(());
+        }
+
+        println!(
+            "{} Corrupted files (skipped by every command):",
+            "⚠️".yellow()
+        );
+        for (path, reason) in &corrupted {
+            let filename = path.file_name().and_then(|s| s.to_str()).unwrap_or("?");
+            println!("  • {filename}: {}", reason.red());
+        }
+        println!("💡 Run `cctx --doctor --fix` to move them aside as .{CORRUPT_EXTENSION}");
+        Ok(())
+    }
+
+    fn doctor_quarantined(&self) {
+        let quarantined = self.quarantined_files();
+        if quarantined.is_empty() {
+            println!("✅ No quarantined files");
+            return;
+        }
+
+        println!();
+        println!("{} Quarantined files:", "⚠️".yellow());
+        for path in &quarantined {
+            let filename = path.file_name().and_then(|s| s.to_str()).unwrap_or("?");
+            let problem = match fs::read_to_string(path) {
+                Ok(content) => match serde_json::from_str::<serde_json::Value>(&content) {
+                    Ok(_) => "now parses cleanly".green().to_string(),
+                    Err(e) => e.to_string().red().to_string(),
+                },
+                Err(e) => format!("unreadable: {e}").red().to_string(),
+            };
+            println!("  • {filename}: {problem}");
+        }
+
+        println!();
+        println!("💡 Fix the JSON, then remove the .{CORRUPT_EXTENSION} suffix to restore a file");
+    }
+}
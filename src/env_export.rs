@@ -0,0 +1,78 @@
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+use crate::context::ContextManager;
+use crate::integration::shell_quote;
+use crate::run::env_value;
+
+/// Shells `--env-export` can print assignments for
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum EnvShell {
+    /// `export KEY='value'`, also for zsh and sh
+    #[value(aliases = ["zsh", "sh"])]
+    Bash,
+    /// `set -gx KEY 'value'`
+    Fish,
+    /// `$env:KEY = 'value'`
+    Powershell,
+}
+
+impl EnvShell {
+    /// The shell from `$SHELL`, or PowerShell on Windows
+    pub fn detect() -> Self {
+        match std::env::var("SHELL") {
+            Ok(shell) if shell.ends_with("fish") => Self::Fish,
+            Ok(_) => Self::Bash,
+            Err(_) if cfg!(windows) => Self::Powershell,
+            Err(_) => Self::Bash,
+        }
+    }
+
+    fn assignment(self, key: &str, value: &str) -> String {
+        match self {
+            Self::Bash => format!("export {key}={}", shell_quote(value)),
+            Self::Fish => format!(
+                "set -gx {key} '{}'",
+                value.replace('\\', "\\\\").replace('\'', "\\'")
+            ),
+            Self::Powershell => format!("$env:{key} = '{}'", value.replace('\'', "''")),
+        }
+    }
+}
+
+/// Names every shell accepts as a variable name
+fn is_portable_name(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl ContextManager {
+    /// Print a context's `env` block as assignments for `eval`, so a terminal
+    /// sees the same environment Claude Code does. `CCTX_CONTEXT` is set too,
+    /// as with `--run`.
+    pub fn print_env_export(&self, name: &str, shell: EnvShell) -> Result<()> {
+        let name = &self.resolve_alias(name);
+        if !self.context_path(name).exists() {
+            bail!("error: no context exists with the name \"{}\"", name);
+        }
+
+        let settings = self.resolved_context(name)?;
+        println!("{}", shell.assignment("CCTX_CONTEXT", name));
+        let Some(env) = settings.get("env").and_then(Value::as_object) else {
+            return Ok(());
+        };
+        for (key, value) in env {
+            if !is_portable_name(key) {
+                eprintln!(
+                    "warning: skipping env var {key:?}, which is not a valid shell variable name"
+                );
+                continue;
+            }
+            println!("{}", shell.assignment(key, &env_value(value)));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+use anyhow::{bail, Context, Result};
+use colored::*;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+use which::which;
+
+use crate::context::ContextManager;
+use crate::progress;
+use crate::signing::signature_path;
+
+/// Number of attempts before giving up on a URL
+const MAX_ATTEMPTS: u32 = 4;
+/// Delay before the first retry; doubled after every failed attempt
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Downloads remote JSON through `curl` with retries and an ETag-aware cache.
+///
+/// Proxies are honored through the standard `HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY`
+/// environment variables, which curl reads itself.
+pub struct Fetcher {
+    cache_dir: PathBuf,
+    offline: bool,
+    progress: bool,
+}
+
+impl Fetcher {
+    pub fn new(cache_dir: PathBuf, offline: bool) -> Self {
+        Self {
+            cache_dir,
+            offline,
+            progress: false,
+        }
+    }
+
+    /// Show a spinner while downloading
+    pub fn with_progress(mut self, progress: bool) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    fn cache_paths(&self, url: &str) -> (PathBuf, PathBuf) {
+        let key = format!("{:x}", Sha256::digest(url.as_bytes()));
+        (
+            self.cache_dir.join(format!("{key}.json")),
+            self.cache_dir.join(format!("{key}.etag")),
+        )
+    }
+
+    /// Fetch a URL, falling back to the cache when offline or unchanged
+    pub fn fetch(&self, url: &str) -> Result<String> {
+        let (body_path, etag_path) = self.cache_paths(url);
+
+        if self.offline {
+            return fs::read_to_string(&body_path)
+                .with_context(|| format!("error: {url} is not cached (required by --offline)"));
+        }
+
+        if which("curl").is_err() {
+            bail!("error: curl is required to fetch URLs but was not found in PATH");
+        }
+
+        fs::create_dir_all(&self.cache_dir)?;
+        let download_path = body_path.with_extension("download");
+
+        let mut backoff = INITIAL_BACKOFF;
+        let mut last_error = String::new();
+        for attempt in 1..=MAX_ATTEMPTS {
+            let spinner = progress::spinner(self.progress, format!("Fetching {url}"));
+            let result = self.try_fetch(url, &body_path, &etag_path, &download_path);
+            spinner.finish_and_clear();
+            // Only a successful body is kept; error pages and empty 304s are not
+            if !matches!(result, Ok(status) if (200..300).contains(&status)) {
+                let _ = fs::remove_file(&download_path);
+            }
+            match result {
+                Ok(304) => {
+                    return Ok(fs::read_to_string(&body_path)?);
+                }
+                Ok(status) if (200..300).contains(&status) => {
+                    fs::rename(&download_path, &body_path)?;
+                    return Ok(fs::read_to_string(&body_path)?);
+                }
+                // Client errors other than rate limiting will not succeed on retry
+                Ok(status) if (400..500).contains(&status) && status != 429 => {
+                    bail!("error: {} returned HTTP {}", url, status);
+                }
+                Ok(status) => last_error = format!("HTTP {status}"),
+                Err(e) => last_error = e.to_string(),
+            }
+
+            if attempt < MAX_ATTEMPTS {
+                eprintln!(
+                    "{} {} failed ({}), retrying in {}s",
+                    "⚠️".yellow(),
+                    url,
+                    last_error,
+                    backoff.as_secs()
+                );
+                thread::sleep(backoff);
+                backoff *= 2;
+            }
+        }
+
+        if body_path.exists() {
+            eprintln!(
+                "{} Using cached copy of {} ({})",
+                "⚠️".yellow(),
+                url,
+                last_error
+            );
+            return Ok(fs::read_to_string(&body_path)?);
+        }
+
+        bail!(
+            "error: failed to fetch {} after {} attempts: {}",
+            url,
+            MAX_ATTEMPTS,
+            last_error
+        )
+    }
+
+    /// Run one curl request and return the HTTP status code
+    fn try_fetch(
+        &self,
+        url: &str,
+        body_path: &Path,
+        etag_path: &Path,
+        download_path: &Path,
+    ) -> Result<u16> {
+        let mut cmd = Command::new("curl");
+        cmd.arg("--silent")
+            .arg("--show-error")
+            .arg("--location")
+            .arg("--output")
+            .arg(download_path)
+            .arg("--write-out")
+            .arg("%{http_code}")
+            .arg("--etag-save")
+            .arg(etag_path);
+
+        // Only revalidate when there is a cached body to fall back on
+        if body_path.exists() && etag_path.exists() {
+            cmd.arg("--etag-compare").arg(etag_path);
+        }
+
+        // `--` keeps a URL starting with `-` from being read as an option
+        let output = cmd.arg("--").arg(url).output()?;
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+
+        let status = String::from_utf8_lossy(&output.stdout);
+        status
+            .trim()
+            .parse()
+            .with_context(|| format!("unexpected curl status output {status:?}"))
+    }
+}
+
+impl ContextManager {
+    /// Import a context from a URL
+    pub fn import_context_from_url(
+        &self,
+        name: &str,
+        url: &str,
+        suffix: Option<&str>,
+        offline: bool,
+    ) -> Result<()> {
+        self.ensure_writable("import contexts")?;
+
+        let name = &self.validate_name(name)?;
+
+        let cache_dir = self.contexts_dir.join(".cctx-cache");
+        let fetcher = Fetcher::new(cache_dir.clone(), offline).with_progress(self.progress);
+        let content = fetcher.fetch(url)?;
+
+        // Signed imports expect a detached signature at "<url>.sig"
+        let sig_path = cache_dir.join("import.sig");
+        let result = self.import_fetched(name, url, suffix, &content, &fetcher, &sig_path);
+        // The signature is only needed until it is copied next to the context
+        let _ = fs::remove_file(&sig_path);
+        result
+    }
+
+    fn import_fetched(
+        &self,
+        name: &str,
+        url: &str,
+        suffix: Option<&str>,
+        content: &str,
+        fetcher: &Fetcher,
+        sig_path: &Path,
+    ) -> Result<()> {
+        if self.require_signed {
+            let signature = fetcher.fetch(&format!("{url}.sig"))?;
+            fs::write(sig_path, signature)?;
+            let principal = self
+                .verify_content(content.as_bytes(), sig_path)
+                .with_context(|| format!("error: signature check failed for {url}"))?;
+            println!("✅ Valid signature from {}", principal.cyan());
+        }
+
+        let imported = self.import_documents(name, suffix, content)?;
+
+        // A single document is stored verbatim, so its signature stays valid
+        if self.require_signed {
+            if let [imported_name] = imported.as_slice() {
+                fs::copy(sig_path, signature_path(&self.context_path(imported_name)))?;
+            }
+        }
+
+        Ok(())
+    }
+}
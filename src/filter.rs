@@ -0,0 +1,84 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::context::ContextManager;
+use crate::ignore::glob_to_regex;
+
+/// Narrows a list of contexts by name and tag. Given on the command line with
+/// `--filter`/`--tag`, or under `ui.completion_filter` for shell completions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContextFilter {
+    /// Substring of the name, or a glob (`*`, `?`, `[...]`) matching the whole name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// Tag the context's metadata must list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+impl ContextFilter {
+    pub fn new(pattern: Option<String>, tag: Option<String>) -> Self {
+        Self { pattern, tag }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pattern.is_none() && self.tag.is_none()
+    }
+
+    /// Whether a name passes the pattern; tags need the manager to look up metadata
+    fn matches_name(&self, name: &str, glob: Option<&Regex>) -> bool {
+        match (&self.pattern, glob) {
+            (_, Some(glob)) => glob.is_match(name),
+            (Some(pattern), None) => name.contains(pattern.as_str()),
+            (None, None) => true,
+        }
+    }
+
+    /// The pattern as a regex when it contains glob characters
+    fn glob(&self) -> Option<Regex> {
+        let pattern = self.pattern.as_deref()?;
+        if pattern.contains(['*', '?', '[']) {
+            glob_to_regex(pattern)
+        } else {
+            None
+        }
+    }
+
+    /// Human-readable description for "no contexts match" messages
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(pattern) = &self.pattern {
+            parts.push(format!("\"{pattern}\""));
+        }
+        if let Some(tag) = &self.tag {
+            parts.push(format!("tag \"{tag}\""));
+        }
+        parts.join(" and ")
+    }
+}
+
+impl ContextManager {
+    /// Contexts tagged with `tag` in their metadata; unreadable metadata has no tags
+    fn has_tag(&self, name: &str, tag: &str) -> bool {
+        self.load_metadata(name)
+            .is_ok_and(|metadata| metadata.tags.iter().any(|t| t == tag))
+    }
+
+    /// Keep the contexts a filter matches, in their original order
+    pub fn filter_contexts(&self, contexts: Vec<String>, filter: &ContextFilter) -> Vec<String> {
+        if filter.is_empty() {
+            return contexts;
+        }
+        let glob = filter.glob();
+        contexts
+            .into_iter()
+            .filter(|name| filter.matches_name(name, glob.as_ref()))
+            .filter(|name| {
+                filter
+                    .tag
+                    .as_ref()
+                    .map_or(true, |tag| self.has_tag(name, tag))
+            })
+            .collect()
+    }
+}
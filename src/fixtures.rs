@@ -0,0 +1,205 @@
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::Path;
+
+use crate::dates::DETERMINISTIC_EPOCH;
+use crate::integration::shell_quote;
+use crate::merge::MergeHistory;
+use crate::metadata::ContextMetadata;
+use crate::state::State;
+
+/// Fixed timestamp so generated files are identical on every run, matching
+/// the clock `--deterministic` freezes
+const EPOCH: &str = DETERMINISTIC_EPOCH;
+
+/// Allow rules in the `huge` context, enough to make slow paths visible
+const HUGE_RULES: usize = 5000;
+
+fn write_json(path: &Path, value: &impl serde::Serialize) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(value)?)
+        .with_context(|| format!("error: failed to write {:?}", path))
+}
+
+fn write_raw(path: &Path, content: &str) -> Result<()> {
+    fs::write(path, content).with_context(|| format!("error: failed to write {:?}", path))
+}
+
+fn work_settings() -> Value {
+    json!({
+        "permissions": {
+            "allow": ["Read(**)", "Edit(src/**)", "Bash(cargo:*)", "Bash(git status)"],
+            "deny": ["Bash(rm -rf:*)", "Read(.env)"]
+        },
+        "env": { "RUST_LOG": "debug", "CARGO_TERM_COLOR": "always" },
+        "model": "claude-sonnet-4-5"
+    })
+}
+
+fn huge_settings() -> Value {
+    let allow: Vec<String> = (0..HUGE_RULES)
+        .map(|i| format!("Bash(tool-{i:04}:*)"))
+        .collect();
+    let deny: Vec<String> = (0..HUGE_RULES / 10)
+        .map(|i| format!("Read(secrets/{i:04}/**)"))
+        .collect();
+    json!({ "permissions": { "allow": allow, "deny": deny } })
+}
+
+fn history_entry(source: &str, day: u32, items: &[&str], full_merge: bool) -> MergeHistory {
+    MergeHistory {
+        source: source.to_string(),
+        timestamp: chrono::NaiveDate::from_ymd_opt(2024, 1, day)
+            .and_then(|date| date.and_hms_opt(9, 30, 0))
+            .map(|ts| ts.and_utc())
+            .unwrap_or_default(),
+        merged_items: items.iter().map(|item| item.to_string()).collect(),
+        full_merge,
+        protected: false,
+        previous_values: Default::default(),
+    }
+}
+
+fn state(current: &str, previous: Option<&str>) -> State {
+    let mut state = State {
+        current: Some(current.to_string()),
+        previous: previous.map(str::to_string),
+        ..State::default()
+    };
+    state
+        .last_used
+        .insert(current.to_string(), EPOCH.to_string());
+    state
+}
+
+/// User level: contexts of every shape, corrupted files, history and metadata
+fn user_level(claude_dir: &Path) -> Result<()> {
+    let settings_dir = claude_dir.join("settings");
+    fs::create_dir_all(&settings_dir)?;
+
+    write_json(&claude_dir.join("settings.json"), &work_settings())?;
+    write_json(&settings_dir.join("work.json"), &work_settings())?;
+    write_json(&settings_dir.join("personal.json"), &json!({}))?;
+    write_json(&settings_dir.join("huge.json"), &huge_settings())?;
+    write_json(
+        &settings_dir.join("client acme.json"),
+        &json!({ "permissions": { "allow": ["WebFetch(domain:acme.example)"] } }),
+    )?;
+    write_json(
+        &settings_dir.join("café.json"),
+        &json!({ "env": { "LANG": "fr_FR.UTF-8" } }),
+    )?;
+    write_json(
+        &settings_dir.join("hooks.json"),
+        &json!({
+            "hooks": {
+                "PreToolUse": [{
+                    "matcher": "Bash",
+                    "hooks": [{ "type": "command", "command": "echo \"$CLAUDE_TOOL\" >> ~/tools.log" }]
+                }]
+            },
+            "permissions": { "allow": ["Bash(npm run:*)"], "additionalDirectories": ["../shared"] }
+        }),
+    )?;
+
+    // Corrupted contexts, each failing a different way
+    write_raw(
+        &settings_dir.join("truncated.json"),
+        "{\n  \"permissions\": {\n    \"allow\": [\"Read(**)\"",
+    )?;
+    write_raw(&settings_dir.join("empty.json"), "")?;
+    write_raw(
+        &settings_dir.join("array.json"),
+        "[\"not\", \"an\", \"object\"]",
+    )?;
+    write_raw(&settings_dir.join("notes.txt"), "not a context\n")?;
+
+    write_json(
+        &settings_dir.join(".cctx-state.json"),
+        &state("work", Some("personal")),
+    )?;
+    write_json(
+        &settings_dir.join(".work-merge-history.json"),
+        &[
+            history_entry("personal", 10, &["Read(**)"], false),
+            history_entry("hooks", 12, &["Bash(npm run:*)"], false),
+            history_entry("client acme", 14, &["permissions", "env"], true),
+        ],
+    )?;
+    write_raw(
+        &settings_dir.join(".huge-merge-history.json"),
+        "[{\"source\": \"work\", \"timestamp\": ",
+    )?;
+    write_json(
+        &settings_dir.join(".work.meta.json"),
+        &ContextMetadata {
+            owner: Some("platform-team".to_string()),
+            contact: Some("#platform".to_string()),
+            source: Some("https://example.com/contexts/work.json".to_string()),
+            ..ContextMetadata::default()
+        },
+    )?;
+    Ok(())
+}
+
+/// Project and local levels sharing one `.claude/settings` directory
+fn project_levels(project_dir: &Path) -> Result<()> {
+    let claude_dir = project_dir.join(".claude");
+    let settings_dir = claude_dir.join("settings");
+    fs::create_dir_all(&settings_dir)?;
+    // Marks the repo root for project discovery
+    fs::create_dir_all(project_dir.join(".git"))?;
+    fs::create_dir_all(project_dir.join("src/nested"))?;
+
+    let frontend = json!({ "permissions": { "allow": ["Bash(npm:*)", "Edit(web/**)"] } });
+    let backend =
+        json!({ "permissions": { "allow": ["Bash(cargo:*)"], "deny": ["Edit(web/**)"] } });
+    let debug = json!({ "env": { "RUST_BACKTRACE": "1" } });
+
+    write_json(&claude_dir.join("settings.json"), &frontend)?;
+    write_json(&claude_dir.join("settings.local.json"), &debug)?;
+    write_json(&settings_dir.join("frontend.json"), &frontend)?;
+    write_json(&settings_dir.join("backend.json"), &backend)?;
+    write_json(&settings_dir.join("debug.json"), &debug)?;
+    write_json(
+        &settings_dir.join(".cctx-state.json"),
+        &state("frontend", None),
+    )?;
+    write_json(
+        &settings_dir.join(".cctx-state.local.json"),
+        &state("debug", None),
+    )?;
+    Ok(())
+}
+
+/// Generate a deterministic sandbox under `dir` for reproducing bugs: contexts
+/// of varying shapes, corrupted files, merge history and all three levels.
+/// `dir` must be missing or empty.
+pub fn generate_fixtures(dir: &Path) -> Result<()> {
+    if dir.exists() && fs::read_dir(dir)?.next().is_some() {
+        bail!(
+            "error: {:?} is not empty; fixtures are only written to a fresh directory",
+            dir
+        );
+    }
+
+    fs::create_dir_all(dir)?;
+    // Absolute paths so the printed hint works from any directory
+    let dir = dir.canonicalize()?;
+    let home = dir.join("home");
+    let project = dir.join("project");
+    user_level(&home.join(".claude"))?;
+    project_levels(&project)?;
+
+    println!("Generated fixtures in {}", dir.display());
+    println!("  home/     user level: valid, huge, unicode, corrupted contexts and history");
+    println!("  project/  project and local levels in a git repo");
+    println!();
+    println!("Try it with:");
+    println!(
+        "  cd {} && HOME={} cctx --in-project",
+        shell_quote(&project.to_string_lossy()),
+        shell_quote(&home.to_string_lossy())
+    );
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use anyhow::{bail, Context, Result};
+use colored::*;
+use serde_json::{Map, Value};
+
+use crate::compression::{read_settings, write_settings};
+use crate::context::ContextManager;
+use crate::merge::{ensure_array, get_array_mut};
+use crate::metadata::{ContextMetadata, Provenance};
+
+/// Changes a fork applies on top of the settings it copies
+#[derive(Debug, Default)]
+pub struct ForkChanges {
+    /// Rules added to `permissions.allow` (and dropped from `permissions.deny`)
+    pub allow: Vec<String>,
+    /// Rules added to `permissions.deny` (and dropped from `permissions.allow`)
+    pub deny: Vec<String>,
+    /// `key=value` assignments; dotted keys reach into objects
+    pub set: Vec<String>,
+}
+
+impl ForkChanges {
+    fn len(&self) -> usize {
+        self.allow.len() + self.deny.len() + self.set.len()
+    }
+}
+
+/// Parse a `--set` value: JSON when it parses (`true`, `3`, `["a"]`), otherwise
+/// a plain string. Env vars are always strings, as Claude Code expects.
+fn parse_value(key: &str, raw: &str) -> Value {
+    if key.starts_with("env.") {
+        return Value::String(raw.to_string());
+    }
+    serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.to_string()))
+}
+
+/// Apply one `key=value` assignment, creating missing objects along a dotted key
+fn apply_set(settings: &mut Value, assignment: &str) -> Result<()> {
+    let Some((key, raw)) = assignment.split_once('=') else {
+        bail!("error: --set expects key=value, got \"{}\"", assignment);
+    };
+    let key = key.trim();
+    if key.is_empty() || key.split('.').any(str::is_empty) {
+        bail!("error: --set has an invalid key \"{}\"", key);
+    }
+
+    let mut current = settings;
+    let mut parts = key.split('.').peekable();
+    while let Some(part) = parts.next() {
+        let Some(object) = current.as_object_mut() else {
+            bail!(
+                "error: --set {}: a parent of \"{}\" is not an object",
+                key,
+                part
+            );
+        };
+        if parts.peek().is_none() {
+            object.insert(part.to_string(), parse_value(key, raw));
+            return Ok(());
+        }
+        current = object
+            .entry(part)
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+    Ok(())
+}
+
+/// Add `rule` to one permission list and remove it from the opposite one
+fn move_rule(settings: &mut Value, rule: &str, to: &str, from: &str) -> Result<()> {
+    if let Some(items) = get_array_mut(settings, from) {
+        items.retain(|item| item.as_str() != Some(rule));
+    }
+    let items = ensure_array(settings, to)?;
+    if !items.iter().any(|item| item.as_str() == Some(rule)) {
+        items.push(Value::String(rule.to_string()));
+    }
+    Ok(())
+}
+
+impl ContextManager {
+    /// Create a context from the live settings with permission rules and values
+    /// changed on the way. Everything is applied in memory first, so a bad
+    /// change leaves no half-written context behind.
+    pub fn fork_context(&self, name: &str, changes: &ForkChanges) -> Result<String> {
+        self.ensure_writable("create contexts")?;
+
+        let name = &self.validate_name(name)?;
+        if self.list_contexts()?.contains(name) {
+            bail!("error: context \"{}\" already exists", name);
+        }
+
+        let mut settings: Value = if self.claude_settings_path.exists() {
+            serde_json::from_str(&read_settings(&self.claude_settings_path)?).with_context(
+                || format!("error: {:?} is not valid JSON", self.claude_settings_path),
+            )?
+        } else {
+            Value::Object(Map::new())
+        };
+        if !settings.is_object() {
+            bail!(
+                "error: {:?} must be a JSON object to fork it",
+                self.claude_settings_path
+            );
+        }
+
+        for rule in &changes.allow {
+            move_rule(&mut settings, rule, "permissions.allow", "permissions.deny")?;
+        }
+        for rule in &changes.deny {
+            move_rule(&mut settings, rule, "permissions.deny", "permissions.allow")?;
+        }
+        for assignment in &changes.set {
+            apply_set(&mut settings, assignment)?;
+        }
+
+        write_settings(
+            &self.context_path(name),
+            &serde_json::to_string_pretty(&settings)?,
+        )?;
+
+        let origin = match self.get_current_context()? {
+            Some(current) => format!("fork of \"{current}\""),
+            None => "fork of live settings".to_string(),
+        };
+        let metadata = ContextMetadata {
+            provenance: Some(Provenance {
+                created_from: format!("{origin} with {} changes", changes.len()),
+                ..Provenance::captured(&self.claude_settings_path)
+            }),
+            ..ContextMetadata::default()
+        };
+        self.save_metadata(name, &metadata)?;
+
+        println!(
+            "Context \"{}\" forked from {} with {} changes",
+            name.green().bold(),
+            origin.trim_start_matches("fork of "),
+            changes.len()
+        );
+        Ok(name.to_string())
+    }
+}
@@ -0,0 +1,124 @@
+use anyhow::{Context, Result};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::atomic::write_atomic;
+use crate::config::{home_dir, Config};
+use crate::context::{ContextManager, ManagerOptions, SettingsLevel};
+
+/// Contexts applied together across levels, configured under `groups`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContextGroup {
+    pub user: Option<String>,
+    pub project: Option<String>,
+    pub local: Option<String>,
+}
+
+/// Files a switch modifies, captured so a failed group switch can be undone
+struct Snapshot {
+    files: Vec<(PathBuf, Option<Vec<u8>>)>,
+}
+
+impl Snapshot {
+    fn capture(manager: &ContextManager) -> Result<Self> {
+        let mut files = Vec::new();
+        for path in [
+            manager.claude_settings_path.clone(),
+            manager.state_path.clone(),
+            manager.status_file_path(),
+        ] {
+            let content = if path.exists() {
+                Some(fs::read(&path)?)
+            } else {
+                None
+            };
+            files.push((path, content));
+        }
+        Ok(Self { files })
+    }
+
+    /// Put every file back, carrying on past failures so as much as possible is
+    /// restored; the first error is returned
+    fn restore(&self) -> Result<()> {
+        let mut first_error = None;
+        for (path, content) in &self.files {
+            let result = match content {
+                Some(bytes) => write_atomic(path, bytes),
+                None if path.exists() => fs::remove_file(path).map_err(Into::into),
+                None => Ok(()),
+            };
+            if let Err(e) = result {
+                first_error.get_or_insert(e);
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+}
+
+impl ContextManager {
+    /// Switch every level of a group, rolling back all levels if one fails
+    pub fn use_group(&self, name: &str, options: &ManagerOptions) -> Result<()> {
+        self.ensure_writable("switch contexts")?;
+
+        let group = self.config.groups.get(name).with_context(|| {
+            format!(
+                "error: no group named \"{}\" in {:?}",
+                name,
+                home_dir()
+                    .map(|home| Config::default_path(&home))
+                    .unwrap_or_default()
+            )
+        })?;
+
+        let options = ManagerOptions {
+            target: None,
+            ..options.clone()
+        };
+        let levels = [
+            (SettingsLevel::User, &group.user),
+            (SettingsLevel::Project, &group.project),
+            (SettingsLevel::Local, &group.local),
+        ];
+
+        // Every level is captured before the first switch, so a failure at any
+        // level (including a half-finished one) can put all of them back
+        let mut switches = Vec::new();
+        for (level, context) in levels {
+            let Some(context) = context else {
+                continue;
+            };
+            let manager = ContextManager::new_with_options(level.clone(), &options)?;
+            let snapshot = Snapshot::capture(&manager)?;
+            switches.push((level, context, manager, snapshot));
+        }
+
+        for (level, context, manager, _) in &switches {
+            if let Err(e) = manager.switch_context(context) {
+                let mut restored = true;
+                for (level, _, _, snapshot) in switches.iter().rev() {
+                    if let Err(restore_error) = snapshot.restore() {
+                        eprintln!(
+                            "warning: could not roll back the {:?} level: {restore_error}",
+                            level
+                        );
+                        restored = false;
+                    }
+                }
+                let outcome = if restored {
+                    "was rolled back"
+                } else {
+                    "could not be fully rolled back"
+                };
+                return Err(e.context(format!(
+                    "error: failed to switch {:?} level to \"{}\"; group \"{}\" {}",
+                    level, context, name, outcome
+                )));
+            }
+        }
+
+        println!("Group \"{}\" applied", name.green().bold());
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use anyhow::{bail, Result};
+use colored::*;
+use std::fs;
+
+use crate::context::ContextManager;
+use crate::state::State;
+
+impl ContextManager {
+    /// Problems that make the contexts directory inconsistent
+    pub fn health_issues(&self) -> Vec<String> {
+        let mut issues = Vec::new();
+
+        let state = if self.state_path.exists() {
+            match fs::read_to_string(&self.state_path)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| Ok(serde_json::from_str::<State>(&content)?))
+            {
+                Ok(state) => Some(state),
+                Err(e) => {
+                    issues.push(format!("{} does not parse: {e}", self.state_path.display()));
+                    None
+                }
+            }
+        } else {
+            Some(State::default())
+        };
+
+        if let Some(current) = state.and_then(|state| state.current) {
+            if !self.context_path(&current).exists() {
+                issues.push(format!("active context \"{current}\" no longer exists"));
+            }
+        }
+
+        issues
+    }
+
+    /// Summarize the store before a destructive operation, refusing to continue
+    /// on an inconsistent one unless `--force` was given
+    pub(crate) fn check_health(&self, operation: &str) -> Result<()> {
+        let issues = self.health_issues();
+
+        if !self.config.backups.enabled {
+            eprintln!(
+                "{} Backups are disabled; the current settings can't be restored afterwards",
+                "⚠️".yellow()
+            );
+        }
+
+        if issues.is_empty() {
+            return Ok(());
+        }
+
+        eprintln!("🩺 Problems found in {}:", self.contexts_dir.display());
+        for issue in &issues {
+            eprintln!("  {} {issue}", "✗".red());
+        }
+
+        if self.force {
+            eprintln!("Continuing because --force was given");
+            return Ok(());
+        }
+        bail!(
+            "error: refusing to {} on an inconsistent store; fix the problems above or pass --force",
+            operation
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use regex::Regex;
+use std::fs;
+use std::path::Path;
+
+/// File in the contexts directory listing files that are never contexts
+pub const IGNORE_FILE: &str = ".cctxignore";
+
+struct Pattern {
+    regex: Regex,
+    negated: bool,
+}
+
+/// Patterns from `.cctxignore`, matched against file names with gitignore rules:
+/// `*`, `?` and `[...]` globs, `!` to re-include, `#` comments, last match wins
+#[derive(Default)]
+pub struct IgnorePatterns {
+    patterns: Vec<Pattern>,
+}
+
+/// Translate a gitignore glob into an anchored regex over a file name
+pub(crate) fn glob_to_regex(glob: &str) -> Option<Regex> {
+    let mut regex = String::from("^");
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' => {
+                // `**` can only span directories, which a flat directory has none of
+                while chars.peek() == Some(&'*') {
+                    chars.next();
+                }
+                regex.push_str("[^/]*");
+            }
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                regex.push('[');
+                if chars.peek() == Some(&'!') {
+                    chars.next();
+                    regex.push('^');
+                }
+                for c in chars.by_ref() {
+                    if c == ']' {
+                        break;
+                    }
+                    if c == '\\' || c == '[' {
+                        regex.push('\\');
+                    }
+                    regex.push(c);
+                }
+                regex.push(']');
+            }
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    regex.push_str(&regex::escape(&escaped.to_string()));
+                }
+            }
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    regex.push('$');
+    Regex::new(&regex).ok()
+}
+
+impl IgnorePatterns {
+    /// Load `.cctxignore` from a contexts directory; missing files ignore nothing
+    pub fn load(contexts_dir: &Path) -> Self {
+        let Ok(content) = fs::read_to_string(contexts_dir.join(IGNORE_FILE)) else {
+            return Self::default();
+        };
+
+        let patterns = content
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            // Directory patterns never match a context file
+            .filter(|line| !line.ends_with('/'))
+            .filter_map(|line| {
+                let (negated, glob) = match line.strip_prefix('!') {
+                    Some(glob) => (true, glob),
+                    None => (false, line),
+                };
+                let glob = glob.trim_start_matches('/');
+                let regex = glob_to_regex(glob);
+                if regex.is_none() {
+                    eprintln!("warning: ignoring invalid pattern \"{line}\" in {IGNORE_FILE}");
+                }
+                Some(Pattern {
+                    regex: regex?,
+                    negated,
+                })
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    pub fn is_ignored(&self, file_name: &str) -> bool {
+        self.patterns
+            .iter()
+            .rev()
+            .find(|pattern| pattern.regex.is_match(file_name))
+            .is_some_and(|pattern| !pattern.negated)
+    }
+}
@@ -0,0 +1,190 @@
+use anyhow::{bail, Context, Result};
+use colored::*;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+use crate::compression::write_settings;
+use crate::context::ContextManager;
+use crate::lint::{lint_settings, Severity};
+use crate::metadata::{metadata_path, ContextMetadata};
+
+/// What `--import-dir` does with a file whose context already exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OnConflict {
+    /// Keep the existing context
+    #[default]
+    Skip,
+    /// Replace the existing context
+    Overwrite,
+    /// Import under `<name><suffix>` (see --suffix)
+    Rename,
+    /// Stop before importing anything
+    Fail,
+}
+
+/// Suffix used by `--on-conflict rename` when `--suffix` is not given
+const DEFAULT_RENAME_SUFFIX: &str = "-imported";
+
+enum Outcome {
+    Imported(String),
+    Overwritten(String),
+    Skipped(String),
+    Failed(String, String),
+}
+
+impl ContextManager {
+    /// Install every `*.json` file in `dir` as a context named after the file
+    pub fn import_dir(
+        &self,
+        dir: &Path,
+        on_conflict: OnConflict,
+        suffix: Option<&str>,
+    ) -> Result<()> {
+        self.ensure_writable("import contexts")?;
+
+        let mut files: Vec<_> = fs::read_dir(dir)
+            .with_context(|| format!("error: failed to read directory {:?}", dir))?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "json"))
+            // Hidden files are metadata sidecars, not contexts
+            .filter(|path| {
+                !path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with('.'))
+            })
+            .collect();
+        files.sort();
+        if files.is_empty() {
+            bail!("error: no .json files in {:?}", dir);
+        }
+
+        let mut contexts = self.list_contexts()?;
+
+        if on_conflict == OnConflict::Fail {
+            let existing: Vec<String> = files
+                .iter()
+                .filter_map(|path| path.file_stem()?.to_str())
+                .filter(|name| contexts.iter().any(|ctx| ctx == name))
+                .map(str::to_string)
+                .collect();
+            if !existing.is_empty() {
+                bail!(
+                    "error: contexts already exist: {} (choose another --on-conflict policy)",
+                    existing.join(", ")
+                );
+            }
+        }
+
+        let mut outcomes = Vec::new();
+        for path in &files {
+            let stem = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default()
+                .to_string();
+            let outcome = match self.import_dir_entry(path, &stem, on_conflict, suffix, &contexts) {
+                Ok(outcome) => outcome,
+                Err(e) => Outcome::Failed(stem, e.to_string()),
+            };
+            if let Outcome::Imported(name) = &outcome {
+                contexts.push(name.clone());
+            }
+            outcomes.push(outcome);
+        }
+
+        let (mut imported, mut overwritten, mut skipped, mut failed) = (0, 0, 0, 0);
+        for outcome in &outcomes {
+            match outcome {
+                Outcome::Imported(name) => {
+                    imported += 1;
+                    println!("  {} {}", "+".green(), name);
+                }
+                Outcome::Overwritten(name) => {
+                    overwritten += 1;
+                    println!("  {} {} (overwritten)", "~".yellow(), name);
+                }
+                Outcome::Skipped(name) => {
+                    skipped += 1;
+                    println!("  {} {} (already exists)", "-".dimmed(), name.dimmed());
+                }
+                Outcome::Failed(name, reason) => {
+                    failed += 1;
+                    println!("  {} {}: {}", "✗".red(), name.bold(), reason);
+                }
+            }
+        }
+
+        println!(
+            "Imported {imported}, overwrote {overwritten}, skipped {skipped}, failed {failed} of {} files",
+            files.len()
+        );
+        if failed > 0 {
+            bail!("error: {} files could not be imported", failed);
+        }
+        Ok(())
+    }
+
+    fn import_dir_entry(
+        &self,
+        path: &Path,
+        stem: &str,
+        on_conflict: OnConflict,
+        suffix: Option<&str>,
+        contexts: &[String],
+    ) -> Result<Outcome> {
+        let name = self.validate_name(stem)?;
+
+        let content = fs::read_to_string(path)?;
+        let settings: Value =
+            serde_json::from_str(&content).map_err(|e| anyhow::anyhow!("invalid JSON: {e}"))?;
+        if let Some(issue) = lint_settings(&settings)
+            .into_iter()
+            .find(|issue| issue.severity == Severity::Error)
+        {
+            bail!("{}", issue.message);
+        }
+
+        // Shared context repos can carry metadata next to each file
+        let metadata_file = path.with_file_name(format!(".{stem}.meta.json"));
+        let metadata = if metadata_file.exists() {
+            let metadata: ContextMetadata =
+                serde_json::from_str(&fs::read_to_string(&metadata_file)?)
+                    .map_err(|e| anyhow::anyhow!("invalid metadata in {:?}: {e}", metadata_file))?;
+            metadata.check_requirements(stem)?;
+            Some(metadata_file)
+        } else {
+            None
+        };
+
+        let exists = |candidate: &str| contexts.iter().any(|ctx| ctx == candidate);
+        let (name, outcome): (String, fn(String) -> Outcome) = if !exists(&name) {
+            (name, Outcome::Imported)
+        } else {
+            match on_conflict {
+                OnConflict::Skip | OnConflict::Fail => return Ok(Outcome::Skipped(name)),
+                OnConflict::Overwrite => (name, Outcome::Overwritten),
+                OnConflict::Rename => {
+                    let base = format!("{name}{}", suffix.unwrap_or(DEFAULT_RENAME_SUFFIX));
+                    let mut candidate = base.clone();
+                    let mut counter = 2;
+                    while exists(&candidate) {
+                        candidate = format!("{base}-{counter}");
+                        counter += 1;
+                    }
+                    (self.validate_name(&candidate)?, Outcome::Imported)
+                }
+            }
+        };
+
+        self.ensure_protected_kept(&name, &settings)?;
+        let context_path = self.context_path(&name);
+        write_settings(&context_path, &content)?;
+        if let Some(metadata_file) = metadata {
+            fs::copy(metadata_file, metadata_path(&context_path))?;
+        }
+        Ok(outcome(name))
+    }
+}
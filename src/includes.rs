@@ -0,0 +1,84 @@
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::{expand_tilde, home_dir};
+use crate::context::ContextManager;
+use crate::merge::deep_append;
+
+/// Top-level key listing fragment files merged into a context when it is applied
+pub const INCLUDE_KEY: &str = "include";
+
+/// Stable fingerprint of `content`, stored in state
+fn fingerprint(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// Fragments whose content differs from when a context last applied them
+pub fn changed_fragments(
+    previous: &BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+) -> Vec<String> {
+    current
+        .iter()
+        .filter(|(path, hash)| previous.get(*path).is_some_and(|seen| seen != *hash))
+        .map(|(path, _)| path.clone())
+        .collect()
+}
+
+impl ContextManager {
+    /// Fragment paths listed under `include`; relative paths are relative to the
+    /// contexts directory
+    fn include_paths(&self, settings: &Value) -> Result<Vec<PathBuf>> {
+        let Some(include) = settings.get(INCLUDE_KEY) else {
+            return Ok(Vec::new());
+        };
+        let Some(entries) = include.as_array() else {
+            bail!("error: \"{}\" must be an array of file paths", INCLUDE_KEY);
+        };
+
+        let home_dir = home_dir()?;
+        entries
+            .iter()
+            .map(|entry| match entry.as_str() {
+                Some(path) => Ok(self.contexts_dir.join(expand_tilde(path, &home_dir))),
+                None => bail!("error: \"{}\" entries must be strings", INCLUDE_KEY),
+            })
+            .collect()
+    }
+
+    /// Merge the fragments a context includes into its settings and drop the
+    /// `include` key, so Claude Code only sees plain settings. The context's own
+    /// values win; fragments only add keys and list entries it lacks.
+    ///
+    /// Returns the fingerprint of every fragment, keyed by path.
+    pub(crate) fn resolve_includes(
+        &self,
+        settings: &mut Value,
+    ) -> Result<BTreeMap<String, String>> {
+        let paths = self.include_paths(settings)?;
+        let mut fingerprints = BTreeMap::new();
+        for path in paths {
+            let content = fs::read_to_string(&path)
+                .with_context(|| format!("error: failed to read fragment {:?}", path))?;
+            let mut fragment: Value = serde_json::from_str(&content)
+                .with_context(|| format!("error: fragment {:?} is not valid JSON", path))?;
+            if !fragment.is_object() {
+                bail!("error: fragment {:?} must be a JSON object", path);
+            }
+            // Fragments are flat; nested includes are not followed
+            if let Some(fragment) = fragment.as_object_mut() {
+                fragment.remove(INCLUDE_KEY);
+            }
+            deep_append(settings, &fragment);
+            fingerprints.insert(path.display().to_string(), fingerprint(&content));
+        }
+        if let Some(settings) = settings.as_object_mut() {
+            settings.remove(INCLUDE_KEY);
+        }
+        Ok(fingerprints)
+    }
+}
@@ -0,0 +1,120 @@
+use anyhow::{Context, Result};
+use colored::*;
+use std::fs;
+use std::path::Path;
+
+use crate::context::ContextManager;
+
+/// Launchers `--integration` can generate script commands for
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum Integration {
+    Raycast,
+    Alfred,
+}
+
+/// Quote a value for a POSIX shell script
+pub(crate) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Raycast script commands: a compact switcher and a full-output listing
+fn raycast_scripts(cctx: &str) -> Vec<(&'static str, String)> {
+    let switch = format!(
+        r#"#!/bin/bash
+
+# @raycast.schemaVersion 1
+# @raycast.title Switch Claude Context
+# @raycast.mode compact
+# @raycast.packageName cctx
+# @raycast.icon 🔄
+# @raycast.argument1 {{ "type": "text", "placeholder": "context" }}
+
+exec {cctx} "$1"
+"#
+    );
+    let list = format!(
+        r#"#!/bin/bash
+
+# @raycast.schemaVersion 1
+# @raycast.title List Claude Contexts
+# @raycast.mode fullOutput
+# @raycast.packageName cctx
+# @raycast.icon 📋
+
+{cctx} --porcelain | awk -F '\t' '{{ print ($2 == "current" ? "● " : "  ") $1 }}'
+"#
+    );
+    vec![("cctx-switch.sh", switch), ("cctx-list.sh", list)]
+}
+
+/// Alfred workflow scripts: a Script Filter emitting JSON items and its switch action
+fn alfred_scripts(cctx: &str) -> Vec<(&'static str, String)> {
+    let filter = format!(
+        r#"#!/bin/bash
+# Alfred Script Filter: lists contexts as Alfred JSON items
+# (context names cannot contain backslashes, so only quotes need escaping)
+
+{cctx} --porcelain | awk -F '\t' '
+BEGIN {{ printf "{{\"items\":["; sep = "" }}
+{{
+    name = $1
+    gsub(/"/, "\\\"", name)
+    subtitle = ($2 == "current") ? "current context" : (($2 == "previous") ? "previous context" : "")
+    printf "%s{{\"uid\":\"%s\",\"title\":\"%s\",\"subtitle\":\"%s\",\"arg\":\"%s\"}}", sep, name, name, subtitle, name
+    sep = ","
+}}
+END {{ print "]}}" }}'
+"#
+    );
+    let switch = format!(
+        r#"#!/bin/bash
+# Alfred Run Script action: switch to the context selected in the Script Filter
+
+exec {cctx} "$1"
+"#
+    );
+    vec![
+        ("cctx-alfred-filter.sh", filter),
+        ("cctx-alfred-switch.sh", switch),
+    ]
+}
+
+impl ContextManager {
+    /// Write launcher script commands into `dir`, built on `cctx --porcelain`
+    pub fn write_integration(&self, kind: Integration, dir: &Path) -> Result<()> {
+        let exe = std::env::current_exe().context("error: could not locate the cctx binary")?;
+        let level = format!("{:?}", self.settings_level).to_lowercase();
+        let cctx = format!("{} --level {}", shell_quote(&exe.to_string_lossy()), level);
+
+        let scripts = match kind {
+            Integration::Raycast => raycast_scripts(&cctx),
+            Integration::Alfred => alfred_scripts(&cctx),
+        };
+
+        fs::create_dir_all(dir)?;
+        for (file_name, content) in &scripts {
+            let path = dir.join(file_name);
+            fs::write(&path, content)
+                .with_context(|| format!("error: could not write {:?}", path))?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                fs::set_permissions(&path, fs::Permissions::from_mode(0o755))?;
+            }
+            println!("  {} {}", "✓".green(), path.display());
+        }
+
+        match kind {
+            Integration::Raycast => println!(
+                "Add {} as a script directory in Raycast (Settings → Extensions → Script Commands)",
+                dir.display()
+            ),
+            Integration::Alfred => println!(
+                "In an Alfred workflow, use {} as a Script Filter connected to a Run Script action running {}",
+                "cctx-alfred-filter.sh".cyan(),
+                "cctx-alfred-switch.sh \"{query}\"".cyan()
+            ),
+        }
+        Ok(())
+    }
+}
@@ -1,70 +1,162 @@
 use anyhow::Result;
 use colored::*;
-use dialoguer::{Confirm, FuzzySelect, Input};
+use dialoguer::{Confirm, FuzzySelect, Input, Select};
 use std::io::Write;
 use std::process::Command;
 use which::which;
 
+use crate::config::Picker;
 use crate::context::ContextManager;
+use crate::filter::ContextFilter;
+use crate::merge::MergeSummary;
+
+/// Merge picker entry that asks for a settings file path instead
+const FILE_CHOICE: &str = "file...";
 
 impl ContextManager {
-    pub fn interactive_select(&self) -> Result<()> {
+    pub fn interactive_select(&self, filter: &ContextFilter) -> Result<()> {
         let contexts = self.list_contexts()?;
         if contexts.is_empty() {
             println!("No contexts found. Create one with: cctx -n <name>");
             return Ok(());
         }
+        let contexts = self.filter_contexts(contexts, filter);
+        if contexts.is_empty() {
+            println!("No contexts match {}", filter.describe());
+            return Ok(());
+        }
 
         let current = self.get_current_context()?;
 
-        // Use fzf if available, otherwise use built-in fuzzy selector
-        if which("fzf").is_ok() && std::env::var("TERM").is_ok() {
-            self.interactive_select_with_fzf(&contexts, &current)
-        } else {
-            self.interactive_select_builtin(&contexts, &current)
+        if self.use_fzf() && self.interactive_select_with_fzf(&contexts, &current)? {
+            return Ok(());
         }
+        self.interactive_select_builtin(&contexts, &current)
+    }
+
+    /// Use fzf if available, otherwise use built-in fuzzy selector
+    fn use_fzf(&self) -> bool {
+        // Windows consoles usually do not set TERM even though fzf works there
+        match self.config.ui.picker {
+            Picker::Fzf => true,
+            Picker::Builtin => false,
+            Picker::Auto => {
+                which(self.config.ui.fzf.binary()).is_ok()
+                    && (std::env::var("TERM").is_ok() || cfg!(windows))
+            }
+        }
+    }
+
+    /// Command line that reruns cctx at this level and project, for fzf previews
+    /// (they run from fzf's directory, so the project is named explicitly)
+    fn preview_command(&self, args: &str) -> Option<String> {
+        let exe = std::env::current_exe().ok()?;
+        let level = format!("{:?}", self.settings_level).to_lowercase();
+        Some(format!(
+            "\"{}\" --level {} --dir \"{}\" {} 2>&1",
+            exe.display(),
+            level,
+            self.project_root.display(),
+            args
+        ))
     }
 
+    /// Pick a context with fzf. Returns false when fzf could not be run, so the
+    /// caller can fall back to the built-in picker.
     fn interactive_select_with_fzf(
         &self,
         contexts: &[String],
         current: &Option<String>,
-    ) -> Result<()> {
-        let mut cmd = Command::new("fzf");
+    ) -> Result<bool> {
+        let fzf = &self.config.ui.fzf;
+        let mut cmd = Command::new(fzf.binary());
         cmd.arg("--ansi");
         cmd.arg("--no-multi");
 
+        // Each line is "<name>\t<display>": only the display is shown, and the
+        // plain name field is read back, so colors and spaces never get in the way
+        cmd.arg("--delimiter").arg("\t");
+        cmd.arg("--with-nth").arg("2..");
+
         if let Some(ref current_ctx) = current {
             cmd.arg("--header").arg(format!("Current: {current_ctx}"));
         }
 
-        let mut child = cmd
+        // Preview the highlighted context (its metadata is printed on stderr)
+        if let Some(preview) = self.preview_command("--show {1}") {
+            cmd.arg("--preview").arg(preview);
+        }
+        cmd.args(&fzf.args);
+
+        let mut child = match cmd
             .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
-            .spawn()?;
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                eprintln!(
+                    "warning: could not run {} ({e}); using the built-in picker",
+                    fzf.binary()
+                );
+                return Ok(false);
+            }
+        };
 
+        let badges = self.lint_badges(contexts);
         if let Some(mut stdin) = child.stdin.take() {
             for ctx in contexts {
+                let badge = badges
+                    .get(ctx)
+                    .map(|badge| format!("{} ", badge.marker()))
+                    .unwrap_or_default();
+                let ownership = self
+                    .ownership(ctx)
+                    .map(|summary| format!(" ({summary})").dimmed().to_string())
+                    .unwrap_or_default();
                 if Some(ctx) == current.as_ref() {
-                    writeln!(stdin, "{} {}", ctx.green().bold(), "(current)".dimmed())?;
+                    writeln!(
+                        stdin,
+                        "{ctx}\t{}{} {}{}",
+                        badge,
+                        ctx.green().bold(),
+                        "(current)".dimmed(),
+                        ownership
+                    )?;
                 } else {
-                    writeln!(stdin, "{ctx}")?;
+                    writeln!(stdin, "{ctx}\t{badge}{ctx}{ownership}")?;
                 }
             }
         }
 
         let output = child.wait_with_output()?;
 
-        if output.status.success() {
-            let selected = String::from_utf8_lossy(&output.stdout);
-            let selected = selected.split_whitespace().next();
-
-            if let Some(name) = selected {
-                self.switch_context(name)?;
+        // 1 means no match and 130 means the picker was dismissed (Esc/Ctrl-C);
+        // both are a deliberate "nothing selected", anything else is an fzf error
+        match output.status.code() {
+            Some(0) => {}
+            Some(1) | Some(130) => return Ok(true),
+            _ => {
+                eprintln!(
+                    "warning: {} exited with {}; using the built-in picker",
+                    fzf.binary(),
+                    output.status
+                );
+                return Ok(false);
             }
         }
 
-        Ok(())
+        let selected = String::from_utf8_lossy(&output.stdout);
+        let name = selected
+            .lines()
+            .next()
+            .and_then(|line| line.split('\t').next())
+            .unwrap_or("");
+        if !name.is_empty() && Some(name) != current.as_deref() {
+            self.switch_context(name)?;
+        }
+
+        Ok(true)
     }
 
     fn interactive_select_builtin(
@@ -72,13 +164,22 @@ impl ContextManager {
         contexts: &[String],
         current: &Option<String>,
     ) -> Result<()> {
+        let badges = self.lint_badges(contexts);
         let items: Vec<String> = contexts
             .iter()
             .map(|ctx| {
+                let badge = badges
+                    .get(ctx)
+                    .map(|badge| format!("{} ", badge.symbol()))
+                    .unwrap_or_default();
+                let ownership = self
+                    .ownership(ctx)
+                    .map(|summary| format!(" ({summary})"))
+                    .unwrap_or_default();
                 if Some(ctx) == current.as_ref() {
-                    format!("{ctx} (current)")
+                    format!("{badge}{ctx} (current){ownership}")
                 } else {
-                    ctx.clone()
+                    format!("{badge}{ctx}{ownership}")
                 }
             })
             .collect();
@@ -139,8 +240,239 @@ impl ContextManager {
         self.rename_context(old_name, &new_name)
     }
 
-    pub fn interactive_create_context(&self) -> Result<()> {
+    /// Prompt for a name, create the context, then offer to edit and switch to
+    /// it; `edit` and `switch` answer those questions up front
+    pub fn interactive_create_context(&self, edit: bool, switch: bool) -> Result<()> {
         let name: String = Input::new().with_prompt("Context name").interact_text()?;
-        self.create_context(&name)
+        let name = self.create_context(&name)?;
+
+        let edit = edit
+            || Confirm::new()
+                .with_prompt("Edit it now?")
+                .default(false)
+                .interact_opt()?
+                .unwrap_or(false);
+        if edit {
+            self.edit_context(&name)?;
+        }
+
+        let switch = switch
+            || Confirm::new()
+                .with_prompt(format!("Switch to \"{name}\" now?"))
+                .default(true)
+                .interact_opt()?
+                .unwrap_or(false);
+        if switch {
+            self.switch_context(&name)?;
+        }
+        Ok(())
+    }
+
+    /// Ask how to handle an import whose name collides with an existing context
+    pub(crate) fn prompt_import_collision(
+        &self,
+        name: &str,
+        contexts: &[String],
+    ) -> Result<Option<String>> {
+        let choices = ["Rename", "Overwrite", "Skip"];
+        let selection = Select::new()
+            .with_prompt(format!("Context \"{name}\" already exists"))
+            .items(&choices)
+            .default(0)
+            .interact()?;
+
+        match selection {
+            0 => {
+                let new_name: String = Input::new()
+                    .with_prompt("New name")
+                    .validate_with(|input: &String| -> Result<(), String> {
+                        if contexts.contains(input) {
+                            Err(format!("context \"{input}\" already exists"))
+                        } else {
+                            Ok(())
+                        }
+                    })
+                    .interact_text()?;
+                Ok(Some(self.validate_name(&new_name)?))
+            }
+            1 => Ok(Some(name.to_string())),
+            _ => Ok(None),
+        }
+    }
+
+    /// Pick a merge source interactively, preview what it adds and confirm
+    pub fn interactive_merge_from(
+        &self,
+        target_context: &str,
+        full: bool,
+        show_diff: bool,
+        env_overwrite: bool,
+        summary: MergeSummary,
+    ) -> Result<()> {
+        let target_name = if target_context == "current" {
+            self.get_current_context()?
+        } else {
+            Some(target_context.to_string())
+        };
+
+        let mut candidates = Vec::new();
+        if target_context != "current" {
+            candidates.push("current".to_string());
+        }
+        // Live settings of the other levels; this level's is "current"
+        for level in ["user", "project", "local"] {
+            let live_path = self.live_settings_source(level)?;
+            if live_path
+                .as_ref()
+                .is_some_and(|path| path.exists() && *path != self.claude_settings_path)
+            {
+                candidates.push(level.to_string());
+            }
+        }
+        candidates.extend(
+            self.list_contexts()?
+                .into_iter()
+                .filter(|ctx| Some(ctx) != target_name.as_ref()),
+        );
+        let file_choice = FILE_CHOICE.to_string();
+        candidates.push(file_choice.clone());
+
+        let picked = if self.use_fzf() {
+            self.pick_merge_source_with_fzf(target_context, &candidates, full)?
+        } else {
+            None
+        };
+        let choice = match picked {
+            Some(Some(choice)) => choice,
+            Some(None) => return Ok(()),
+            None => {
+                let selection = FuzzySelect::new()
+                    .with_prompt(format!("Merge into \"{target_context}\" from"))
+                    .items(&candidates)
+                    .interact()?;
+                candidates[selection].clone()
+            }
+        };
+
+        let source = if choice == file_choice {
+            Input::<String>::new()
+                .with_prompt("Settings file path")
+                .interact_text()?
+        } else {
+            choice
+        };
+
+        let items = self.print_merge_preview(target_context, &source, full)?;
+        if items == 0 {
+            return Ok(());
+        }
+
+        let confirm = Confirm::new()
+            .with_prompt(format!("Merge {items} items?"))
+            .default(true)
+            .interact()?;
+
+        if !confirm {
+            return Ok(());
+        }
+
+        if full {
+            self.merge_from_full(target_context, &source, show_diff, env_overwrite, summary)
+        } else {
+            self.merge_from(target_context, &source, show_diff, summary)
+        }
+    }
+
+    /// Pick a merge source with fzf, previewing what each one would add.
+    /// Returns `None` when fzf could not be run, so the caller can fall back
+    /// to the built-in picker, and `Some(None)` when nothing was picked.
+    fn pick_merge_source_with_fzf(
+        &self,
+        target_context: &str,
+        candidates: &[String],
+        full: bool,
+    ) -> Result<Option<Option<String>>> {
+        let fzf = &self.config.ui.fzf;
+        let mut cmd = Command::new(fzf.binary());
+        cmd.arg("--no-multi");
+        cmd.arg("--header")
+            .arg(format!("Merge into \"{target_context}\" from"));
+
+        let full = if full { " --merge-full" } else { "" };
+        if let Some(preview) =
+            self.preview_command(&format!("--merge-preview {{}}{full} \"{target_context}\""))
+        {
+            cmd.arg("--preview").arg(preview);
+        }
+        cmd.args(&fzf.args);
+
+        let mut child = match cmd
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(e) => {
+                eprintln!(
+                    "warning: could not run {} ({e}); using the built-in picker",
+                    fzf.binary()
+                );
+                return Ok(None);
+            }
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            for candidate in candidates {
+                writeln!(stdin, "{candidate}")?;
+            }
+        }
+
+        let output = child.wait_with_output()?;
+        match output.status.code() {
+            Some(0) => {}
+            Some(1) | Some(130) => return Ok(Some(None)),
+            _ => {
+                eprintln!(
+                    "warning: {} exited with {}; using the built-in picker",
+                    fzf.binary(),
+                    output.status
+                );
+                return Ok(None);
+            }
+        }
+
+        let selected = String::from_utf8_lossy(&output.stdout);
+        Ok(Some(
+            selected
+                .lines()
+                .next()
+                .filter(|line| !line.is_empty())
+                .map(str::to_string),
+        ))
+    }
+
+    /// Print what merging `source` would add and return how many items that is.
+    /// Also the preview pane of the fzf merge picker (`--merge-preview`).
+    pub fn print_merge_preview(
+        &self,
+        target_context: &str,
+        source: &str,
+        full: bool,
+    ) -> Result<usize> {
+        if source == FILE_CHOICE {
+            println!("Pick this to enter the path of a settings file");
+            return Ok(0);
+        }
+
+        let items = self.preview_merge(target_context, source, full)?;
+        if items.is_empty() {
+            println!("Nothing to merge from '{}'", source.yellow());
+            return Ok(0);
+        }
+
+        println!("📋 Merging from '{}' would add:", source.green());
+        for item in &items {
+            println!("  • {item}");
+        }
+        Ok(items.len())
     }
 }
@@ -0,0 +1,355 @@
+use anyhow::{bail, Result};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+use crate::compression::read_settings;
+use crate::context::ContextManager;
+use crate::includes::INCLUDE_KEY;
+
+/// Revision of the Claude Code settings shape this build lints against;
+/// contexts can require a newer one through `min_schema_version` metadata
+pub const SETTINGS_SCHEMA_VERSION: u32 = 1;
+
+/// Permission lists that must be arrays of strings
+pub(crate) const PERMISSION_LISTS: &[&str] = &["allow", "deny", "ask", "additionalDirectories"];
+
+/// Values Claude Code accepts for `permissions.defaultMode`
+const DEFAULT_MODES: &[&str] = &["default", "acceptEdits", "plan", "bypassPermissions"];
+
+fn default_true() -> bool {
+    true
+}
+
+/// Lint settings, configured under `lint` in the config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintConfig {
+    /// Refuse to switch to contexts with lint errors (invalid JSON is always refused)
+    #[serde(default = "default_true")]
+    pub check_on_switch: bool,
+    /// Show a ✓ / ! badge next to each context in listings and the picker
+    #[serde(default = "default_true")]
+    pub badges: bool,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            check_on_switch: true,
+            badges: true,
+        }
+    }
+}
+
+/// Lint outcome of a context, summarized for listings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintBadge {
+    Clean,
+    Warnings,
+    Errors,
+}
+
+impl LintBadge {
+    fn from_issues(issues: &[LintIssue]) -> Self {
+        if issues.iter().any(|issue| issue.severity == Severity::Error) {
+            Self::Errors
+        } else if issues.is_empty() {
+            Self::Clean
+        } else {
+            Self::Warnings
+        }
+    }
+
+    /// Uncolored badge, for pickers that match on the displayed text
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Clean => "✓",
+            Self::Warnings | Self::Errors => "!",
+        }
+    }
+
+    pub fn marker(self) -> ColoredString {
+        match self {
+            Self::Clean => self.symbol().green(),
+            Self::Warnings => self.symbol().yellow(),
+            Self::Errors => self.symbol().red().bold(),
+        }
+    }
+}
+
+/// A cached badge, valid while the context file keeps its mtime and size
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedLint {
+    modified_ns: u64,
+    size: u64,
+    badge: LintBadge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Claude Code would reject or misread the settings
+    Error,
+    /// Valid, but probably not what was intended
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct LintIssue {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl LintIssue {
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+
+    fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+        }
+    }
+}
+
+/// Check settings against the shape Claude Code expects
+pub fn lint_settings(settings: &Value) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+
+    let Some(root) = settings.as_object() else {
+        issues.push(LintIssue::error("settings must be a JSON object"));
+        return issues;
+    };
+
+    if let Some(permissions) = root.get("permissions") {
+        match permissions.as_object() {
+            Some(permissions) => lint_permissions(permissions, &mut issues),
+            None => issues.push(LintIssue::error("permissions must be an object")),
+        }
+    }
+
+    if let Some(env) = root.get("env") {
+        match env.as_object() {
+            Some(env) => {
+                for (key, value) in env {
+                    if !value.is_string() {
+                        issues.push(LintIssue::warning(format!(
+                            "env.{key} is not a string and will be converted"
+                        )));
+                    }
+                }
+            }
+            None => issues.push(LintIssue::error("env must be an object")),
+        }
+    }
+
+    if root.get("hooks").is_some_and(|hooks| !hooks.is_object()) {
+        issues.push(LintIssue::error("hooks must be an object"));
+    }
+
+    if let Some(include) = root.get(INCLUDE_KEY) {
+        let is_paths = include
+            .as_array()
+            .is_some_and(|paths| paths.iter().all(Value::is_string));
+        if !is_paths {
+            issues.push(LintIssue::error(format!(
+                "{INCLUDE_KEY} must be an array of file paths"
+            )));
+        }
+    }
+
+    if root.get("model").is_some_and(|model| !model.is_string()) {
+        issues.push(LintIssue::error("model must be a string"));
+    }
+
+    issues
+}
+
+fn lint_permissions(permissions: &serde_json::Map<String, Value>, issues: &mut Vec<LintIssue>) {
+    for list in PERMISSION_LISTS {
+        let Some(value) = permissions.get(*list) else {
+            continue;
+        };
+        let Some(items) = value.as_array() else {
+            issues.push(LintIssue::error(format!(
+                "permissions.{list} must be an array"
+            )));
+            continue;
+        };
+
+        let mut seen = std::collections::HashSet::new();
+        for item in items {
+            match item.as_str() {
+                Some(rule) if !seen.insert(rule) => issues.push(LintIssue::warning(format!(
+                    "permissions.{list} contains \"{rule}\" more than once"
+                ))),
+                Some(_) => {}
+                None => issues.push(LintIssue::error(format!(
+                    "permissions.{list} must only contain strings, found {item}"
+                ))),
+            }
+        }
+    }
+
+    let rules = |list: &str| -> Vec<&str> {
+        permissions
+            .get(list)
+            .and_then(|v| v.as_array())
+            .map(|items| items.iter().filter_map(|item| item.as_str()).collect())
+            .unwrap_or_default()
+    };
+    let deny = rules("deny");
+    for rule in rules("allow") {
+        if deny.contains(&rule) {
+            issues.push(LintIssue::warning(format!(
+                "\"{rule}\" is both allowed and denied (deny wins)"
+            )));
+        }
+    }
+
+    if let Some(mode) = permissions.get("defaultMode") {
+        if !mode
+            .as_str()
+            .is_some_and(|mode| DEFAULT_MODES.contains(&mode))
+        {
+            issues.push(LintIssue::error(format!(
+                "permissions.defaultMode must be one of {}, found {mode}",
+                DEFAULT_MODES.join(", ")
+            )));
+        }
+    }
+}
+
+impl ContextManager {
+    fn lint_cache_path(&self) -> PathBuf {
+        self.contexts_dir.join(".cctx-cache").join("lint.json")
+    }
+
+    /// Lint badges for listings, relinting only contexts whose file changed
+    /// since the cached result. Empty when badges are disabled in the config.
+    pub fn lint_badges(&self, names: &[String]) -> BTreeMap<String, LintBadge> {
+        let mut badges = BTreeMap::new();
+        if !self.config.lint.badges {
+            return badges;
+        }
+
+        let cache_path = self.lint_cache_path();
+        let cache: BTreeMap<String, CachedLint> = fs::read_to_string(&cache_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        let mut updated = BTreeMap::new();
+        for name in names {
+            let Ok(metadata) = fs::metadata(self.context_path(name)) else {
+                continue;
+            };
+            let modified_ns = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|since| since.as_nanos() as u64)
+                .unwrap_or(0);
+            let size = metadata.len();
+
+            let entry = match cache.get(name) {
+                Some(cached) if cached.modified_ns == modified_ns && cached.size == size => {
+                    cached.clone()
+                }
+                _ => {
+                    let Ok(issues) = self.lint_context(name) else {
+                        continue;
+                    };
+                    CachedLint {
+                        modified_ns,
+                        size,
+                        badge: LintBadge::from_issues(&issues),
+                    }
+                }
+            };
+            badges.insert(name.clone(), entry.badge);
+            updated.insert(name.clone(), entry);
+        }
+
+        // The cache is only an optimization, so failing to write it is not an error
+        let changed = updated.len() != cache.len()
+            || updated.iter().any(|(name, entry)| {
+                cache.get(name).map(|c| c.modified_ns) != Some(entry.modified_ns)
+            });
+        if changed && !self.read_only {
+            if let Some(parent) = cache_path.parent() {
+                let _ = fs::create_dir_all(parent);
+            }
+            if let Ok(content) = serde_json::to_string_pretty(&updated) {
+                let _ = fs::write(&cache_path, content);
+            }
+        }
+
+        badges
+    }
+
+    /// Parse and lint a context, turning unparsable JSON into an error issue
+    pub fn lint_context(&self, name: &str) -> Result<Vec<LintIssue>> {
+        let content = read_settings(&self.context_path(name))?;
+        Ok(match serde_json::from_str::<Value>(&content) {
+            Ok(settings) => lint_settings(&settings),
+            Err(e) => vec![LintIssue::error(format!("invalid JSON: {e}"))],
+        })
+    }
+
+    /// Lint one context, or every context when no name is given
+    pub fn print_lint(&self, name: Option<&str>) -> Result<()> {
+        let names = match name {
+            Some(name) => {
+                if !self.context_path(name).exists() {
+                    bail!("error: no context exists with the name \"{}\"", name);
+                }
+                vec![name.to_string()]
+            }
+            None => self.list_contexts()?,
+        };
+
+        let mut failed = 0;
+        for name in &names {
+            let issues = self.lint_context(name)?;
+            if issues.is_empty() {
+                println!("{} {}", "✓".green(), name);
+                continue;
+            }
+
+            if self.strict || issues.iter().any(|issue| issue.severity == Severity::Error) {
+                failed += 1;
+            }
+            println!("{} {}", "!".yellow().bold(), name.bold());
+            for issue in &issues {
+                let label = match issue.severity {
+                    Severity::Error => "error".red().bold(),
+                    Severity::Warning => "warning".yellow(),
+                };
+                println!("    {label}: {}", issue.message);
+            }
+        }
+
+        if failed > 0 {
+            bail!(
+                "error: {} of {} contexts have lint {}",
+                failed,
+                names.len(),
+                if self.strict {
+                    "errors or warnings (--strict)"
+                } else {
+                    "errors"
+                }
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,232 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, Local};
+use colored::*;
+use dialoguer::console::Term;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+use crate::context::ContextManager;
+use crate::dates;
+
+/// Pager used when `$PAGER` is not set: quit if one screen is enough, keep colors
+const DEFAULT_PAGER: &str = "less -FRX";
+
+/// Columns `--columns` can show in the context listing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Column {
+    Name,
+    Lint,
+    Owner,
+    Description,
+    Tags,
+    Mtime,
+    Size,
+    LastUsed,
+}
+
+impl Column {
+    fn header(self) -> &'static str {
+        match self {
+            Self::Name => "NAME",
+            Self::Lint => "LINT",
+            Self::Owner => "OWNER",
+            Self::Description => "DESCRIPTION",
+            Self::Tags => "TAGS",
+            Self::Mtime => "MODIFIED",
+            Self::Size => "SIZE",
+            Self::LastUsed => "LAST USED",
+        }
+    }
+}
+
+/// How the context listing is cut into pages, which columns it shows and
+/// whether long output goes through a pager
+#[derive(Debug, Clone, Default)]
+pub struct ListView {
+    pub limit: Option<usize>,
+    /// 1-based page of `limit` contexts
+    pub page: Option<usize>,
+    /// Columns to show as a table; empty keeps the usual layout
+    pub columns: Vec<Column>,
+    pub pager: bool,
+}
+
+impl ListView {
+    /// The names on the requested page, and a footer saying where it is
+    pub fn paginate(&self, names: Vec<String>) -> Result<(Vec<String>, Option<String>)> {
+        let Some(limit) = self.limit else {
+            return Ok((names, None));
+        };
+        if limit == 0 {
+            bail!("error: --limit must be at least 1");
+        }
+
+        let total = names.len();
+        let pages = total.div_ceil(limit).max(1);
+        let page = self.page.unwrap_or(1);
+        if page == 0 || page > pages {
+            bail!("error: --page {} is out of range (1-{})", page, pages);
+        }
+
+        let start = (page - 1) * limit;
+        let shown: Vec<String> = names.into_iter().skip(start).take(limit).collect();
+        let mut footer = format!(
+            "Page {page} of {pages} ({}-{} of {total} contexts)",
+            start + 1,
+            start + shown.len()
+        );
+        if page < pages {
+            footer.push_str(&format!("; next: --page {}", page + 1));
+        }
+        Ok((shown, Some(footer)))
+    }
+}
+
+/// Print `lines`, through `$PAGER` when stdout is a terminal too short to show
+/// them all. A pager that cannot be started falls back to printing.
+pub fn print_paged(lines: &[String], enabled: bool) -> Result<()> {
+    let fits = Term::stdout()
+        .size_checked()
+        .map_or(true, |(rows, _)| lines.len() < usize::from(rows));
+    let pager = std::env::var("PAGER")
+        .ok()
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PAGER.to_string());
+    let mut parts = pager.split_whitespace();
+
+    let child = match parts.next() {
+        Some(program) if enabled && !fits && io::stdout().is_terminal() => Command::new(program)
+            .args(parts)
+            .stdin(Stdio::piped())
+            .spawn()
+            .ok(),
+        _ => None,
+    };
+    let Some(mut child) = child else {
+        for line in lines {
+            println!("{line}");
+        }
+        return Ok(());
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        for line in lines {
+            // The pager was quit before reading everything
+            if writeln!(stdin, "{line}").is_err() {
+                break;
+            }
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
+
+impl ContextManager {
+    /// The listing as a table of `columns`, header first; details that cannot be
+    /// read are shown as `-`
+    pub(crate) fn column_lines(
+        &self,
+        names: &[String],
+        current: Option<&String>,
+        columns: &[Column],
+    ) -> Result<Vec<String>> {
+        let state = self.load_state()?;
+        let badges = if columns.contains(&Column::Lint) {
+            self.lint_badges(names)
+        } else {
+            Default::default()
+        };
+
+        // Plain text decides the widths; colors are added after padding
+        let rows: Vec<Vec<(String, Option<ColoredString>)>> = names
+            .iter()
+            .map(|name| {
+                let metadata = self.load_metadata(name).unwrap_or_default();
+                let file = fs::metadata(self.context_path(name)).ok();
+                columns
+                    .iter()
+                    .map(|column| match column {
+                        Column::Name if Some(name) == current => {
+                            (name.clone(), Some(name.green().bold()))
+                        }
+                        Column::Name => (name.clone(), None),
+                        Column::Lint => match badges.get(name) {
+                            Some(badge) => (badge.symbol().to_string(), Some(badge.marker())),
+                            None => ("-".to_string(), None),
+                        },
+                        Column::Owner => (metadata.owner.clone().unwrap_or("-".into()), None),
+                        Column::Description => (self.description(name).unwrap_or("-".into()), None),
+                        Column::Tags if metadata.tags.is_empty() => ("-".to_string(), None),
+                        Column::Tags => (metadata.tags.join(","), None),
+                        Column::Mtime => (
+                            file.as_ref()
+                                .and_then(|file| file.modified().ok())
+                                .map(|modified| {
+                                    dates::format_datetime(
+                                        &DateTime::<Local>::from(modified),
+                                        self.date_style,
+                                    )
+                                })
+                                .unwrap_or("-".into()),
+                            None,
+                        ),
+                        Column::Size => (
+                            file.as_ref()
+                                .map_or("-".into(), |file| file.len().to_string()),
+                            None,
+                        ),
+                        Column::LastUsed => (
+                            state
+                                .last_used
+                                .get(name)
+                                .map(|raw| dates::format_rfc3339(raw, self.date_style))
+                                .unwrap_or("never".into()),
+                            None,
+                        ),
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let widths: Vec<usize> = columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| {
+                rows.iter()
+                    .map(|row| row[i].0.chars().count())
+                    .chain([column.header().len()])
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let last = columns.len().saturating_sub(1);
+
+        let header = columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| match i {
+                _ if i == last => column.header().to_string(),
+                _ => format!("{:<width$}", column.header(), width = widths[i]),
+            })
+            .collect::<Vec<_>>()
+            .join("  ");
+        let mut lines = vec![format!("  {}", header.dimmed())];
+        for row in rows {
+            let cells: Vec<String> = row
+                .into_iter()
+                .enumerate()
+                .map(|(i, (plain, colored))| {
+                    let padding = match i {
+                        _ if i == last => 0,
+                        _ => widths[i] - plain.chars().count(),
+                    };
+                    let text = colored.map_or(plain, |colored| colored.to_string());
+                    format!("{text}{}", " ".repeat(padding))
+                })
+                .collect();
+            lines.push(format!("  {}", cells.join("  ")));
+        }
+        Ok(lines)
+    }
+}
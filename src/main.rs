@@ -1,47 +1,287 @@
+mod analyze;
+mod annotate;
+mod atomic;
+mod backup;
+mod budget;
+mod cleanup;
 mod cli;
+mod clipboard;
 mod completions;
+mod compression;
+mod config;
+mod conflicts;
 mod context;
+mod dates;
+mod dedupe;
+mod diff;
+mod doctor;
+mod env_export;
+mod fetch;
+mod filter;
+#[cfg(feature = "dev")]
+mod fixtures;
+mod fork;
+mod groups;
+mod health;
+mod ignore;
+mod import_dir;
+mod includes;
+mod integration;
 mod interactive;
+mod lint;
+mod listing;
+mod managed;
 mod merge;
+mod metadata;
+mod migrate;
+mod naming;
+mod parallel;
+mod permissions;
+mod pretty;
+mod progress;
+mod projects;
+mod prompt;
+mod quarantine;
+mod recording;
+mod repair;
+mod report;
+mod review;
+mod run;
+mod scaffold;
+mod serve;
+mod session_hook;
+mod setup;
+mod share;
+mod signing;
+mod similarity;
+mod snapshot;
 mod state;
+mod status;
+mod statusline;
+mod template;
 
 use anyhow::Result;
 use clap::Parser;
+use std::io::IsTerminal;
 
 use cli::Cli;
 use completions::print_enhanced_completions;
+use config::Config;
 use context::ContextManager;
+use context::ManagerOptions;
 use context::SettingsLevel;
+use dates::DateStyle;
+use filter::ContextFilter;
+use listing::ListView;
+use merge::MergeSummary;
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    run(cli)
+}
+
+fn run(cli: Cli) -> Result<()> {
+    // Before anything reads the clock or the local time zone
+    if cli.deterministic {
+        dates::freeze_clock(dates::deterministic_epoch());
+        std::env::set_var("TZ", "UTC");
+        std::env::set_var("LC_ALL", "C");
+    }
 
     // Handle completions first
     if let Some(shell) = cli.completions {
         return print_enhanced_completions(shell);
     }
 
-    // Determine settings level: default to User, explicit flags override
-    let settings_level = if cli.local {
+    #[cfg(feature = "dev")]
+    if let Some(dir) = cli.dev_fixtures {
+        return fixtures::generate_fixtures(std::path::Path::new(&dir));
+    }
+
+    // Determine settings level: default to User. The level flags are mutually
+    // exclusive (enforced by clap), so at most one of them is set.
+    let settings_level = if let Some(level) = cli.level.clone() {
+        level
+    } else if cli.local {
         SettingsLevel::Local
     } else if cli.in_project {
         SettingsLevel::Project
-    } else {
-        // Default: always use user level for predictable behavior
+    } else if cli.user {
         SettingsLevel::User
+    } else {
+        // Default: user level for predictable behavior, unless the config picks one
+        Config::load_default()?
+            .ui
+            .default_level
+            .unwrap_or(SettingsLevel::User)
     };
 
-    let manager = ContextManager::new_with_level(settings_level)?;
+    let options = ManagerOptions {
+        discover: !cli.no_discover,
+        create: cli.create,
+        target: cli.target.clone(),
+        read_only: cli.current || cli.prompt || cli.session_start || cli.read_only,
+        require_signed: cli.require_signed,
+        force: cli.force,
+        yes: cli.yes,
+        strict: cli.strict,
+        progress: std::io::stderr().is_terminal() && !cli.porcelain && !cli.deterministic,
+        interactive: !cli.serve,
+        date_style: if cli.utc {
+            Some(DateStyle::Utc)
+        } else if cli.iso {
+            Some(DateStyle::Iso)
+        } else {
+            None
+        },
+        project_dir: cli.dir.clone(),
+    };
+    let manager = ContextManager::new_with_options(settings_level, &options)?;
+
+    if !manager.config.ui.color || cli.porcelain || cli.deterministic {
+        colored::control::set_override(false);
+    }
+
+    // Offer setup on a bare `cctx` before any config exists
+    if cli.setup || (std::env::args_os().len() == 1 && manager.is_first_run()) {
+        return manager.first_run_setup();
+    }
 
     // Handle special modes first
+    if cli.complete_contexts {
+        return manager.print_completion_names();
+    }
+
+    if cli.current && cli.verbose {
+        return manager.print_current_verbose();
+    }
+
     if cli.current {
-        if let Some(current) = manager.get_current_context()? {
+        // A running --serve daemon already knows, without touching the disk
+        let current = match manager.query_daemon() {
+            Some(index) => index.current,
+            None => manager.get_current_context()?,
+        };
+        if let Some(current) = current {
             println!("{current}");
         }
         return Ok(());
     }
 
+    if cli.prompt {
+        return manager.print_prompt(cli.all_levels, &options);
+    }
+
+    if cli.session_start {
+        return manager.session_start(&options);
+    }
+
+    if cli.hook_snippet {
+        return manager.print_hook_snippet();
+    }
+
+    if let Some(action) = cli.statusline {
+        return manager.statusline(action, cli.context.as_deref());
+    }
+
+    if let Some(name) = cli.run {
+        let code = manager.run_with_context(&name, &cli.command, !cli.no_env)?;
+        std::process::exit(code);
+    }
+
+    if let Some(name) = cli.require {
+        let code = manager.exec_requiring(&name, &cli.command, cli.switch)?;
+        std::process::exit(code);
+    }
+
+    if let Some(pattern) = cli.expand {
+        return manager.explain_rule(&pattern, cli.context.as_deref());
+    }
+
+    if let Some(name) = cli.check {
+        let input = match cli.input {
+            Some(input) => input,
+            None if !cli.command.is_empty() => cli.command.join(" "),
+            None => {
+                return Err(anyhow::anyhow!(
+                "error: no input given; usage: cctx --check <context> [--tool <tool>] -- <command>"
+            ))
+            }
+        };
+        let decision = manager.check_permission(&name, &cli.tool, &input)?;
+        std::process::exit(decision.exit_code());
+    }
+
+    if let Some(kind) = cli.integration {
+        return manager.write_integration(kind, std::path::Path::new(&cli.out_dir));
+    }
+
+    if cli.serve {
+        return manager.serve(cli.port.unwrap_or_else(serve::daemon_port));
+    }
+
+    if cli.suggest_cleanup {
+        return manager.suggest_cleanup(cli.older_than_months);
+    }
+
+    if cli.backups {
+        if let Some(id) = cli.restore {
+            return manager.restore_backup(&id);
+        }
+        if cli.prune {
+            return manager.prune_backups(cli.keep);
+        }
+        return manager.show_backups();
+    }
+
+    if let Some(script) = cli.record {
+        return manager.start_recording(&script);
+    }
+
+    if cli.record_stop {
+        return manager.stop_recording();
+    }
+
+    if let Some(script) = cli.replay {
+        return manager.replay(&script, cli.context.as_deref());
+    }
+
+    if let Some(group) = cli.group {
+        return manager.use_group(&group, &options);
+    }
+
+    if cli.conflicts {
+        return manager.review_conflicts();
+    }
+
+    if cli.migrate {
+        return manager.migrate_legacy();
+    }
+
+    if let Some(format) = cli.report {
+        return manager.print_report(format);
+    }
+
+    if cli.status {
+        return manager.print_status(&options);
+    }
+
+    if cli.doctor {
+        return manager.doctor(cli.fix);
+    }
+
+    if cli.dedupe {
+        return manager.dedupe();
+    }
+
+    if cli.projects {
+        return manager.projects(&options, cli.context.as_deref());
+    }
+
     if cli.unset {
+        if cli.restore_previous {
+            return manager.unset_and_restore_previous();
+        }
         return manager.unset_context();
     }
 
@@ -65,75 +305,233 @@ fn main() -> Result<()> {
     }
 
     if cli.new {
-        if let Some(name) = cli.context {
-            return manager.create_context(&name);
+        let Some(name) = cli.context else {
+            if cli.stdin {
+                return Err(anyhow::anyhow!("error: context name required for --stdin"));
+            }
+            return manager.interactive_create_context(cli.edit, cli.use_context);
+        };
+        let name = if let Some(template) = cli.template {
+            manager.create_from_template(&name, &template, &cli.var)?
+        } else if cli.stdin {
+            manager.create_context_from_stdin(&name)?
         } else {
-            return manager.interactive_create_context();
+            manager.create_context(&name)?
+        };
+        if cli.edit {
+            manager.edit_context(&name)?;
         }
+        if cli.use_context {
+            manager.switch_context(&name)?;
+        }
+        return Ok(());
     }
 
-    if cli.edit {
-        let context = if let Some(ctx) = cli.context {
-            ctx
-        } else if let Some(current) = manager.get_current_context()? {
-            current
-        } else {
-            return Err(anyhow::anyhow!("error: no current context set"));
+    if let Some(name) = cli.fork {
+        let changes = fork::ForkChanges {
+            allow: cli.allow,
+            deny: cli.deny,
+            set: cli.set,
         };
+        manager.fork_context(&name, &changes)?;
+        return Ok(());
+    }
+
+    if cli.edit {
+        let context = manager.named_or_current(cli.context.as_deref())?;
         return manager.edit_context(&context);
     }
 
     if cli.show {
-        let context = if let Some(ctx) = cli.context {
-            ctx
-        } else if let Some(current) = manager.get_current_context()? {
-            current
-        } else {
-            return Err(anyhow::anyhow!("error: no current context set"));
-        };
-        return manager.show_context(&context);
+        let context = manager.named_or_current(cli.context.as_deref())?;
+        if cli.annotate {
+            return manager.show_annotated(&context);
+        }
+        return manager.show_context(&context, cli.summary, cli.flat || cli.porcelain);
+    }
+
+    if cli.env_export {
+        let context = manager.named_or_current(cli.context.as_deref())?;
+        let shell = cli.shell.unwrap_or_else(env_export::EnvShell::detect);
+        return manager.print_env_export(&context, shell);
+    }
+
+    if cli.diff_user {
+        let context = manager.named_or_current(cli.context.as_deref())?;
+        return manager.diff_against_user(&context);
+    }
+
+    if let Some(level) = cli.intended_level {
+        let context = manager.named_or_current(cli.context.as_deref())?;
+        return manager.set_intended_level(&context, level);
+    }
+
+    if let Some(base) = cli.diff_all_against {
+        return manager.diff_all_against(&base, &ContextFilter::new(cli.filter, cli.tag));
+    }
+
+    if cli.review_start || cli.review_approve {
+        let context = manager.named_or_current(cli.context.as_deref())?;
+        if cli.review_approve {
+            return manager.review_approve(&context, cli.reviewer.as_deref());
+        }
+        return manager.review_start(&context, cli.reviewer.as_deref());
+    }
+
+    if cli.snapshot || cli.snapshots || cli.restore_snapshot.is_some() {
+        let context = manager.named_or_current(cli.context.as_deref())?;
+        if let Some(label) = cli.restore_snapshot {
+            return manager.restore_snapshot(&context, &label);
+        }
+        if cli.snapshots {
+            return manager.show_snapshots(&context);
+        }
+        return manager.snapshot_context(&context, cli.label.as_deref());
+    }
+
+    if cli.lint {
+        return manager.print_lint(cli.context.as_deref());
+    }
+
+    if cli.analyze {
+        let context = manager.named_or_current(cli.context.as_deref())?;
+        return manager.analyze_context(&context, cli.unused_days);
+    }
+
+    if cli.sign || cli.verify {
+        let context = manager.named_or_current(cli.context.as_deref())?;
+        if cli.sign {
+            return manager.sign_context(&context);
+        }
+        return manager.print_verification(&context);
     }
 
     if cli.export {
-        let context = if let Some(ctx) = cli.context {
-            ctx
-        } else if let Some(current) = manager.get_current_context()? {
-            current
-        } else {
-            return Err(anyhow::anyhow!("error: no current context set"));
-        };
+        let context = manager.named_or_current(cli.context.as_deref())?;
+        if cli.clipboard {
+            return manager.export_context_to_clipboard(&context);
+        }
         return manager.export_context(&context);
     }
 
+    if cli.share {
+        let context = manager.named_or_current(cli.context.as_deref())?;
+        return manager.share_context(&context);
+    }
+
     if cli.import {
         if let Some(name) = cli.context {
-            return manager.import_context(&name);
+            if cli.merge {
+                let (source, buffer) = if cli.clipboard {
+                    ("import:clipboard".to_string(), clipboard::clipboard_text()?)
+                } else {
+                    match cli.from_file.as_deref() {
+                        Some(path) if path != "-" => (
+                            format!("import:{path}"),
+                            context::read_import_input(Some(path))?,
+                        ),
+                        _ => (
+                            "import:stdin".to_string(),
+                            context::read_import_input(None)?,
+                        ),
+                    }
+                };
+                // Recorded in the merge history under `source`, so it can be unmerged
+                let Some(target) = manager.import_merge_target(&name, &buffer)? else {
+                    return Ok(());
+                };
+                let summary = merge_summary(cli.verbose, cli.quiet, &manager);
+                if cli.merge_full {
+                    return manager.merge_content_full(
+                        &target,
+                        &source,
+                        &buffer,
+                        cli.show_diff,
+                        cli.env_overwrite,
+                        summary,
+                    );
+                }
+                return manager.merge_content(&target, &source, &buffer, cli.show_diff, summary);
+            }
+            if cli.clipboard {
+                return manager.import_context_from_clipboard(&name, cli.suffix.as_deref());
+            }
+            return manager.import_context(&name, cli.suffix.as_deref(), cli.from_file.as_deref());
+        } else {
+            return Err(anyhow::anyhow!("error: context name required for import"));
+        }
+    }
+
+    if let Some(dir) = cli.import_dir {
+        return manager.import_dir(
+            std::path::Path::new(&dir),
+            cli.on_conflict,
+            cli.suffix.as_deref(),
+        );
+    }
+
+    if let Some(url) = cli.import_url {
+        if let Some(name) = cli.context {
+            return manager.import_context_from_url(
+                &name,
+                &url,
+                cli.suffix.as_deref(),
+                cli.offline,
+            );
         } else {
             return Err(anyhow::anyhow!("error: context name required for import"));
         }
     }
 
+    if let Some(source) = cli.merge_preview {
+        let target = cli.context.as_deref().unwrap_or("current");
+        return manager
+            .print_merge_preview(target, &source, cli.merge_full)
+            .map(|_| ());
+    }
+
     // Handle merge operations
     if let Some(source) = cli.merge_from {
         let target = cli.context.as_deref().unwrap_or("current");
+        let summary = merge_summary(cli.verbose, cli.quiet, &manager);
+        let Some(source) = source else {
+            return manager.interactive_merge_from(
+                target,
+                cli.merge_full,
+                cli.show_diff,
+                cli.env_overwrite,
+                summary,
+            );
+        };
         if cli.merge_full {
-            return manager.merge_from_full(target, &source);
+            return manager.merge_from_full(
+                target,
+                &source,
+                cli.show_diff,
+                cli.env_overwrite,
+                summary,
+            );
         } else {
-            return manager.merge_from(target, &source);
+            return manager.merge_from(target, &source, cli.show_diff, summary);
         }
     }
 
     if let Some(source) = cli.unmerge {
         let target = cli.context.as_deref().unwrap_or("current");
         if cli.merge_full {
-            return manager.unmerge_from_full(target, &source);
+            return manager.unmerge_from_full(target, &source, cli.show_diff);
         } else {
-            return manager.unmerge_from(target, &source);
+            return manager.unmerge_from(target, &source, cli.show_diff);
         }
     }
 
     if cli.merge_history {
-        return manager.show_merge_history(cli.context.as_deref());
+        return manager.show_merge_history(
+            cli.context.as_deref(),
+            cli.graph,
+            cli.compact,
+            cli.since.as_deref(),
+        );
     }
 
     // Normal operation
@@ -148,13 +546,33 @@ fn main() -> Result<()> {
         }
         None => {
             // No argument - show list or interactive select
-            if std::env::var("CCTX_INTERACTIVE").unwrap_or_default() == "1" {
+            let filter = ContextFilter::new(cli.filter, cli.tag);
+            if cli.porcelain {
+                manager.print_porcelain_list(&filter)
+            } else if std::env::var("CCTX_INTERACTIVE").unwrap_or_default() == "1" {
                 // Interactive mode
-                manager.interactive_select()
+                manager.interactive_select(&filter)
             } else {
                 // List contexts
-                manager.list_contexts_with_current(cli.quiet)
+                let view = ListView {
+                    limit: cli.limit,
+                    page: cli.page,
+                    columns: cli.columns,
+                    pager: !cli.no_pager,
+                };
+                manager.list_contexts_with_current(cli.quiet, &filter, &view)
             }
         }
     }
 }
+
+/// How many merged items to print: --verbose and --quiet override `merge.summary`
+fn merge_summary(verbose: bool, quiet: bool, manager: &ContextManager) -> MergeSummary {
+    if verbose {
+        MergeSummary::Full
+    } else if quiet {
+        MergeSummary::Counts
+    } else {
+        manager.config.merge.summary
+    }
+}
@@ -0,0 +1,90 @@
+use anyhow::{bail, Result};
+use serde_json::Value;
+use std::path::PathBuf;
+
+use crate::context::ContextManager;
+use crate::merge::{get_array, MergeManager};
+
+/// Merge source naming the managed (enterprise) policy settings; merges from it
+/// are recorded as protected
+pub const MANAGED_SOURCE: &str = "managed";
+
+/// Where administrators deploy Claude Code's managed settings. `CCTX_MANAGED_SETTINGS`
+/// points elsewhere, e.g. to try a policy before it is rolled out.
+pub fn managed_settings_path() -> PathBuf {
+    if let Some(path) = std::env::var_os("CCTX_MANAGED_SETTINGS") {
+        return PathBuf::from(path);
+    }
+    if cfg!(target_os = "macos") {
+        PathBuf::from("/Library/Application Support/ClaudeCode/managed-settings.json")
+    } else if cfg!(windows) {
+        PathBuf::from(r"C:\ProgramData\ClaudeCode\managed-settings.json")
+    } else {
+        PathBuf::from("/etc/claude-code/managed-settings.json")
+    }
+}
+
+/// Whether a merged history item is still in `settings`. Items are recorded as
+/// `env:KEY`, `allow:<rule>` / `permissions.allow:<rule>` for list entries, and a
+/// bare key for other fields; `overwrite:` and `append:` mark changes to a field.
+fn item_present(settings: &Value, item: &str) -> bool {
+    let item = item
+        .strip_prefix("overwrite:")
+        .or_else(|| item.strip_prefix("append:"))
+        .unwrap_or(item);
+    if let Some(key) = item.strip_prefix("env:") {
+        return settings
+            .get("env")
+            .and_then(Value::as_object)
+            .is_some_and(|env| env.contains_key(key));
+    }
+    if let Some((label, entry)) = item.split_once(':') {
+        let list = get_array(settings, label)
+            .or_else(|| get_array(settings, &format!("permissions.{label}")));
+        return list.is_some_and(|list| list.iter().any(|v| v.as_str() == Some(entry)));
+    }
+    settings.get(item).is_some()
+}
+
+/// Refuse to unmerge a source whose entries in `context_name` are protected
+pub(crate) fn ensure_unmergeable(
+    merge_manager: &MergeManager,
+    context_name: &str,
+    source: &str,
+) -> Result<()> {
+    let history = merge_manager.load_history(context_name)?;
+    if history
+        .iter()
+        .any(|entry| entry.source == source && entry.protected)
+    {
+        bail!(
+            "error: entries merged into \"{}\" from '{}' are required by the managed policy and cannot be unmerged",
+            context_name,
+            source
+        );
+    }
+    Ok(())
+}
+
+impl ContextManager {
+    /// Refuse settings for `context_name` that drop entries merged from the managed
+    /// policy, which the organisation requires
+    pub(crate) fn ensure_protected_kept(&self, context_name: &str, settings: &Value) -> Result<()> {
+        let history = self.merge_manager().load_history(context_name)?;
+        let missing: Vec<&str> = history
+            .iter()
+            .filter(|entry| entry.protected)
+            .flat_map(|entry| entry.merged_items.iter())
+            .filter(|item| !item_present(settings, item))
+            .map(String::as_str)
+            .collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        bail!(
+            "error: \"{}\" must keep entries required by the managed policy: {}",
+            context_name,
+            missing.join(", ")
+        );
+    }
+}
@@ -1,34 +1,389 @@
 use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::*;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use crate::atomic::write_atomic;
+use crate::dates::{self, DateStyle};
+use crate::quarantine::{quarantine, warn_corrupt, CorruptFiles};
 
 /// Represents the merge history for tracking what was merged from where
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MergeHistory {
     pub source: String,
-    pub timestamp: String,
+    /// Stored as RFC 3339 in UTC
+    #[serde(with = "dates::utc_rfc3339")]
+    pub timestamp: DateTime<Utc>,
     pub merged_items: Vec<String>,
     pub full_merge: bool,
+    /// Merged from the managed policy; these items cannot be unmerged or edited away
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub protected: bool,
+    /// Values the `overwrite:` and `append:` items replaced, restored on unmerge
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub previous_values: BTreeMap<String, Value>,
+}
+
+/// Limits beyond which older history entries are compacted, configured under
+/// `merge.history`. Compaction squashes entries per source, so unmerge keeps working.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct HistoryRetention {
+    /// Keep at most this many entries verbatim
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_entries: Option<usize>,
+    /// Keep entries younger than this many days verbatim
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u32>,
+}
+
+impl HistoryRetention {
+    fn is_unlimited(&self) -> bool {
+        self.max_entries.is_none() && self.max_age_days.is_none()
+    }
+}
+
+/// Squash entries into one per source and merge kind, keeping every merged
+/// item (which is all unmerge needs) and the newest timestamp
+fn squash(entries: Vec<MergeHistory>) -> Vec<MergeHistory> {
+    let mut squashed: Vec<MergeHistory> = Vec::new();
+    for entry in entries {
+        match squashed
+            .iter_mut()
+            .find(|s| s.source == entry.source && s.full_merge == entry.full_merge)
+        {
+            Some(existing) => {
+                for item in entry.merged_items {
+                    if !existing.merged_items.contains(&item) {
+                        existing.merged_items.push(item);
+                    }
+                }
+                existing.timestamp = existing.timestamp.max(entry.timestamp);
+                existing.protected |= entry.protected;
+                // The oldest value is the one from before the source changed anything
+                for (item, value) in entry.previous_values {
+                    existing.previous_values.entry(item).or_insert(value);
+                }
+            }
+            None => squashed.push(entry),
+        }
+    }
+    squashed
+}
+
+/// Compact the entries that fall outside `retention`; entries inside it are kept as is.
+/// With unlimited retention everything is squashed.
+pub fn compact_history(
+    history: Vec<MergeHistory>,
+    retention: &HistoryRetention,
+) -> Vec<MergeHistory> {
+    if retention.is_unlimited() {
+        return squash(history);
+    }
+
+    let cutoff = retention
+        .max_age_days
+        .map(|days| dates::now().with_timezone(&Utc) - chrono::Duration::days(i64::from(days)));
+    let keep_from = history
+        .len()
+        .saturating_sub(retention.max_entries.unwrap_or(usize::MAX));
+
+    let (mut old, mut recent) = (Vec::new(), Vec::new());
+    for (i, entry) in history.into_iter().enumerate() {
+        let expired = cutoff.is_some_and(|cutoff| entry.timestamp < cutoff);
+        if i < keep_from || expired {
+            old.push(entry);
+        } else {
+            recent.push(entry);
+        }
+    }
+
+    let mut compacted = squash(old);
+    compacted.append(&mut recent);
+    compacted
+}
+
+/// List-valued settings merged with deduplication unless the config overrides them
+pub const DEFAULT_ARRAY_PATHS: &[&str] = &[
+    "permissions.allow",
+    "permissions.deny",
+    "permissions.ask",
+    "permissions.additionalDirectories",
+];
+
+/// How many merged items a merge prints, configured as `merge.summary` and
+/// overridden with --verbose or --quiet
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeSummary {
+    /// Only the number of merged items
+    Counts,
+    /// The first few items, then how many more there are (default)
+    #[default]
+    Short,
+    /// Every merged item
+    Full,
+}
+
+/// Items listed by a short summary before "... and N more"
+pub const SHORT_SUMMARY_ITEMS: usize = 5;
+
+/// How `merge_full` resolves a key present in both source and target
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MergeStrategy {
+    /// Leave the target value untouched (default)
+    #[default]
+    KeepTarget,
+    /// Replace the target value with the source value
+    Overwrite,
+    /// Append missing array items and object keys, recursively
+    Append,
+}
+
+/// An env var set in both target and source to different values
+pub struct EnvConflict {
+    pub key: String,
+    pub target: Value,
+    pub source: Value,
+}
+
+/// Env vars a full merge would keep (or, when overwriting, replace) in the target
+pub fn env_conflicts(target: &Value, source: &Value) -> Vec<EnvConflict> {
+    let (Some(target_env), Some(source_env)) = (
+        target.get("env").and_then(|e| e.as_object()),
+        source.get("env").and_then(|e| e.as_object()),
+    ) else {
+        return Vec::new();
+    };
+
+    source_env
+        .iter()
+        .filter_map(|(key, source_value)| {
+            let target_value = target_env.get(key)?;
+            (target_value != source_value).then(|| EnvConflict {
+                key: key.clone(),
+                target: target_value.clone(),
+                source: source_value.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Print conflicting env vars as a key / target / source table
+pub fn print_env_conflicts(conflicts: &[EnvConflict]) {
+    let show = |value: &Value| match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    let rows: Vec<[String; 3]> = conflicts
+        .iter()
+        .map(|c| [c.key.clone(), show(&c.target), show(&c.source)])
+        .collect();
+
+    let header = ["KEY", "TARGET", "SOURCE"].map(String::from);
+    let mut widths = header.clone().map(|h| h.len());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    for row in std::iter::once(&header).chain(&rows) {
+        println!(
+            "  {:<key$}  {:<target$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            key = widths[0],
+            target = widths[1]
+        );
+    }
+}
+
+/// Recursively add array items and object keys from source missing in target.
+///
+/// Scalars already present in the target are kept. Returns whether anything changed.
+pub(crate) fn deep_append(target: &mut Value, source: &Value) -> bool {
+    match (target, source) {
+        (Value::Array(target_items), Value::Array(source_items)) => {
+            let mut changed = false;
+            for item in source_items {
+                if !target_items.contains(item) {
+                    target_items.push(item.clone());
+                    changed = true;
+                }
+            }
+            changed
+        }
+        (Value::Object(target_obj), Value::Object(source_obj)) => {
+            let mut changed = false;
+            for (key, value) in source_obj {
+                match target_obj.get_mut(key) {
+                    Some(existing) => changed |= deep_append(existing, value),
+                    None => {
+                        target_obj.insert(key.clone(), value.clone());
+                        changed = true;
+                    }
+                }
+            }
+            changed
+        }
+        _ => false,
+    }
+}
+
+/// Look up the array at a dotted path such as `permissions.allow`
+pub(crate) fn get_array<'a>(value: &'a Value, path: &str) -> Option<&'a Vec<Value>> {
+    path.split('.')
+        .try_fold(value, |current, key| current.get(key))
+        .and_then(|v| v.as_array())
+}
+
+/// Mutable variant of [`get_array`]
+pub(crate) fn get_array_mut<'a>(value: &'a mut Value, path: &str) -> Option<&'a mut Vec<Value>> {
+    path.split('.')
+        .try_fold(value, |current, key| current.get_mut(key))
+        .and_then(|v| v.as_array_mut())
+}
+
+/// Get the array at a dotted path, creating missing objects and the array itself
+pub(crate) fn ensure_array<'a>(value: &'a mut Value, path: &str) -> Result<&'a mut Vec<Value>> {
+    let mut current = value;
+    for key in path.split('.') {
+        let obj = current
+            .as_object_mut()
+            .ok_or_else(|| anyhow::anyhow!("Target {} is not inside an object", path))?;
+        current = obj.entry(key).or_insert_with(|| serde_json::json!({}));
+    }
+
+    if current.as_object().is_some_and(|obj| obj.is_empty()) {
+        *current = serde_json::json!([]);
+    }
+
+    current
+        .as_array_mut()
+        .ok_or_else(|| anyhow::anyhow!("Target {} is not an array", path))
 }
 
 /// Manages merge operations for Claude Code settings
 pub struct MergeManager {
     settings_dir: PathBuf,
+    array_paths: Vec<String>,
+    strategies: BTreeMap<String, MergeStrategy>,
+    date_style: DateStyle,
+    emoji: bool,
+    retention: HistoryRetention,
+    history_suffix: String,
+}
+
+/// Why the history file at `path` cannot be loaded, if it exists and is corrupted
+fn history_parse_error(path: &Path) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str::<Vec<MergeHistory>>(&content)
+        .err()
+        .map(|e| e.to_string())
 }
 
 impl MergeManager {
     /// Create a new MergeManager
     pub fn new(settings_dir: PathBuf) -> Self {
-        Self { settings_dir }
+        Self {
+            settings_dir,
+            array_paths: DEFAULT_ARRAY_PATHS.iter().map(|s| s.to_string()).collect(),
+            strategies: BTreeMap::new(),
+            date_style: DateStyle::default(),
+            emoji: true,
+            retention: HistoryRetention::default(),
+            history_suffix: String::new(),
+        }
+    }
+
+    /// Keep history in `.<context>-merge-history<suffix>.json`, so levels sharing
+    /// the contexts directory record merges into their live settings separately
+    pub fn with_history_suffix(mut self, suffix: &str) -> Self {
+        self.history_suffix = suffix.to_string();
+        self
+    }
+
+    /// Use a custom list of dotted array paths instead of [`DEFAULT_ARRAY_PATHS`]
+    pub fn with_array_paths(mut self, array_paths: Vec<String>) -> Self {
+        self.array_paths = array_paths;
+        self
+    }
+
+    /// Use per-key conflict strategies for full merges
+    pub fn with_strategies(mut self, strategies: BTreeMap<String, MergeStrategy>) -> Self {
+        self.strategies = strategies;
+        self
+    }
+
+    /// Compact history beyond these limits whenever it is saved
+    pub fn with_retention(mut self, retention: HistoryRetention) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Use plain text instead of emoji icons in history output
+    pub fn with_emoji(mut self, emoji: bool) -> Self {
+        self.emoji = emoji;
+        self
+    }
+
+    /// Set how history timestamps are displayed
+    pub fn with_date_style(mut self, date_style: DateStyle) -> Self {
+        self.date_style = date_style;
+        self
+    }
+
+    /// Override the strategy for a single top-level key
+    pub fn with_strategy(mut self, key: &str, strategy: MergeStrategy) -> Self {
+        self.strategies.insert(key.to_string(), strategy);
+        self
+    }
+
+    pub fn strategy_for(&self, key: &str) -> MergeStrategy {
+        self.strategies.get(key).copied().unwrap_or_default()
     }
 
     /// Get the path to the merge history file for a specific context
     fn get_history_path(&self, context_name: &str) -> PathBuf {
-        self.settings_dir
-            .join(format!(".{}-merge-history.json", context_name))
+        self.settings_dir.join(format!(
+            ".{}-merge-history{}.json",
+            context_name, self.history_suffix
+        ))
+    }
+
+    /// Every history file in the directory, whatever its suffix, with the
+    /// context it belongs to
+    fn history_files(&self) -> Vec<(String, PathBuf)> {
+        let Ok(entries) = fs::read_dir(&self.settings_dir) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .filter_map(|entry| {
+                let file_name = entry.file_name().into_string().ok()?;
+                let (context, rest) = file_name.strip_prefix('.')?.rsplit_once("-merge-history")?;
+                let suffix = rest.strip_suffix(".json")?;
+                (suffix.is_empty() || suffix.starts_with('.'))
+                    .then(|| (context.to_string(), entry.path()))
+            })
+            .collect()
+    }
+
+    /// History files that fail to parse, with the reason
+    pub fn corrupt_history_files(&self) -> CorruptFiles {
+        self.history_files()
+            .into_iter()
+            .filter_map(|(_, path)| {
+                let reason = history_parse_error(&path)?;
+                Some((path, reason))
+            })
+            .collect()
     }
 
     /// Load merge history for a context
@@ -41,20 +396,103 @@ impl MergeManager {
         let content = fs::read_to_string(&history_path)
             .with_context(|| format!("Failed to read merge history from {:?}", history_path))?;
 
-        serde_json::from_str(&content)
-            .with_context(|| format!("Failed to parse merge history from {:?}", history_path))
+        match serde_json::from_str(&content) {
+            Ok(history) => Ok(history),
+            Err(e) => {
+                // Losing history only affects unmerge; don't block the command
+                warn_corrupt(&history_path, &e.to_string());
+                Ok(Vec::new())
+            }
+        }
     }
 
     /// Save merge history for a context
     pub fn save_history(&self, context_name: &str, history: &[MergeHistory]) -> Result<()> {
         let history_path = self.get_history_path(context_name);
+        let history = if self.retention.is_unlimited() {
+            history.to_vec()
+        } else {
+            compact_history(history.to_vec(), &self.retention)
+        };
         let content =
-            serde_json::to_string_pretty(history).context("Failed to serialize merge history")?;
+            serde_json::to_string_pretty(&history).context("Failed to serialize merge history")?;
 
-        fs::write(&history_path, content)
+        // Keep a corrupted history file instead of overwriting it
+        if let Some(reason) = history_parse_error(&history_path) {
+            let _ = quarantine(&history_path, &reason);
+        }
+        write_atomic(&history_path, content.as_bytes())
             .with_context(|| format!("Failed to write merge history to {:?}", history_path))
     }
 
+    /// Carry merge history over to a renamed context and repoint entries merged from it,
+    /// at every level sharing the directory. `prefixes` are the levels whose
+    /// `<level>:<name>` sources name this directory's contexts.
+    ///
+    /// Returns the contexts whose history referenced the old name.
+    pub fn rename_context(
+        &self,
+        old_name: &str,
+        new_name: &str,
+        prefixes: &[&str],
+    ) -> Result<Vec<String>> {
+        let old_prefix = format!(".{old_name}-merge-history");
+        for (context, path) in self.history_files() {
+            if context != old_name {
+                continue;
+            }
+            let file_name = path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .unwrap_or_default();
+            let renamed = file_name.replacen(&old_prefix, &format!(".{new_name}-merge-history"), 1);
+            fs::rename(&path, self.settings_dir.join(renamed))?;
+        }
+
+        let mut renames = vec![(old_name.to_string(), new_name.to_string())];
+        renames.extend(prefixes.iter().map(|prefix| {
+            (
+                format!("{prefix}:{old_name}"),
+                format!("{prefix}:{new_name}"),
+            )
+        }));
+        self.repoint_sources(&renames)
+    }
+
+    /// Rewrite history entries whose source is the first of a pair to the second.
+    ///
+    /// Returns the contexts whose history changed.
+    pub fn repoint_sources(&self, renames: &[(String, String)]) -> Result<Vec<String>> {
+        let mut updated = Vec::new();
+        for (context, path) in self.history_files() {
+            let Ok(content) = fs::read_to_string(&path) else {
+                continue;
+            };
+            // Corrupted history is reported when loaded and quarantined by --doctor --fix
+            let Ok(mut history) = serde_json::from_str::<Vec<MergeHistory>>(&content) else {
+                continue;
+            };
+            let mut changed = false;
+            for entry in history.iter_mut() {
+                if let Some((_, new_source)) = renames.iter().find(|(old, _)| *old == entry.source)
+                {
+                    entry.source = new_source.clone();
+                    changed = true;
+                }
+            }
+            if changed {
+                let content = serde_json::to_string_pretty(&history)
+                    .context("Failed to serialize merge history")?;
+                write_atomic(&path, content.as_bytes())
+                    .with_context(|| format!("Failed to write merge history to {:?}", path))?;
+                if !updated.contains(&context) {
+                    updated.push(context);
+                }
+            }
+        }
+        Ok(updated)
+    }
+
     /// Merge permissions from source into target
     pub fn merge_permissions(
         &self,
@@ -62,87 +500,81 @@ impl MergeManager {
         source: &Value,
         source_name: &str,
     ) -> Result<MergeHistory> {
-        let mut merged_items = Vec::new();
+        // History labels keep the short "allow:..." form for permission lists
+        let merged_items = self.merge_arrays(target, source, |path| {
+            path.strip_prefix("permissions.")
+                .unwrap_or(path)
+                .to_string()
+        })?;
 
-        // Ensure target has permissions object
-        if target.get("permissions").is_none() {
-            target["permissions"] = serde_json::json!({
-                "allow": [],
-                "deny": []
-            });
-        }
-
-        // Merge allow permissions
-        if let Some(source_allow) = source
-            .get("permissions")
-            .and_then(|p| p.get("allow"))
-            .and_then(|a| a.as_array())
-        {
-            let target_allow = target["permissions"]["allow"]
-                .as_array_mut()
-                .ok_or_else(|| anyhow::anyhow!("Target permissions.allow is not an array"))?;
+        // Create history entry
+        let history = MergeHistory {
+            source: source_name.to_string(),
+            timestamp: dates::now().with_timezone(&Utc),
+            merged_items,
+            full_merge: false,
+            protected: false,
+            previous_values: BTreeMap::new(),
+        };
 
-            // Convert to HashSet for deduplication
-            let mut allow_set: HashSet<String> = target_allow
-                .iter()
-                .filter_map(|v| v.as_str().map(String::from))
-                .collect();
+        Ok(history)
+    }
 
-            for item in source_allow {
-                if let Some(s) = item.as_str() {
-                    if allow_set.insert(s.to_string()) {
-                        merged_items.push(format!("allow:{}", s));
-                    }
-                }
-            }
+    /// Merge every configured array path from source into target with deduplication.
+    ///
+    /// Returns one `"<label>:<item>"` entry per added item, where the label is
+    /// derived from the array path by `label`.
+    fn merge_arrays(
+        &self,
+        target: &mut Value,
+        source: &Value,
+        label: impl Fn(&str) -> String,
+    ) -> Result<Vec<String>> {
+        let mut merged_items = Vec::new();
 
-            // Convert back to array
-            *target_allow = allow_set
-                .into_iter()
-                .map(serde_json::Value::String)
-                .collect();
-        }
+        for path in &self.array_paths {
+            let Some(source_items) = get_array(source, path) else {
+                continue;
+            };
 
-        // Merge deny permissions
-        if let Some(source_deny) = source
-            .get("permissions")
-            .and_then(|p| p.get("deny"))
-            .and_then(|a| a.as_array())
-        {
-            let target_deny = target["permissions"]["deny"]
-                .as_array_mut()
-                .ok_or_else(|| anyhow::anyhow!("Target permissions.deny is not an array"))?;
+            let target_items = ensure_array(target, path)?;
 
-            // Convert to HashSet for deduplication
-            let mut deny_set: HashSet<String> = target_deny
-                .iter()
-                .filter_map(|v| v.as_str().map(String::from))
-                .collect();
+            // Deduplicate the target while keeping its order
+            let mut seen: HashSet<String> = HashSet::new();
+            target_items.retain(|v| match v.as_str() {
+                Some(s) => seen.insert(s.to_string()),
+                None => true,
+            });
 
-            for item in source_deny {
+            for item in source_items {
                 if let Some(s) = item.as_str() {
-                    if deny_set.insert(s.to_string()) {
-                        merged_items.push(format!("deny:{}", s));
+                    if seen.insert(s.to_string()) {
+                        target_items.push(Value::String(s.to_string()));
+                        merged_items.push(format!("{}:{}", label(path), s));
                     }
                 }
             }
-
-            // Convert back to array
-            *target_deny = deny_set
-                .into_iter()
-                .map(serde_json::Value::String)
-                .collect();
         }
 
-        // Create history entry
-        let history = MergeHistory {
-            source: source_name.to_string(),
-            timestamp: chrono::Local::now().to_rfc3339(),
-            merged_items,
-            full_merge: false,
-        };
+        Ok(merged_items)
+    }
 
-        Ok(history)
+    /// Remove array items recorded under `"<label>:<item>"` from every configured array path
+    fn unmerge_arrays(
+        &self,
+        target: &mut Value,
+        items_to_remove: &HashSet<String>,
+        label: impl Fn(&str) -> String,
+    ) {
+        for path in &self.array_paths {
+            let label = label(path);
+            if let Some(items) = get_array_mut(target, path) {
+                items.retain(|v| match v.as_str() {
+                    Some(s) => !items_to_remove.contains(&format!("{label}:{s}")),
+                    None => true,
+                });
+            }
+        }
     }
 
     /// Remove permissions that were previously merged from a specific source
@@ -161,35 +593,11 @@ impl MergeManager {
             .flat_map(|h| h.merged_items.iter().cloned())
             .collect();
 
-        // Remove from allow list
-        if let Some(allow) = target
-            .get_mut("permissions")
-            .and_then(|p| p.get_mut("allow"))
-            .and_then(|a| a.as_array_mut())
-        {
-            allow.retain(|v| {
-                if let Some(s) = v.as_str() {
-                    !items_to_remove.contains(&format!("allow:{}", s))
-                } else {
-                    true
-                }
-            });
-        }
-
-        // Remove from deny list
-        if let Some(deny) = target
-            .get_mut("permissions")
-            .and_then(|p| p.get_mut("deny"))
-            .and_then(|a| a.as_array_mut())
-        {
-            deny.retain(|v| {
-                if let Some(s) = v.as_str() {
-                    !items_to_remove.contains(&format!("deny:{}", s))
-                } else {
-                    true
-                }
-            });
-        }
+        self.unmerge_arrays(target, &items_to_remove, |path| {
+            path.strip_prefix("permissions.")
+                .unwrap_or(path)
+                .to_string()
+        });
 
         // Update history to remove entries from this source
         let updated_history: Vec<MergeHistory> = history
@@ -210,90 +618,16 @@ impl MergeManager {
         source_name: &str,
     ) -> Result<MergeHistory> {
         let mut merged_items = Vec::new();
+        let mut previous_values = BTreeMap::new();
 
         // Deep merge all fields from source to target
         if let Some(source_obj) = source.as_object() {
             if let Some(target_obj) = target.as_object_mut() {
                 for (key, value) in source_obj {
                     match key.as_str() {
-                        "permissions" => {
-                            // Handle permissions specially to merge arrays
-                            if !target_obj.contains_key("permissions") {
-                                target_obj.insert(
-                                    "permissions".to_string(),
-                                    serde_json::json!({
-                                        "allow": [],
-                                        "deny": []
-                                    }),
-                                );
-                            }
-
-                            if let Some(source_perms) = value.as_object() {
-                                // Merge allow permissions
-                                if let Some(source_allow) =
-                                    source_perms.get("allow").and_then(|a| a.as_array())
-                                {
-                                    let target_allow = target_obj["permissions"]["allow"]
-                                        .as_array_mut()
-                                        .ok_or_else(|| {
-                                            anyhow::anyhow!(
-                                                "Target permissions.allow is not an array"
-                                            )
-                                        })?;
-
-                                    let mut allow_set: HashSet<String> = target_allow
-                                        .iter()
-                                        .filter_map(|v| v.as_str().map(String::from))
-                                        .collect();
-
-                                    for item in source_allow {
-                                        if let Some(s) = item.as_str() {
-                                            if allow_set.insert(s.to_string()) {
-                                                merged_items
-                                                    .push(format!("permissions.allow:{}", s));
-                                            }
-                                        }
-                                    }
-
-                                    *target_allow = allow_set
-                                        .into_iter()
-                                        .map(serde_json::Value::String)
-                                        .collect();
-                                }
-
-                                // Merge deny permissions
-                                if let Some(source_deny) =
-                                    source_perms.get("deny").and_then(|a| a.as_array())
-                                {
-                                    let target_deny = target_obj["permissions"]["deny"]
-                                        .as_array_mut()
-                                        .ok_or_else(|| {
-                                            anyhow::anyhow!(
-                                                "Target permissions.deny is not an array"
-                                            )
-                                        })?;
-
-                                    let mut deny_set: HashSet<String> = target_deny
-                                        .iter()
-                                        .filter_map(|v| v.as_str().map(String::from))
-                                        .collect();
-
-                                    for item in source_deny {
-                                        if let Some(s) = item.as_str() {
-                                            if deny_set.insert(s.to_string()) {
-                                                merged_items
-                                                    .push(format!("permissions.deny:{}", s));
-                                            }
-                                        }
-                                    }
-
-                                    *target_deny = deny_set
-                                        .into_iter()
-                                        .map(serde_json::Value::String)
-                                        .collect();
-                                }
-                            }
-                        }
+                        // Permission lists are merged below via the array paths
+                        "permissions" => {}
+                        _ if self.array_paths.contains(key) => {}
                         "env" => {
                             // Merge environment variables
                             if let Some(source_env) = value.as_object() {
@@ -301,36 +635,78 @@ impl MergeManager {
                                     target_obj.insert("env".to_string(), serde_json::json!({}));
                                 }
 
+                                let overwrite =
+                                    self.strategy_for("env") == MergeStrategy::Overwrite;
                                 if let Some(target_env) =
                                     target_obj.get_mut("env").and_then(|e| e.as_object_mut())
                                 {
                                     for (env_key, env_value) in source_env {
-                                        if !target_env.contains_key(env_key) {
-                                            target_env.insert(env_key.clone(), env_value.clone());
-                                            merged_items.push(format!("env:{}", env_key));
+                                        match target_env.get(env_key) {
+                                            None => {
+                                                target_env
+                                                    .insert(env_key.clone(), env_value.clone());
+                                                merged_items.push(format!("env:{}", env_key));
+                                            }
+                                            Some(existing)
+                                                if overwrite && existing != env_value =>
+                                            {
+                                                let item = format!("overwrite:env:{}", env_key);
+                                                previous_values
+                                                    .insert(item.clone(), existing.clone());
+                                                target_env
+                                                    .insert(env_key.clone(), env_value.clone());
+                                                merged_items.push(item);
+                                            }
+                                            Some(_) => {}
                                         }
                                     }
                                 }
                             }
                         }
-                        _ => {
-                            // For other fields, overwrite if not present
-                            if !target_obj.contains_key(key) {
+                        _ => match target_obj.get_mut(key) {
+                            // Missing fields are always added
+                            None => {
                                 target_obj.insert(key.clone(), value.clone());
                                 merged_items.push(key.clone());
                             }
-                        }
+                            // Changes to existing fields are recorded with a prefix so
+                            // unmerge never deletes a key the target already had
+                            Some(existing) => match self.strategy_for(key) {
+                                MergeStrategy::KeepTarget => {}
+                                MergeStrategy::Overwrite => {
+                                    if *existing != *value {
+                                        let item = format!("overwrite:{}", key);
+                                        previous_values.insert(item.clone(), existing.clone());
+                                        *existing = value.clone();
+                                        merged_items.push(item);
+                                    }
+                                }
+                                MergeStrategy::Append => {
+                                    let previous = existing.clone();
+                                    if deep_append(existing, value) {
+                                        let item = format!("append:{}", key);
+                                        previous_values.insert(item.clone(), previous);
+                                        merged_items.push(item);
+                                    }
+                                }
+                            },
+                        },
                     }
                 }
             }
         }
 
+        // Full merges record array items under their full path
+        merged_items.extend(self.merge_arrays(target, source, |path| path.to_string())?);
+
         // Create history entry
         let history = MergeHistory {
             source: source_name.to_string(),
-            timestamp: chrono::Local::now().to_rfc3339(),
+            timestamp: dates::now().with_timezone(&Utc),
             merged_items,
             full_merge: true,
+            protected: false,
+            previous_values,
         };
 
         Ok(history)
@@ -346,11 +722,19 @@ impl MergeManager {
         let history = self.load_history(context_name)?;
 
         // Find all full merge entries from this source
-        let full_merge_items: HashSet<String> = history
+        let entries: Vec<&MergeHistory> = history
             .iter()
             .filter(|h| h.source == source_name && h.full_merge)
+            .collect();
+        let full_merge_items: HashSet<String> = entries
+            .iter()
             .flat_map(|h| h.merged_items.iter().cloned())
             .collect();
+        // The oldest entry holds the value from before this source changed it
+        let mut previous_values: BTreeMap<&str, &Value> = BTreeMap::new();
+        for (item, value) in entries.iter().flat_map(|h| &h.previous_values) {
+            previous_values.entry(item).or_insert(value);
+        }
 
         if let Some(target_obj) = target.as_object_mut() {
             // Remove top-level keys that were merged
@@ -358,70 +742,185 @@ impl MergeManager {
 
             // Handle special cases for nested structures
             for item in &full_merge_items {
-                if item.starts_with("env:") {
-                    if let Some(env_key) = item.strip_prefix("env:") {
-                        if let Some(env_obj) =
-                            target_obj.get_mut("env").and_then(|e| e.as_object_mut())
-                        {
-                            env_obj.remove(env_key);
-                        }
+                if let Some(env_key) = item.strip_prefix("env:") {
+                    if let Some(env_obj) = target_obj.get_mut("env").and_then(|e| e.as_object_mut())
+                    {
+                        env_obj.remove(env_key);
                     }
-                } else if item.starts_with("permissions.allow:")
-                    || item.starts_with("permissions.deny:")
-                {
-                    // Handle permissions removal
-                    let (perm_type, perm_value) = if item.starts_with("permissions.allow:") {
-                        ("allow", item.strip_prefix("permissions.allow:").unwrap())
-                    } else {
-                        ("deny", item.strip_prefix("permissions.deny:").unwrap())
-                    };
-
-                    if let Some(perms) = target_obj
-                        .get_mut("permissions")
-                        .and_then(|p| p.get_mut(perm_type))
-                        .and_then(|a| a.as_array_mut())
+                }
+            }
+
+            // Put back the values that were overwritten or appended to
+            for (item, previous) in previous_values {
+                if let Some(env_key) = item.strip_prefix("overwrite:env:") {
+                    if let Some(env_obj) = target_obj.get_mut("env").and_then(|e| e.as_object_mut())
                     {
-                        perms.retain(|v| v.as_str() != Some(perm_value));
+                        env_obj.insert(env_key.to_string(), previous.clone());
                     }
+                } else if let Some(key) = item
+                    .strip_prefix("overwrite:")
+                    .or_else(|| item.strip_prefix("append:"))
+                {
+                    target_obj.insert(key.to_string(), previous.clone());
                 }
             }
         }
 
+        // Handle array items recorded under their full path
+        self.unmerge_arrays(target, &full_merge_items, |path| path.to_string());
+
         // Also handle regular permission unmerge
         self.unmerge_permissions(target, context_name, source_name)?;
 
         Ok(())
     }
 
-    /// Display merge history for a context
-    pub fn display_history(&self, context_name: &str) -> Result<()> {
+    /// Squash a context's whole history into one entry per source; returns the
+    /// entry counts before and after
+    pub fn compact(&self, context_name: &str) -> Result<(usize, usize)> {
         let history = self.load_history(context_name)?;
+        let before = history.len();
+        let compacted = squash(history);
+        let after = compacted.len();
+
+        let history_path = self.get_history_path(context_name);
+        if history_path.exists() {
+            let content = serde_json::to_string_pretty(&compacted)
+                .context("Failed to serialize merge history")?;
+            write_atomic(&history_path, content.as_bytes())
+                .with_context(|| format!("Failed to write merge history to {:?}", history_path))?;
+        }
+        Ok((before, after))
+    }
+
+    /// Display merge history as a reverse-chronological timeline grouped by day,
+    /// optionally only entries at or after `since`.
+    ///
+    /// With `graph`, a bar per source shows how many items it contributed.
+    pub fn display_history(
+        &self,
+        context_name: &str,
+        graph: bool,
+        since: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let mut history = self.load_history(context_name)?;
+        if let Some(since) = since {
+            history.retain(|entry| entry.timestamp >= since);
+        }
+        // Hand-edited or imported history may be out of order
+        history.sort_by_key(|entry| entry.timestamp);
 
         if history.is_empty() {
-            println!("No merge history for context '{}'", context_name);
+            match since {
+                Some(since) => println!(
+                    "No merge history for context '{}' since {}",
+                    context_name,
+                    dates::format_datetime(&since, self.date_style)
+                ),
+                None => println!("No merge history for context '{}'", context_name),
+            }
             return Ok(());
         }
 
-        println!("📋 Merge history for context '{}':", context_name);
-        println!();
+        let icon = |emoji: &'static str, plain: &'static str| {
+            if self.emoji {
+                emoji
+            } else {
+                plain
+            }
+        };
+
+        println!(
+            "{}Merge history for context '{}' ({} entries):",
+            icon("📋 ", ""),
+            context_name.bold(),
+            history.len()
+        );
+
+        if graph {
+            self.display_history_graph(&history);
+            return Ok(());
+        }
 
-        for entry in &history {
-            println!("  📅 {}", entry.timestamp);
-            println!("  📁 Source: {}", entry.source);
+        let mut day = String::new();
+        for entry in history.iter().rev() {
+            let ts = entry.timestamp;
+            let (entry_day, time) = if self.date_style == DateStyle::Utc {
+                (
+                    ts.format("%Y-%m-%d UTC").to_string(),
+                    ts.format("%H:%M").to_string(),
+                )
+            } else {
+                let ts = ts.with_timezone(&chrono::Local);
+                (
+                    ts.format("%Y-%m-%d").to_string(),
+                    ts.format("%H:%M").to_string(),
+                )
+            };
+
+            if entry_day != day {
+                println!();
+                println!("{}", format!("── {entry_day} ──").cyan());
+                day = entry_day;
+            }
+
+            let kind = if entry.full_merge {
+                icon("📦 full ", "full ")
+            } else {
+                icon("🔒 perms", "perms")
+            };
+            let relative = if self.date_style == DateStyle::Relative {
+                format!(
+                    " {}",
+                    dates::format_datetime(&entry.timestamp, self.date_style)
+                )
+                .dimmed()
+                .to_string()
+            } else {
+                String::new()
+            };
             println!(
-                "  📝 Merged {} items{}",
-                entry.merged_items.len(),
-                if entry.full_merge {
-                    " (full merge)"
-                } else {
-                    ""
-                }
+                "  {time}  {kind}  {:<20} +{} items{relative}",
+                entry.source,
+                entry.merged_items.len()
             );
-            println!();
         }
 
         Ok(())
     }
+
+    /// One bar per source, scaled to the largest contributor
+    fn display_history_graph(&self, history: &[MergeHistory]) {
+        const WIDTH: usize = 30;
+
+        let mut totals: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+        for entry in history {
+            let total = totals.entry(entry.source.as_str()).or_default();
+            total.0 += entry.merged_items.len();
+            total.1 += 1;
+        }
+
+        let max = totals
+            .values()
+            .map(|(items, _)| *items)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let name_width = totals
+            .keys()
+            .map(|source| source.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        println!();
+        for (source, (items, merges)) in &totals {
+            let bar = "█".repeat((items * WIDTH).div_ceil(max).max(usize::from(*items > 0)));
+            println!(
+                "  {source:<name_width$}  {} {items} items in {merges} merges",
+                bar.green()
+            );
+        }
+    }
 }
 
 /// Add chrono dependency for timestamps
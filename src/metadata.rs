@@ -0,0 +1,289 @@
+use anyhow::{bail, Context, Result};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::compression::context_name;
+use crate::context::{ContextManager, SettingsLevel};
+use crate::dates;
+use crate::lint::SETTINGS_SCHEMA_VERSION;
+use crate::review::Review;
+
+/// Optional per-context metadata, kept in a hidden file next to the context
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContextMetadata {
+    /// What the context is for; shown as the shell completion annotation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Editor used by `--edit` for this context instead of `$EDITOR` (e.g. `code --wait`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+    /// Extra arguments passed to the editor before the file path
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub editor_args: Vec<String>,
+    /// Labels for narrowing listings with `--tag` (e.g. `client`, `prod`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Person or team responsible for the context
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// Where to ask about changes (email, chat channel, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contact: Option<String>,
+    /// Where the context comes from (repository, URL, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// Oldest cctx release that understands this context (e.g. `0.4.0`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_cctx_version: Option<String>,
+    /// Oldest Claude Code settings schema revision the context relies on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_schema_version: Option<u32>,
+    /// How the context came to exist, recorded when it is captured from live settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// Settings level the context is meant for; switching it at another level warns
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<SettingsLevel>,
+    /// Latest review and approval, from --review-start and --review-approve
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub review: Option<Review>,
+    /// Names of identical contexts `--dedupe` archived in favour of this one;
+    /// they resolve to it at this level only
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+}
+
+impl ContextMetadata {
+    /// Take over what an identical context archived as `name` knew: fields this
+    /// one leaves unset, its tags and aliases, and its name as an alias
+    pub fn absorb(&mut self, name: &str, other: ContextMetadata) {
+        fn fill<T>(field: &mut Option<T>, other: Option<T>) {
+            if field.is_none() {
+                *field = other;
+            }
+        }
+        fill(&mut self.description, other.description);
+        fill(&mut self.editor, other.editor);
+        if self.editor_args.is_empty() {
+            self.editor_args = other.editor_args;
+        }
+        fill(&mut self.owner, other.owner);
+        fill(&mut self.contact, other.contact);
+        fill(&mut self.source, other.source);
+        fill(&mut self.min_cctx_version, other.min_cctx_version);
+        fill(&mut self.min_schema_version, other.min_schema_version);
+        fill(&mut self.provenance, other.provenance);
+        fill(&mut self.level, other.level);
+        fill(&mut self.review, other.review);
+        for tag in other.tags {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+        for alias in std::iter::once(name.to_string()).chain(other.aliases) {
+            if !self.aliases.contains(&alias) {
+                self.aliases.push(alias);
+            }
+        }
+    }
+}
+
+/// Where a context captured from a machine's live settings came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Provenance {
+    /// What was copied, e.g. `live settings (~/.claude/settings.json)`
+    pub created_from: String,
+    /// RFC 3339 time of the capture
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host: Option<String>,
+    pub cctx_version: String,
+}
+
+impl Provenance {
+    /// Provenance for a capture of the settings file at `settings_path`, now
+    pub fn captured(settings_path: &Path) -> Self {
+        Self {
+            created_from: format!("live settings ({})", settings_path.display()),
+            created_at: dates::now().to_rfc3339(),
+            host: hostname(),
+            cctx_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// Machine name from the environment or `/etc/hostname`, if available
+fn hostname() -> Option<String> {
+    std::env::var("HOSTNAME")
+        .or_else(|_| std::env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// Parse `major.minor.patch` (missing parts count as 0, pre-release tags are ignored)
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.trim().trim_start_matches('v');
+    let core = core.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u64>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+    let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+    Some((major, minor, patch))
+}
+
+impl ContextMetadata {
+    /// One-line summary of owner, contact and source, if any are set
+    pub fn ownership(&self) -> Option<String> {
+        let parts: Vec<String> = [
+            ("owner", &self.owner),
+            ("contact", &self.contact),
+            ("source", &self.source),
+        ]
+        .into_iter()
+        .filter_map(|(label, value)| value.as_ref().map(|value| format!("{label}: {value}")))
+        .collect();
+
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join(", "))
+        }
+    }
+
+    /// Fail with an upgrade hint when this build is older than the context requires
+    pub fn check_requirements(&self, name: &str) -> Result<()> {
+        if let Some(required) = &self.min_cctx_version {
+            let running = env!("CARGO_PKG_VERSION");
+            let Some(required_version) = parse_version(required) else {
+                bail!(
+                    "error: context \"{}\" declares an invalid min_cctx_version \"{}\"",
+                    name,
+                    required
+                );
+            };
+            if parse_version(running).is_some_and(|running| running < required_version) {
+                bail!(
+                    "error: context \"{}\" requires cctx {} or newer (this is {}); upgrade with `cargo install cctx`",
+                    name,
+                    required,
+                    running
+                );
+            }
+        }
+
+        if let Some(required) = self.min_schema_version {
+            if required > SETTINGS_SCHEMA_VERSION {
+                bail!(
+                    "error: context \"{}\" uses settings schema {} but this cctx only knows schema {}; upgrade cctx so newer settings keys are not misread",
+                    name,
+                    required,
+                    SETTINGS_SCHEMA_VERSION
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn level_name(level: &SettingsLevel) -> String {
+    format!("{level:?}").to_lowercase()
+}
+
+/// Metadata file for a context (`work.json` -> `.work.meta.json`)
+pub fn metadata_path(context_path: &Path) -> PathBuf {
+    let name = context_name(context_path).unwrap_or_default();
+    context_path.with_file_name(format!(".{name}.meta.json"))
+}
+
+impl ContextManager {
+    /// Write a context's metadata file
+    pub fn save_metadata(&self, name: &str, metadata: &ContextMetadata) -> Result<()> {
+        let path = metadata_path(&self.context_path(name));
+        fs::write(&path, serde_json::to_string_pretty(metadata)?)
+            .with_context(|| format!("Failed to write metadata to {:?}", path))
+    }
+
+    /// Load a context's metadata, or defaults when it has none
+    pub fn load_metadata(&self, name: &str) -> Result<ContextMetadata> {
+        let path = metadata_path(&self.context_path(name));
+        if !path.exists() {
+            return Ok(ContextMetadata::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read metadata from {:?}", path))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse metadata from {:?}", path))
+    }
+
+    /// Refuse contexts that declare a newer cctx or settings schema than this build
+    pub fn check_requirements(&self, name: &str) -> Result<()> {
+        self.load_metadata(name)?.check_requirements(name)
+    }
+
+    /// Warn (or with --strict, refuse) when a context is applied at a level other
+    /// than the one it is meant for, e.g. a local context with secrets at project level
+    pub(crate) fn check_intended_level(&self, name: &str) -> Result<()> {
+        let Some(intended) = self.load_metadata(name)?.level else {
+            return Ok(());
+        };
+        if intended == self.settings_level {
+            return Ok(());
+        }
+
+        let (intended, applied) = (level_name(&intended), level_name(&self.settings_level));
+        if self.strict {
+            bail!(
+                "error: context \"{}\" is meant for the {} level, not {} (--strict)",
+                name,
+                intended,
+                applied
+            );
+        }
+        eprintln!(
+            "{} Context \"{}\" is meant for the {} level but is being applied at the {} level",
+            "⚠️".yellow(),
+            name.yellow().bold(),
+            intended,
+            applied
+        );
+        Ok(())
+    }
+
+    /// Record the level a context is meant for in its metadata
+    pub fn set_intended_level(&self, name: &str, level: SettingsLevel) -> Result<()> {
+        self.ensure_writable("edit metadata")?;
+        let name = &self.resolve_alias(name);
+        if !self.context_path(name).exists() {
+            bail!("error: no context exists with the name \"{}\"", name);
+        }
+
+        let mut metadata = self.load_metadata(name)?;
+        let label = level_name(&level);
+        metadata.level = Some(level);
+        self.save_metadata(name, &metadata)?;
+        println!(
+            "Context \"{}\" is meant for the {} level",
+            name.green().bold(),
+            label
+        );
+        Ok(())
+    }
+
+    /// Ownership summary for listings; unreadable metadata is ignored there
+    pub fn ownership(&self, name: &str) -> Option<String> {
+        self.load_metadata(name).ok()?.ownership()
+    }
+
+    /// One-line description for completion annotations; unreadable metadata is ignored
+    pub fn description(&self, name: &str) -> Option<String> {
+        let description = self.load_metadata(name).ok()?.description?;
+        let line = description.split_whitespace().collect::<Vec<_>>().join(" ");
+        (!line.is_empty()).then_some(line)
+    }
+}
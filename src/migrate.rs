@@ -0,0 +1,211 @@
+use anyhow::{bail, Result};
+use colored::*;
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::compression::read_settings;
+use crate::config::home_dir;
+use crate::context::{ContextManager, SettingsLevel};
+
+/// Directory a previous context switcher kept its contexts in
+const LEGACY_TOOL_DIR: &str = ".cctx";
+
+/// A context found in a layout older setups used
+struct LegacyContext {
+    name: String,
+    path: PathBuf,
+}
+
+/// `settings.<name>.json` or `settings-<name>.json` kept directly under `~/.claude`
+fn claude_dir_context(file_name: &str) -> Option<&str> {
+    let name = file_name
+        .strip_prefix("settings.")
+        .or_else(|| file_name.strip_prefix("settings-"))?
+        .strip_suffix(".json")?;
+    // settings.local.json is Claude Code's own local settings file
+    (!name.is_empty() && name != "local").then_some(name)
+}
+
+fn legacy_contexts(home: &Path) -> Vec<LegacyContext> {
+    let mut found = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(home.join(".claude")) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(name) = path
+                .file_name()
+                .and_then(|s| s.to_str())
+                .and_then(claude_dir_context)
+            else {
+                continue;
+            };
+            if path.is_file() {
+                found.push(LegacyContext {
+                    name: name.to_string(),
+                    path,
+                });
+            }
+        }
+    }
+
+    if let Ok(entries) = fs::read_dir(home.join(LEGACY_TOOL_DIR)) {
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Some(file_name) = path.file_name().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            if file_name.starts_with('.') || !path.is_file() {
+                continue;
+            }
+            if let Some(name) = file_name.strip_suffix(".json") {
+                found.push(LegacyContext {
+                    name: name.to_string(),
+                    path,
+                });
+            }
+        }
+    }
+
+    found.sort_by(|a, b| a.name.cmp(&b.name));
+    found
+}
+
+/// Current context recorded by the previous tool, as a plain `current` file
+fn legacy_current(home: &Path) -> Option<String> {
+    let current = fs::read_to_string(home.join(LEGACY_TOOL_DIR).join("current")).ok()?;
+    let current = current.trim();
+    (!current.is_empty()).then(|| current.to_string())
+}
+
+/// Move a file, copying when source and destination are on different filesystems
+fn move_file(from: &Path, to: &Path) -> Result<()> {
+    if fs::rename(from, to).is_err() {
+        fs::copy(from, to)?;
+        fs::remove_file(from)?;
+    }
+    Ok(())
+}
+
+impl ContextManager {
+    /// Number of contexts waiting in a legacy layout; only the user level has one
+    pub fn legacy_context_count(&self) -> usize {
+        if !matches!(self.settings_level, SettingsLevel::User) {
+            return 0;
+        }
+        home_dir().map_or(0, |home| legacy_contexts(&home).len())
+    }
+
+    /// Move contexts from legacy layouts into the contexts directory, archiving
+    /// the originals under `.archive/legacy` and reconstructing the current context
+    pub fn migrate_legacy(&self) -> Result<()> {
+        self.ensure_writable("migrate contexts")?;
+        if !matches!(self.settings_level, SettingsLevel::User) {
+            bail!("error: legacy layouts only exist at the user level");
+        }
+
+        let home = home_dir()?;
+        let legacy = legacy_contexts(&home);
+        if legacy.is_empty() {
+            println!("No contexts in legacy layouts found");
+            return Ok(());
+        }
+
+        let archive_dir = self.archive_dir().join("legacy");
+        let mut contexts = self.list_contexts()?;
+        let mut migrated = Vec::new();
+
+        for LegacyContext { name, path } in legacy {
+            let content = fs::read_to_string(&path)?;
+            let settings: Value = match serde_json::from_str(&content) {
+                Ok(settings) => settings,
+                Err(e) => {
+                    println!("  {} {}: not valid JSON ({e})", "✗".red(), path.display());
+                    continue;
+                }
+            };
+
+            // Identical contexts were migrated before; different ones keep both copies
+            let existing = contexts.iter().find(|ctx| **ctx == name).map(|ctx| {
+                read_settings(&self.context_path(ctx))
+                    .ok()
+                    .and_then(|c| serde_json::from_str::<Value>(&c).ok())
+            });
+            let target = match existing {
+                Some(Some(existing)) if existing == settings => None,
+                Some(_) => {
+                    let mut candidate = format!("{name}-legacy");
+                    let mut counter = 2;
+                    while contexts.contains(&candidate) {
+                        candidate = format!("{name}-legacy-{counter}");
+                        counter += 1;
+                    }
+                    Some(candidate)
+                }
+                None => Some(name.clone()),
+            };
+
+            match target {
+                Some(target) => {
+                    let target = match self.validate_name(&target) {
+                        Ok(target) => target,
+                        Err(e) => {
+                            println!("  {} {}: {e}", "✗".red(), path.display());
+                            continue;
+                        }
+                    };
+                    fs::write(self.context_path(&target), &content)?;
+                    println!("  {} {} -> {}", "+".green(), path.display(), target);
+                    contexts.push(target.clone());
+                    migrated.push((name, target, settings));
+                }
+                None => println!(
+                    "  {} {} (already migrated)",
+                    "-".dimmed(),
+                    path.display().to_string().dimmed()
+                ),
+            }
+
+            // One subdirectory per source layout, so equal file names cannot collide
+            let layout = path
+                .parent()
+                .and_then(|dir| dir.file_name())
+                .unwrap_or_default();
+            fs::create_dir_all(archive_dir.join(layout))?;
+            move_file(
+                &path,
+                &archive_dir
+                    .join(layout)
+                    .join(path.file_name().unwrap_or_default()),
+            )?;
+        }
+
+        // Reconstruct the current context: the previous tool's record, or the
+        // context the live settings file matches
+        let mut state = self.load_state()?;
+        if state.current.is_none() {
+            let live = read_settings(&self.claude_settings_path)
+                .ok()
+                .and_then(|c| serde_json::from_str::<Value>(&c).ok());
+            let current = legacy_current(&home)
+                .and_then(|current| migrated.iter().find(|(name, _, _)| *name == current))
+                .or_else(|| {
+                    migrated
+                        .iter()
+                        .find(|(_, _, settings)| Some(settings) == live.as_ref())
+                });
+            if let Some((_, target, _)) = current {
+                state.set_current(target.clone());
+                self.save_state(&state)?;
+                println!("Current context set to \"{}\"", target.green().bold());
+            }
+        }
+
+        println!(
+            "Migrated {} contexts; originals archived in {}",
+            migrated.len(),
+            archive_dir.display()
+        );
+        Ok(())
+    }
+}
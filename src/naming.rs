@@ -0,0 +1,84 @@
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::context::{is_valid_context_name, ContextManager};
+
+/// Case normalization applied to new context names
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NameCase {
+    #[default]
+    Preserve,
+    Lower,
+    Upper,
+}
+
+/// Team naming conventions for contexts, configured under `naming` in the config
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NamingRules {
+    /// Regex every new name must match, e.g. `^[a-z]+-(dev|prod)-[a-z]+$`
+    pub pattern: Option<String>,
+    pub max_length: Option<usize>,
+    /// Names that may not be used (compared case-insensitively)
+    #[serde(default)]
+    pub reserved: Vec<String>,
+    #[serde(default)]
+    pub case: NameCase,
+}
+
+impl NamingRules {
+    /// Normalize a name and check it against the rules, returning the final name
+    pub fn apply(&self, name: &str) -> Result<String> {
+        // Surrounding whitespace is almost always a typo and hard to spot in listings
+        let name = name.trim();
+        let name = match self.case {
+            NameCase::Preserve => name.to_string(),
+            NameCase::Lower => name.to_lowercase(),
+            NameCase::Upper => name.to_uppercase(),
+        };
+
+        if !is_valid_context_name(&name) {
+            bail!("error: invalid context name \"{}\"", name);
+        }
+
+        if let Some(max) = self.max_length {
+            if name.chars().count() > max {
+                bail!(
+                    "error: context name \"{}\" is longer than {} characters",
+                    name,
+                    max
+                );
+            }
+        }
+
+        if self
+            .reserved
+            .iter()
+            .any(|reserved| reserved.to_lowercase() == name.to_lowercase())
+        {
+            bail!("error: context name \"{}\" is reserved", name);
+        }
+
+        if let Some(pattern) = &self.pattern {
+            let re = Regex::new(pattern)
+                .with_context(|| format!("error: invalid naming pattern {pattern:?} in config"))?;
+            if !re.is_match(&name) {
+                bail!(
+                    "error: context name \"{}\" does not match the naming pattern {}",
+                    name,
+                    pattern
+                );
+            }
+        }
+
+        Ok(name)
+    }
+}
+
+impl ContextManager {
+    /// Validate a new context name against the configured naming rules
+    pub fn validate_name(&self, name: &str) -> Result<String> {
+        self.config.naming.apply(name)
+    }
+}
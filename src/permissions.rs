@@ -0,0 +1,565 @@
+use anyhow::{bail, Result};
+use colored::*;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::compression::read_settings;
+use crate::context::ContextManager;
+
+/// Tools whose specifiers are gitignore-style path patterns
+const PATH_TOOLS: &[&str] = &[
+    "Read",
+    "Edit",
+    "Write",
+    "MultiEdit",
+    "NotebookEdit",
+    "Glob",
+    "Grep",
+];
+
+/// A permission rule such as `Bash(npm run test:*)` or `Read(./src/**)`
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub tool: String,
+    pub specifier: Option<String>,
+}
+
+impl Rule {
+    pub fn parse(rule: &str) -> Self {
+        let rule = rule.trim();
+        let parts = rule
+            .split_once('(')
+            .and_then(|(tool, rest)| Some((tool, rest.strip_suffix(')')?)));
+        match parts {
+            Some((tool, specifier)) => Self {
+                tool: tool.to_string(),
+                specifier: Some(specifier.to_string()),
+            },
+            _ => Self {
+                tool: rule.to_string(),
+                specifier: None,
+            },
+        }
+    }
+
+    /// Whether the rule applies to every use of its tool
+    fn matches_any_input(&self) -> bool {
+        matches!(self.specifier.as_deref(), None | Some("") | Some("*"))
+    }
+
+    fn matches_tool(&self, tool: &str) -> bool {
+        // `mcp__server` covers every tool the server provides
+        self.tool == tool
+            || (self.tool.starts_with("mcp__")
+                && self.specifier.is_none()
+                && tool.starts_with(&format!("{}__", self.tool)))
+    }
+
+    /// Whether the rule matches a tool invocation under Claude Code's matching rules
+    pub fn matches(&self, tool: &str, input: &str) -> bool {
+        if !self.matches_tool(tool) {
+            return false;
+        }
+        if self.matches_any_input() {
+            return true;
+        }
+
+        let specifier = self.specifier.as_deref().unwrap_or_default();
+        if self.tool == "Bash" {
+            // A chain is only covered when the rule covers every command in it
+            let commands = bash_commands(input);
+            !commands.is_empty()
+                && commands
+                    .iter()
+                    .all(|command| bash_command_matches(specifier, command))
+        } else if PATH_TOOLS.contains(&self.tool.as_str()) {
+            glob_regex(specifier).is_some_and(|re| re.is_match(input))
+        } else if let Some(domain) = specifier.strip_prefix("domain:") {
+            url_host(input) == domain
+        } else {
+            input == specifier
+        }
+    }
+
+    /// Plain-language description of what the rule matches
+    pub fn explain(&self) -> String {
+        if self.tool.starts_with("mcp__") && self.specifier.is_none() {
+            return format!("every tool provided by the MCP server behind {}", self.tool);
+        }
+        if self.matches_any_input() {
+            return format!("every use of the {} tool", self.tool);
+        }
+
+        let specifier = self.specifier.as_deref().unwrap_or_default();
+        if self.tool == "Bash" {
+            let command = match specifier.strip_suffix(":*") {
+                Some(prefix) => format!(
+                    "the Bash command `{prefix}` alone or followed by arguments (not `{prefix}x...`)"
+                ),
+                None => format!("exactly the Bash command `{specifier}` (no other arguments)"),
+            };
+            format!("{command}; commands chained with &&, ||, ;, & or | must each be allowed on their own, and commands with $(...) or backticks are never allowed")
+        } else if PATH_TOOLS.contains(&self.tool.as_str()) {
+            format!(
+                "{} on paths matching `{}` (`*` stays within a directory, `**` crosses directories)",
+                self.tool, specifier
+            )
+        } else if let Some(domain) = specifier.strip_prefix("domain:") {
+            format!("{} requests to the host {domain}", self.tool)
+        } else {
+            format!("{} with the exact input `{specifier}`", self.tool)
+        }
+    }
+}
+
+/// Whether one Bash command matches a specifier: `prefix:*` matches the prefix
+/// alone or followed by whitespace and arguments, anything else must be exact
+fn bash_command_matches(specifier: &str, command: &str) -> bool {
+    match specifier.strip_suffix(":*") {
+        Some(prefix) => command.strip_prefix(prefix).is_some_and(|rest| {
+            rest.is_empty() || prefix.ends_with(' ') || rest.starts_with(char::is_whitespace)
+        }),
+        None => command == specifier,
+    }
+}
+
+/// Split a Bash command line into the commands chained by `&&`, `||`, `;`, `&`,
+/// `|` and newlines. Operators inside quotes do not split, and neither do the
+/// `&` and `|` of redirections such as `2>&1`, `&>file` or `>|file`.
+fn bash_commands(input: &str) -> Vec<&str> {
+    let mut commands = Vec::new();
+    let mut quote = None;
+    let mut escaped = false;
+    let mut start = 0;
+    let mut previous = None;
+    let mut chars = input.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let next = chars.peek().map(|&(_, next)| next);
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quote != Some('\'') => escaped = true,
+            '\'' | '"' if quote == Some(c) => quote = None,
+            '\'' | '"' if quote.is_none() => quote = Some(c),
+            '&' if matches!(previous, Some('>' | '<')) || next == Some('>') => {}
+            '|' if previous == Some('>') => {}
+            ';' | '&' | '|' | '\n' if quote.is_none() => {
+                commands.push(&input[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        previous = Some(c);
+    }
+    commands.push(&input[start..]);
+    commands
+        .into_iter()
+        .map(str::trim)
+        .filter(|command| !command.is_empty())
+        .collect()
+}
+
+/// Whether a Bash command runs a substitution (`$(...)`, backticks or
+/// `<(...)`) whose inner command no rule can see
+fn has_substitution(command: &str) -> bool {
+    let mut quote = None;
+    let mut escaped = false;
+    let mut previous = None;
+    for c in command.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\'' | '"' if quote == Some(c) => quote = None,
+            _ if quote == Some('\'') => {}
+            '\\' => escaped = true,
+            '`' => return true,
+            '(' if matches!(previous, Some('$' | '<' | '>')) => return true,
+            '\'' | '"' if quote.is_none() => quote = Some(c),
+            _ => {}
+        }
+        previous = Some(c);
+    }
+    false
+}
+
+/// Convert a gitignore-style glob to a regex (`**` crosses directories, `*` and `?` don't)
+fn glob_regex(pattern: &str) -> Option<Regex> {
+    let pattern = pattern.strip_prefix("./").unwrap_or(pattern);
+    let mut re = String::from("^(?:\\./)?");
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                // `**/` also matches no directory at all (`src/**/*.rs` takes `src/main.rs`)
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    re.push_str("(?:.*/)?");
+                } else {
+                    re.push_str(".*");
+                }
+            }
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    re.push('$');
+    Regex::new(&re).ok()
+}
+
+/// Host part of a URL, or the input itself when it is a bare domain
+fn url_host(input: &str) -> &str {
+    let rest = input.split_once("://").map_or(input, |(_, rest)| rest);
+    rest.split(['/', ':', '?', '#']).next().unwrap_or(rest)
+}
+
+/// Outcome of checking a tool invocation against a context's permissions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    Allow,
+    Deny,
+    Ask,
+    /// No rule matched; Claude Code falls back to its default mode and asks
+    Unmatched,
+}
+
+impl Decision {
+    /// Exit code reported by `--check` (1 is left for errors)
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Decision::Allow => 0,
+            Decision::Ask => 2,
+            Decision::Deny => 3,
+            Decision::Unmatched => 4,
+        }
+    }
+
+    pub fn label(&self) -> ColoredString {
+        match self {
+            Decision::Allow => "allow".green().bold(),
+            Decision::Deny => "deny".red().bold(),
+            Decision::Ask => "ask".yellow().bold(),
+            Decision::Unmatched => "no matching rule (Claude will ask)".dimmed(),
+        }
+    }
+}
+
+/// Decide a tool invocation: deny rules win over ask, ask over allow.
+///
+/// Chained Bash commands are decided one by one and the most restrictive
+/// decision wins, so the chain is only allowed when every command is.
+/// Returns the decision and the rule (or, for an allowed chain, the rules)
+/// that produced it.
+pub fn decide(settings: &Value, tool: &str, input: &str) -> (Decision, Option<String>) {
+    if tool != "Bash" {
+        return decide_one(settings, tool, input);
+    }
+    let decisions: Vec<(Decision, Option<String>)> = bash_commands(input)
+        .into_iter()
+        .map(|command| decide_command(settings, command))
+        .collect();
+    for restrictive in [Decision::Deny, Decision::Ask, Decision::Unmatched] {
+        if let Some(decided) = decisions.iter().find(|(d, _)| *d == restrictive) {
+            return decided.clone();
+        }
+    }
+    if decisions.is_empty() {
+        return (Decision::Unmatched, None);
+    }
+
+    let mut rules: Vec<String> = Vec::new();
+    for rule in decisions.into_iter().filter_map(|(_, rule)| rule) {
+        if !rules.contains(&rule) {
+            rules.push(rule);
+        }
+    }
+    (Decision::Allow, Some(rules.join(", ")))
+}
+
+/// The Bash commands of a chain that no rule decides
+fn unmatched_commands<'a>(settings: &Value, commands: &[&'a str]) -> Vec<&'a str> {
+    commands
+        .iter()
+        .copied()
+        .filter(|command| decide_command(settings, command).0 == Decision::Unmatched)
+        .collect()
+}
+
+/// Decide one Bash command of a chain. A command with a substitution is
+/// never allowed, because the substituted command is not checked.
+fn decide_command(settings: &Value, command: &str) -> (Decision, Option<String>) {
+    match decide_one(settings, "Bash", command) {
+        (Decision::Allow, _) if has_substitution(command) => (Decision::Unmatched, None),
+        decided => decided,
+    }
+}
+
+fn decide_one(settings: &Value, tool: &str, input: &str) -> (Decision, Option<String>) {
+    for (list, decision) in [
+        ("deny", Decision::Deny),
+        ("ask", Decision::Ask),
+        ("allow", Decision::Allow),
+    ] {
+        let rules = settings
+            .pointer(&format!("/permissions/{list}"))
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|rule| rule.as_str());
+        for rule in rules {
+            if Rule::parse(rule).matches(tool, input) {
+                return (decision, Some(rule.to_string()));
+            }
+        }
+    }
+    (Decision::Unmatched, None)
+}
+
+impl ContextManager {
+    fn load_settings(&self, name: &str) -> Result<Value> {
+        let context_path = self.context_path(name);
+        if !context_path.exists() {
+            bail!("error: no context exists with the name \"{}\"", name);
+        }
+        Ok(serde_json::from_str(&read_settings(&context_path)?)?)
+    }
+
+    /// Explain a permission pattern and where it already appears in a context
+    pub fn explain_rule(&self, pattern: &str, context: Option<&str>) -> Result<()> {
+        let rule = Rule::parse(pattern);
+        println!("{} matches {}", pattern.cyan().bold(), rule.explain());
+
+        if let Some(name) = context {
+            let settings = self.load_settings(name)?;
+            for list in ["allow", "deny", "ask"] {
+                let present = settings
+                    .pointer(&format!("/permissions/{list}"))
+                    .and_then(|v| v.as_array())
+                    .is_some_and(|rules| rules.iter().any(|r| r.as_str() == Some(pattern)));
+                if present {
+                    println!("  listed in permissions.{list} of \"{}\"", name.green());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a tool invocation against a context's permissions and print the decision
+    pub fn check_permission(&self, name: &str, tool: &str, input: &str) -> Result<Decision> {
+        let settings = self.load_settings(name)?;
+        let (decision, rule) = decide(&settings, tool, input);
+        match rule {
+            Some(rule) => println!(
+                "{}: {} `{}` (matched {})",
+                decision.label(),
+                tool,
+                input,
+                rule.cyan()
+            ),
+            None => println!("{}: {} `{}`", decision.label(), tool, input),
+        }
+        // In a chain, say which commands no rule covers
+        if decision == Decision::Unmatched && tool == "Bash" {
+            let commands = bash_commands(input);
+            if commands.len() > 1 {
+                for command in unmatched_commands(&settings, &commands) {
+                    println!("  no rule covers `{command}`");
+                }
+            }
+        }
+        Ok(decision)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn settings() -> Value {
+        json!({
+            "permissions": {
+                "allow": ["Bash(npm:*)", "Bash(git status)", "Read(./src/**)"],
+                "ask": ["Bash(git push:*)"],
+                "deny": ["Bash(rm:*)", "Read(./.env)"]
+            }
+        })
+    }
+
+    #[test]
+    fn prefix_rules_match_on_a_word_boundary() {
+        let settings = settings();
+        assert_eq!(
+            decide(&settings, "Bash", "npm test"),
+            (Decision::Allow, Some("Bash(npm:*)".to_string()))
+        );
+        assert_eq!(decide(&settings, "Bash", "npm").0, Decision::Allow);
+        assert_eq!(
+            decide(&settings, "Bash", "npmx install").0,
+            Decision::Unmatched
+        );
+        assert_eq!(
+            decide(&settings, "Bash", "git status --short").0,
+            Decision::Unmatched
+        );
+    }
+
+    #[test]
+    fn every_chained_command_must_be_allowed() {
+        let settings = settings();
+        for chained in [
+            "npm test && rm -rf /",
+            "npm test || rm -rf /",
+            "npm test; rm -rf /",
+            "npm test | rm -rf /",
+        ] {
+            assert_eq!(
+                decide(&settings, "Bash", chained),
+                (Decision::Deny, Some("Bash(rm:*)".to_string())),
+                "{chained}"
+            );
+        }
+        assert_eq!(
+            decide(&settings, "Bash", "npm test && curl example.com"),
+            (Decision::Unmatched, None)
+        );
+        assert_eq!(
+            decide(&settings, "Bash", "npm ci && git push origin"),
+            (Decision::Ask, Some("Bash(git push:*)".to_string()))
+        );
+        assert_eq!(
+            decide(&settings, "Bash", "npm ci && git status && npm test"),
+            (
+                Decision::Allow,
+                Some("Bash(npm:*), Bash(git status)".to_string())
+            )
+        );
+    }
+
+    #[test]
+    fn quoted_operators_do_not_split() {
+        let settings = settings();
+        assert_eq!(
+            decide(&settings, "Bash", "npm run 'a && b' \"c | d\""),
+            (Decision::Allow, Some("Bash(npm:*)".to_string()))
+        );
+    }
+
+    #[test]
+    fn redirections_do_not_split() {
+        let settings = settings();
+        for redirected in ["npm test 2>&1", "npm test &>out.log", "npm test >|out.log"] {
+            assert_eq!(
+                decide(&settings, "Bash", redirected),
+                (Decision::Allow, Some("Bash(npm:*)".to_string())),
+                "{redirected}"
+            );
+        }
+    }
+
+    #[test]
+    fn substitutions_are_never_allowed() {
+        let settings = settings();
+        for substituted in [
+            "npm test $(rm -rf /)",
+            "npm test `rm -rf /`",
+            "npm test \"$(curl example.com)\"",
+            "npm test <(curl example.com)",
+        ] {
+            assert_eq!(
+                decide(&settings, "Bash", substituted),
+                (Decision::Unmatched, None),
+                "{substituted}"
+            );
+        }
+        assert_eq!(
+            decide(&settings, "Bash", "npm test '$(literal)'").0,
+            Decision::Allow
+        );
+    }
+
+    #[test]
+    fn deny_wins_for_paths() {
+        let settings = settings();
+        assert_eq!(
+            decide(&settings, "Read", "./.env"),
+            (Decision::Deny, Some("Read(./.env)".to_string()))
+        );
+        assert_eq!(
+            decide(&settings, "Read", "src/main.rs"),
+            (Decision::Allow, Some("Read(./src/**)".to_string()))
+        );
+    }
+
+    #[test]
+    fn other_tools_report_the_deciding_rule() {
+        let settings = json!({
+            "permissions": {
+                "allow": ["WebFetch(domain:docs.rs)", "mcp__github"],
+                "deny": ["mcp__github__delete_repo"]
+            }
+        });
+        assert_eq!(
+            decide(&settings, "WebFetch", "https://docs.rs/serde"),
+            (
+                Decision::Allow,
+                Some("WebFetch(domain:docs.rs)".to_string())
+            )
+        );
+        assert_eq!(
+            decide(&settings, "WebFetch", "https://docs.rs.evil.com/"),
+            (Decision::Unmatched, None)
+        );
+        assert_eq!(
+            decide(&settings, "mcp__github__list_issues", ""),
+            (Decision::Allow, Some("mcp__github".to_string()))
+        );
+        assert_eq!(
+            decide(&settings, "mcp__github__delete_repo", ""),
+            (Decision::Deny, Some("mcp__github__delete_repo".to_string()))
+        );
+    }
+
+    #[test]
+    fn unmatched_chains_name_the_uncovered_commands() {
+        let settings = settings();
+        let commands = bash_commands("npm ci && curl example.com | sh");
+        assert_eq!(
+            unmatched_commands(&settings, &commands),
+            ["curl example.com", "sh"]
+        );
+    }
+
+    #[test]
+    fn exit_codes_distinguish_decisions() {
+        let codes: Vec<i32> = [
+            Decision::Allow,
+            Decision::Ask,
+            Decision::Deny,
+            Decision::Unmatched,
+        ]
+        .iter()
+        .map(Decision::exit_code)
+        .collect();
+        assert_eq!(codes, [0, 2, 3, 4]);
+    }
+
+    #[test]
+    fn glob_stars_respect_directories() {
+        let single = glob_regex("./src/*.rs").unwrap();
+        assert!(single.is_match("src/main.rs"));
+        assert!(single.is_match("./src/main.rs"));
+        assert!(!single.is_match("src/bin/main.rs"));
+
+        let double = glob_regex("src/**/*.rs").unwrap();
+        assert!(double.is_match("src/bin/main.rs"));
+        assert!(double.is_match("src/main.rs"));
+        assert!(!double.is_match("srcmain.rs"));
+        assert!(!double.is_match("tests/main.rs"));
+        assert!(glob_regex("**/*.rs").unwrap().is_match("main.rs"));
+
+        let question = glob_regex("a?.txt").unwrap();
+        assert!(question.is_match("ab.txt"));
+        assert!(!question.is_match("a/.txt"));
+        assert!(glob_regex("file.(json)").unwrap().is_match("file.(json)"));
+    }
+}
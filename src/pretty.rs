@@ -0,0 +1,168 @@
+use anyhow::Result;
+use colored::*;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+
+/// Contexts larger than this are re-indented as text instead of being parsed
+/// into a tree and serialized again
+pub const STREAM_THRESHOLD: usize = 256 * 1024;
+
+fn skip_whitespace(bytes: &[u8], mut i: usize) -> usize {
+    while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+        i += 1;
+    }
+    i
+}
+
+fn newline(out: &mut impl Write, depth: usize) -> io::Result<()> {
+    out.write_all(b"\n")?;
+    for _ in 0..depth {
+        out.write_all(b"  ")?;
+    }
+    Ok(())
+}
+
+/// Pretty-print JSON text with the same layout as `serde_json::to_string_pretty`,
+/// without building a `Value`. Keys keep their order in the file, and strings and
+/// numbers are copied as written. `content` must already be valid JSON.
+pub fn write_pretty(content: &str, out: &mut impl Write) -> io::Result<()> {
+    let bytes = content.as_bytes();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                let start = i;
+                i += 1;
+                while i < bytes.len() {
+                    match bytes[i] {
+                        b'\\' => i += 2,
+                        b'"' => {
+                            i += 1;
+                            break;
+                        }
+                        _ => i += 1,
+                    }
+                }
+                out.write_all(&bytes[start..i.min(bytes.len())])?;
+                continue;
+            }
+            open @ (b'{' | b'[') => {
+                let close = if open == b'{' { b'}' } else { b']' };
+                let next = skip_whitespace(bytes, i + 1);
+                if bytes.get(next) == Some(&close) {
+                    out.write_all(&[open, close])?;
+                    i = next;
+                } else {
+                    depth += 1;
+                    out.write_all(&[open])?;
+                    newline(out, depth)?;
+                }
+            }
+            close @ (b'}' | b']') => {
+                depth -= 1;
+                newline(out, depth)?;
+                out.write_all(&[close])?;
+            }
+            b',' => {
+                out.write_all(b",")?;
+                newline(out, depth)?;
+            }
+            b':' => out.write_all(b": ")?,
+            b if b.is_ascii_whitespace() => {}
+            b => out.write_all(&[b])?,
+        }
+        i += 1;
+    }
+    out.write_all(b"\n")
+}
+
+/// Tool a permission rule applies to: the name before `(`, or `mcp__<server>`
+/// so every tool of one MCP server is grouped together
+fn tool_group(rule: &str) -> &str {
+    let tool = rule.split('(').next().unwrap_or(rule).trim();
+    match tool.strip_prefix("mcp__").and_then(|rest| rest.find("__")) {
+        Some(end) => &tool[.."mcp__".len() + end],
+        None => tool,
+    }
+}
+
+/// Print a context for review on a terminal: everything but `permissions` as
+/// JSON, then each permission list grouped by tool with sorted entries
+pub fn print_grouped(settings: &Value) -> Result<()> {
+    let Some(Value::Object(permissions)) = settings.get("permissions") else {
+        println!("{}", serde_json::to_string_pretty(settings)?);
+        return Ok(());
+    };
+
+    let mut rest = settings.clone();
+    if let Some(root) = rest.as_object_mut() {
+        root.remove("permissions");
+        if !root.is_empty() {
+            println!("{}", serde_json::to_string_pretty(&rest)?);
+            println!();
+        }
+    }
+
+    println!("{}", "permissions".bold());
+    for (key, value) in permissions {
+        let Some(rules) = value
+            .as_array()
+            .and_then(|items| items.iter().map(Value::as_str).collect::<Option<Vec<_>>>())
+        else {
+            println!("  {}: {}", key.cyan(), value);
+            continue;
+        };
+
+        println!("  {} ({})", key.cyan(), rules.len());
+        let mut groups: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for rule in rules {
+            groups.entry(tool_group(rule)).or_default().push(rule);
+        }
+        for (tool, mut rules) in groups {
+            rules.sort_unstable();
+            println!("    {} ({})", tool.bold(), rules.len());
+            for rule in rules {
+                println!("      {rule}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn describe(value: &Value) -> String {
+    match value {
+        Value::Object(map) => format!("{} keys", map.len()),
+        Value::Array(items) => format!("{} entries", items.len()),
+        Value::String(_) => "string".to_string(),
+        Value::Number(_) => "number".to_string(),
+        Value::Bool(_) => "boolean".to_string(),
+        Value::Null => "null".to_string(),
+    }
+}
+
+/// Print counts per section: each top-level key, and the lists and maps one level below
+pub fn print_summary(name: &str, size: u64, settings: &Value) {
+    let Some(sections) = settings.as_object() else {
+        println!("{}: {} bytes, {}", name.bold(), size, describe(settings));
+        return;
+    };
+
+    println!(
+        "{}: {} bytes, {} sections",
+        name.bold(),
+        size,
+        sections.len()
+    );
+    for (key, value) in sections {
+        println!("  {} {}", format!("{key:<24}").cyan(), describe(value));
+        if let Value::Object(children) = value {
+            for (child, child_value) in children {
+                if child_value.is_array() || child_value.is_object() {
+                    println!("    {:<22} {}", child, describe(child_value));
+                }
+            }
+        }
+    }
+}
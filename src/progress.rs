@@ -0,0 +1,26 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use std::time::Duration;
+
+/// Spinner for an operation of unknown length; hidden when progress is disabled
+pub fn spinner(enabled: bool, message: String) -> ProgressBar {
+    if !enabled {
+        return ProgressBar::hidden();
+    }
+
+    let spinner = ProgressBar::new_spinner().with_message(message);
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    spinner
+}
+
+/// Progress bar over `len` items; hidden when progress is disabled
+pub fn bar(enabled: bool, len: usize, message: &'static str) -> ProgressBar {
+    if !enabled {
+        return ProgressBar::hidden();
+    }
+
+    let bar = ProgressBar::new(len as u64).with_message(message);
+    if let Ok(style) = ProgressStyle::with_template("{msg} [{bar:30}] {pos}/{len}") {
+        bar.set_style(style.progress_chars("=> "));
+    }
+    bar
+}
@@ -0,0 +1,151 @@
+use anyhow::{bail, Result};
+use colored::*;
+use dialoguer::MultiSelect;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+
+use crate::context::{ContextManager, ManagerOptions, SettingsLevel};
+
+/// Directories never searched for nested projects
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "vendor", "dist", "build"];
+
+/// Directories below `root` (including itself) that have a `.claude` directory,
+/// sorted by path. Hidden directories, symlinks and build output are skipped.
+fn discover_projects(root: &Path) -> Vec<PathBuf> {
+    let mut projects = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        if dir.join(".claude").is_dir() {
+            projects.push(dir.clone());
+        }
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let is_dir = entry.file_type().is_ok_and(|kind| kind.is_dir());
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if is_dir && !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_ref()) {
+                pending.push(entry.path());
+            }
+        }
+    }
+    projects.sort();
+    projects
+}
+
+impl ContextManager {
+    /// Project directory shown relative to where the search started
+    fn project_label(&self, dir: &Path) -> String {
+        match dir.strip_prefix(&self.project_root) {
+            Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+            Ok(relative) => relative.display().to_string(),
+            Err(_) => dir.display().to_string(),
+        }
+    }
+
+    /// List the projects with their own `.claude` directory below the project
+    /// root, or apply `context` to the ones selected (all of them with --yes).
+    /// Project level is used unless --local was given.
+    pub fn projects(&self, options: &ManagerOptions, context: Option<&str>) -> Result<()> {
+        let level = match self.settings_level {
+            SettingsLevel::Local => SettingsLevel::Local,
+            _ => SettingsLevel::Project,
+        };
+        let projects = discover_projects(&self.project_root);
+        if projects.is_empty() {
+            println!(
+                "No .claude directories found under {}",
+                self.project_root.display()
+            );
+            return Ok(());
+        }
+
+        let manager_for = |dir: &Path, read_only: bool| {
+            let options = ManagerOptions {
+                project_dir: Some(dir.to_path_buf()),
+                target: None,
+                read_only: options.read_only || read_only,
+                ..options.clone()
+            };
+            ContextManager::new_with_options(level.clone(), &options)
+        };
+
+        let Some(context) = context else {
+            let width = projects
+                .iter()
+                .map(|dir| self.project_label(dir).len())
+                .max()
+                .unwrap_or(0);
+            for dir in &projects {
+                let label = self.project_label(dir);
+                let status = match manager_for(dir, true).and_then(|manager| {
+                    Ok((
+                        manager.get_current_context()?,
+                        manager.list_contexts()?.len(),
+                    ))
+                }) {
+                    Ok((Some(current), count)) => {
+                        format!("{} ({count} contexts)", current.green().bold())
+                    }
+                    Ok((None, count)) => format!("{} ({count} contexts)", "none".dimmed()),
+                    Err(e) => format!("{}", e.to_string().red()),
+                };
+                println!("  {label:<width$}  {status}");
+            }
+            return Ok(());
+        };
+
+        self.ensure_writable("switch contexts")?;
+        let selected: Vec<&PathBuf> = if self.assume_yes {
+            projects.iter().collect()
+        } else if io::stdin().is_terminal() {
+            let labels: Vec<String> = projects.iter().map(|dir| self.project_label(dir)).collect();
+            let defaults = vec![true; labels.len()];
+            MultiSelect::new()
+                .with_prompt(format!("Apply \"{context}\" to (space to toggle)"))
+                .items(&labels)
+                .defaults(&defaults)
+                .interact()?
+                .into_iter()
+                .map(|i| &projects[i])
+                .collect()
+        } else {
+            bail!(
+                "error: pass --yes to apply \"{}\" to every project without a terminal",
+                context
+            );
+        };
+
+        // Each project is switched on its own, so one failure does not stop the rest
+        let total = selected.len();
+        let mut failed = Vec::new();
+        for dir in selected {
+            let label = self.project_label(dir);
+            println!("{}", label.bold());
+            if let Err(e) =
+                manager_for(dir, false).and_then(|manager| manager.switch_context(context))
+            {
+                println!("  {} {e}", "✗".red());
+                failed.push(label);
+            }
+        }
+        println!();
+        if !failed.is_empty() {
+            bail!(
+                "error: \"{}\" could not be applied to {} of {} projects: {}",
+                context,
+                failed.len(),
+                total,
+                failed.join(", ")
+            );
+        }
+        println!(
+            "✅ Applied \"{}\" to {} projects",
+            context.green().bold(),
+            total
+        );
+        Ok(())
+    }
+}
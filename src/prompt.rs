@@ -0,0 +1,71 @@
+use anyhow::Result;
+use std::fs;
+use std::path::Path;
+
+use crate::context::{ContextManager, ManagerOptions, SettingsLevel};
+use crate::parallel::parallel_map;
+use crate::state::State;
+
+/// Shown for a level without a current context
+const NO_CONTEXT: &str = "--";
+
+/// Current context named by a state file, read without creating, migrating or
+/// quarantining anything; prompts run on every keystroke and must not write
+fn current_in(state_path: &Path) -> Option<String> {
+    let content = fs::read_to_string(state_path).ok()?;
+    serde_json::from_str::<State>(&content).ok()?.current
+}
+
+fn prefix(level: &SettingsLevel) -> &'static str {
+    match level {
+        SettingsLevel::User => "u",
+        SettingsLevel::Project => "p",
+        SettingsLevel::Local => "l",
+    }
+}
+
+/// `u:work`, or `u:--` for a level without a current context
+pub(crate) fn segment(level: &SettingsLevel, current: Option<&str>) -> String {
+    format!("{}:{}", prefix(level), current.unwrap_or(NO_CONTEXT))
+}
+
+/// Current context of every level, user first, read concurrently with
+/// read-only managers
+pub(crate) fn level_currents(options: &ManagerOptions) -> Vec<(SettingsLevel, Option<String>)> {
+    let options = ManagerOptions {
+        read_only: true,
+        target: None,
+        ..options.clone()
+    };
+    let levels = [
+        SettingsLevel::User,
+        SettingsLevel::Project,
+        SettingsLevel::Local,
+    ];
+    parallel_map(&levels, |level| {
+        // Levels outside a project have no state, same as no context
+        let current = ContextManager::new_with_options(level.clone(), &options)
+            .ok()
+            .and_then(|manager| current_in(&manager.state_path));
+        (level.clone(), current)
+    })
+}
+
+impl ContextManager {
+    /// One-line summary for shell prompts: `u:work` for this level, or with
+    /// `all_levels` every level at once (`u:work p:strict l:--`), read concurrently
+    pub fn print_prompt(&self, all_levels: bool, options: &ManagerOptions) -> Result<()> {
+        if !all_levels {
+            let current = current_in(&self.state_path);
+            println!("{}", segment(&self.settings_level, current.as_deref()));
+            return Ok(());
+        }
+
+        let segments: Vec<String> = level_currents(options)
+            .iter()
+            .map(|(level, current)| segment(level, current.as_deref()))
+            .collect();
+        println!("{}", segments.join(" "));
+        Ok(())
+    }
+}
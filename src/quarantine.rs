@@ -0,0 +1,68 @@
+use colored::*;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Extension appended to files that failed to parse
+pub const CORRUPT_EXTENSION: &str = "corrupt";
+
+/// Files that fail to parse, each with the reason
+pub type CorruptFiles = Vec<(PathBuf, String)>;
+
+/// Path a corrupted file is moved to (`work.json` -> `work.json.corrupt`)
+pub fn quarantine_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(CORRUPT_EXTENSION);
+    path.with_file_name(name)
+}
+
+/// Move a corrupted file out of the way, for `cctx --doctor --fix` or before a
+/// write would replace it.
+///
+/// Prints a warning either way; if the rename itself fails the file is left in
+/// place and callers simply skip it.
+pub fn quarantine(path: &Path, reason: &str) -> io::Result<PathBuf> {
+    let target = quarantine_path(path);
+    let result = fs::rename(path, &target);
+
+    match &result {
+        Ok(()) => eprintln!(
+            "{} {} is corrupted ({}); moved to {}",
+            "⚠️".yellow(),
+            path.display(),
+            reason,
+            target.display()
+        ),
+        Err(e) => eprintln!(
+            "{} {} is corrupted ({}) and could not be quarantined: {}",
+            "⚠️".yellow(),
+            path.display(),
+            reason,
+            e
+        ),
+    }
+
+    result.map(|()| target)
+}
+
+/// Warn about a corrupted file that is being skipped. Reading never moves it;
+/// `cctx --doctor --fix` quarantines it.
+pub fn warn_corrupt(path: &Path, reason: &str) {
+    eprintln!(
+        "{} Skipping corrupted file {} ({}); run `cctx --doctor --fix` to quarantine it",
+        "⚠️".yellow(),
+        path.display(),
+        reason
+    );
+}
+
+/// Warn about a file that could not be read and is being skipped
+pub fn warn_unreadable(path: &Path, error: &dyn std::fmt::Display) {
+    eprintln!(
+        "{} Skipping unreadable file {}: {}",
+        "⚠️".yellow(),
+        path.display(),
+        error
+    );
+}
@@ -0,0 +1,381 @@
+use anyhow::{bail, Context, Result};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::compression::{context_name, read_settings, write_settings};
+use crate::context::ContextManager;
+
+/// Permission lists whose rules are recorded one by one
+const RULE_LISTS: &[&str] = &["allow", "deny", "ask"];
+
+/// Context name recorded for changes to the live settings
+const CURRENT: &str = "current";
+
+/// Marker holding the path of the recording script active for a contexts
+/// directory. It lives next to the contexts, so a recording started for a
+/// project (e.g. with `--dir`) follows that project, not the user level.
+fn marker_path(contexts_dir: &Path) -> PathBuf {
+    contexts_dir.join(".cctx-recording")
+}
+
+/// One recorded settings change. Changes carry their values, so replaying
+/// them needs neither the original inputs nor the original contexts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case")]
+pub enum Change {
+    /// Add a rule to `permissions.<list>`
+    Add { list: String, rule: String },
+    /// Remove a rule from `permissions.<list>`
+    Remove { list: String, rule: String },
+    /// Set a variable in the `env` block
+    SetEnv { key: String, value: Value },
+    /// Remove a variable from the `env` block
+    UnsetEnv { key: String },
+    /// Set any other setting, e.g. `["model"]` or `["permissions", "defaultMode"]`
+    Set { path: Vec<String>, value: Value },
+    /// Remove any other setting
+    Unset { path: Vec<String> },
+}
+
+/// A line of a recording: a change and the context it was made to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Recorded {
+    context: String,
+    #[serde(flatten)]
+    change: Change,
+}
+
+impl Change {
+    fn describe(&self) -> String {
+        match self {
+            Change::Add { list, rule } => format!("{list} {rule}"),
+            Change::Remove { list, rule } => format!("remove {list} {rule}"),
+            Change::SetEnv { key, .. } => format!("env {key}"),
+            Change::UnsetEnv { key } => format!("unset env {key}"),
+            Change::Set { path, .. } => format!("set {}", path.join(".")),
+            Change::Unset { path } => format!("unset {}", path.join(".")),
+        }
+    }
+
+    fn apply(&self, settings: &mut Value) {
+        match self {
+            Change::Add { list, rule } => {
+                let rules = entry(settings, &["permissions", list], Value::Array(Vec::new()));
+                if let Some(rules) = rules.as_array_mut() {
+                    if !rules.iter().any(|r| r.as_str() == Some(rule)) {
+                        rules.push(Value::String(rule.clone()));
+                    }
+                }
+            }
+            Change::Remove { list, rule } => {
+                if let Some(rules) = settings
+                    .pointer_mut(&format!("/permissions/{list}"))
+                    .and_then(Value::as_array_mut)
+                {
+                    rules.retain(|r| r.as_str() != Some(rule));
+                }
+            }
+            Change::SetEnv { key, value } => {
+                *entry(settings, &["env", key], Value::Null) = value.clone();
+            }
+            Change::UnsetEnv { key } => remove(settings, &["env", key]),
+            Change::Set { path, value } => {
+                let path: Vec<&str> = path.iter().map(String::as_str).collect();
+                *entry(settings, &path, Value::Null) = value.clone();
+            }
+            Change::Unset { path } => {
+                let path: Vec<&str> = path.iter().map(String::as_str).collect();
+                remove(settings, &path);
+            }
+        }
+    }
+}
+
+/// The value at `path`, creating objects along the way and `default` at the end
+fn entry<'a>(settings: &'a mut Value, path: &[&str], default: Value) -> &'a mut Value {
+    let mut value = settings;
+    for (i, key) in path.iter().enumerate() {
+        if !value.is_object() {
+            *value = Value::Object(Map::new());
+        }
+        let fill = if i + 1 == path.len() {
+            default.clone()
+        } else {
+            Value::Object(Map::new())
+        };
+        value = value
+            .as_object_mut()
+            .expect("just made an object")
+            .entry(key.to_string())
+            .or_insert(fill);
+    }
+    value
+}
+
+fn remove(settings: &mut Value, path: &[&str]) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut value = settings;
+    for key in parents {
+        match value.get_mut(*key) {
+            Some(next) => value = next,
+            None => return,
+        }
+    }
+    if let Some(object) = value.as_object_mut() {
+        object.remove(*last);
+    }
+}
+
+/// The changes that turn `before` into `after`: rules added to and removed
+/// from the permission lists, `env` variables, and any other setting that changed
+pub fn settings_changes(before: &Value, after: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let empty = Map::new();
+    let before_object = before.as_object().unwrap_or(&empty);
+    let after_object = after.as_object().unwrap_or(&empty);
+
+    let before_permissions = before.get("permissions").and_then(Value::as_object);
+    let after_permissions = after.get("permissions").and_then(Value::as_object);
+    for list in RULE_LISTS {
+        let rules = |permissions: Option<&Map<String, Value>>| -> Vec<String> {
+            permissions
+                .and_then(|p| p.get(*list))
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|rule| rule.as_str().map(str::to_string))
+                .collect()
+        };
+        let (old, new) = (rules(before_permissions), rules(after_permissions));
+        for rule in old.iter().filter(|rule| !new.contains(rule)) {
+            changes.push(Change::Remove {
+                list: list.to_string(),
+                rule: rule.clone(),
+            });
+        }
+        for rule in new.iter().filter(|rule| !old.contains(rule)) {
+            changes.push(Change::Add {
+                list: list.to_string(),
+                rule: rule.clone(),
+            });
+        }
+    }
+    diff_objects(
+        &["permissions"],
+        before_permissions.unwrap_or(&empty),
+        after_permissions.unwrap_or(&empty),
+        |key| RULE_LISTS.contains(&key),
+        &mut changes,
+    );
+
+    let before_env = before.get("env").and_then(Value::as_object);
+    let after_env = after.get("env").and_then(Value::as_object);
+    for key in before_env.into_iter().flat_map(|env| env.keys()) {
+        if !after_env.is_some_and(|env| env.contains_key(key)) {
+            changes.push(Change::UnsetEnv { key: key.clone() });
+        }
+    }
+    for (key, value) in after_env.into_iter().flatten() {
+        if before_env.and_then(|env| env.get(key)) != Some(value) {
+            changes.push(Change::SetEnv {
+                key: key.clone(),
+                value: value.clone(),
+            });
+        }
+    }
+
+    diff_objects(
+        &[],
+        before_object,
+        after_object,
+        |key| key == "env" || (key == "permissions" && after_permissions.is_some()),
+        &mut changes,
+    );
+    changes
+}
+
+/// `Set` and `Unset` changes for the keys of an object, except `skip`ped ones
+fn diff_objects(
+    parent: &[&str],
+    before: &Map<String, Value>,
+    after: &Map<String, Value>,
+    skip: impl Fn(&str) -> bool,
+    changes: &mut Vec<Change>,
+) {
+    let path = |key: &str| -> Vec<String> {
+        parent
+            .iter()
+            .map(|p| p.to_string())
+            .chain(std::iter::once(key.to_string()))
+            .collect()
+    };
+    for key in before.keys().filter(|key| !skip(key)) {
+        if !after.contains_key(key) {
+            changes.push(Change::Unset { path: path(key) });
+        }
+    }
+    for (key, value) in after.iter().filter(|(key, _)| !skip(key)) {
+        if before.get(key) != Some(value) {
+            changes.push(Change::Set {
+                path: path(key),
+                value: value.clone(),
+            });
+        }
+    }
+}
+
+/// The contexts directory and context name a settings file belongs to: a
+/// context file in its contexts directory, or the live `settings.json` /
+/// `settings.local.json` (recorded as `current`) next to one
+fn recording_target(path: &Path) -> Option<(PathBuf, String)> {
+    let parent = path.parent()?;
+    let name = context_name(path)?;
+    if marker_path(parent).exists() && !name.starts_with('.') {
+        return Some((parent.to_path_buf(), name.to_string()));
+    }
+    let contexts_dir = parent.join("settings");
+    if matches!(name, "settings" | "settings.local") && marker_path(&contexts_dir).exists() {
+        return Some((contexts_dir, CURRENT.to_string()));
+    }
+    None
+}
+
+/// Append the changes from `before` (`None` for a new file) to `after` to the
+/// recording active for the file's contexts directory, if any
+pub fn record_change(path: &Path, before: Option<&str>, after: &str) -> Result<()> {
+    let Some((contexts_dir, context)) = recording_target(path) else {
+        return Ok(());
+    };
+    let parse = |content: &str| serde_json::from_str::<Value>(content).ok();
+    let before = match before {
+        Some(before) => parse(before),
+        None => Some(Value::Object(Map::new())),
+    };
+    let (Some(before), Some(after)) = (before, parse(after)) else {
+        eprintln!(
+            "warning: {} is not valid JSON, so this change to it was not recorded",
+            path.display()
+        );
+        return Ok(());
+    };
+
+    let script = fs::read_to_string(marker_path(&contexts_dir))?;
+    let script = script.trim();
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(script)
+        .with_context(|| format!("Failed to append to recording {}", script))?;
+    for change in settings_changes(&before, &after) {
+        let recorded = Recorded {
+            context: context.clone(),
+            change,
+        };
+        writeln!(file, "{}", serde_json::to_string(&recorded)?)?;
+    }
+    Ok(())
+}
+
+impl ContextManager {
+    /// Start appending the settings changes made at this level to a script
+    pub fn start_recording(&self, script: &str) -> Result<()> {
+        self.ensure_writable("start recordings")?;
+
+        let script = std::path::absolute(script)?;
+        fs::create_dir_all(&self.contexts_dir)?;
+        fs::write(
+            marker_path(&self.contexts_dir),
+            script.to_string_lossy().as_bytes(),
+        )?;
+
+        println!("⏺️  Recording changes to {}", script.display());
+        println!("💡 Stop with: cctx --record-stop");
+        Ok(())
+    }
+
+    pub fn stop_recording(&self) -> Result<()> {
+        let marker = marker_path(&self.contexts_dir);
+        let Ok(script) = fs::read_to_string(&marker) else {
+            bail!("error: no recording is active");
+        };
+        fs::remove_file(&marker)?;
+
+        println!("⏹️  Recording saved to {}", script.trim());
+        Ok(())
+    }
+
+    /// Apply the changes of a recorded script in order, to `target` when given
+    /// and otherwise to the contexts they were recorded for. Nothing is
+    /// written unless every line of the script can be read.
+    pub fn replay(&self, script: &str, target: Option<&str>) -> Result<()> {
+        self.ensure_writable("replay recordings")?;
+
+        let content = fs::read_to_string(script)
+            .with_context(|| format!("error: failed to read recording {:?}", script))?;
+        let target = target.map(|name| self.resolve_alias(name));
+
+        let mut contexts: BTreeMap<String, (PathBuf, Value)> = BTreeMap::new();
+        for (i, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let recorded: Recorded = serde_json::from_str(line).with_context(|| {
+                format!(
+                    "error: line {} of {} is not a recorded change",
+                    i + 1,
+                    script
+                )
+            })?;
+            let name = target.clone().unwrap_or(recorded.context);
+
+            if !contexts.contains_key(&name) {
+                let (path, settings) = self.replay_target(&name, target.is_none())?;
+                contexts.insert(name.clone(), (path, settings));
+            }
+            let (_, settings) = contexts.get_mut(&name).expect("just inserted");
+            println!("{} {}: {}", "▶".cyan(), name, recorded.change.describe());
+            recorded.change.apply(settings);
+        }
+
+        for (name, (path, settings)) in contexts {
+            if name == CURRENT {
+                self.backup_settings(self.get_current_context()?.as_deref())?;
+            }
+            write_settings(&path, &serde_json::to_string_pretty(&settings)?)?;
+        }
+        Ok(())
+    }
+
+    /// File and settings a replay writes to. Contexts a script was recorded
+    /// for are created when missing; a named target must exist.
+    fn replay_target(&self, name: &str, create: bool) -> Result<(PathBuf, Value)> {
+        if name == CURRENT || !create {
+            let path = self.merge_target_path(name)?;
+            let settings = serde_json::from_str(&read_settings(&path)?)
+                .with_context(|| format!("error: {} is not valid JSON", path.display()))?;
+            return Ok((path, settings));
+        }
+
+        if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+            bail!(
+                "error: recorded context name {:?} is not a valid context name",
+                name
+            );
+        }
+        let path = self.context_path(name);
+        if !path.exists() {
+            fs::create_dir_all(&self.contexts_dir)?;
+            return Ok((path, Value::Object(Map::new())));
+        }
+        let settings = serde_json::from_str(&read_settings(&path)?)
+            .with_context(|| format!("error: context \"{name}\" is not valid JSON"))?;
+        Ok((path, settings))
+    }
+}
@@ -0,0 +1,235 @@
+use anyhow::{bail, Result};
+use colored::*;
+use dialoguer::Select;
+use serde_json::{Map, Value};
+use std::io::{self, IsTerminal};
+use std::path::PathBuf;
+
+use crate::compression::{read_settings, write_settings};
+use crate::context::ContextManager;
+use crate::lint::PERMISSION_LISTS;
+
+/// Turn a malformed permission list into an array of rules, if its intent is clear:
+/// a single rule, an object keyed by index (`{"0": "Read"}`), or an object of
+/// rules mapped to a flag (`{"Read": true}`, where `false` drops the rule)
+fn as_rule_list(value: &Value) -> Option<Vec<Value>> {
+    match value {
+        Value::Null => Some(Vec::new()),
+        Value::String(_) => Some(vec![value.clone()]),
+        Value::Object(map) if map.keys().all(|key| key.parse::<usize>().is_ok()) => {
+            let mut entries: Vec<(usize, &Value)> = map
+                .iter()
+                .filter_map(|(key, value)| Some((key.parse().ok()?, value)))
+                .collect();
+            entries.sort_by_key(|(index, _)| *index);
+            Some(
+                entries
+                    .into_iter()
+                    .map(|(_, value)| value.clone())
+                    .collect(),
+            )
+        }
+        Value::Object(map) => Some(
+            map.iter()
+                .filter(|(_, enabled)| !matches!(enabled, Value::Bool(false) | Value::Null))
+                .map(|(rule, _)| Value::String(rule.clone()))
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+fn shape(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "an object",
+        Value::Array(_) => "a list",
+        Value::String(_) => "a string",
+        Value::Number(_) => "a number",
+        Value::Bool(_) => "a boolean",
+        Value::Null => "null",
+    }
+}
+
+/// Number of rules in a bare list under `permissions`. Whether they were meant to
+/// allow, deny or ask is unclear, so only the user can repair it.
+fn bare_permission_rules(settings: &Value) -> Option<usize> {
+    settings
+        .get("permissions")
+        .and_then(Value::as_array)
+        .map(Vec::len)
+}
+
+/// Move a bare list under `permissions` to `permissions.<list>`
+fn move_bare_permissions(settings: &mut Value, list: &str) -> Option<String> {
+    let root = settings.as_object_mut()?;
+    let Some(Value::Array(rules)) = root.remove("permissions") else {
+        return None;
+    };
+    let mut permissions = Map::new();
+    permissions.insert(list.to_string(), Value::Array(rules));
+    root.insert("permissions".to_string(), Value::Object(permissions));
+    Some(format!(
+        "permissions was a list; moved it to permissions.{list}"
+    ))
+}
+
+/// Convert common malformed shapes into the canonical settings schema, returning
+/// one description per repair. Shapes whose intent is unclear, like a bare
+/// `permissions` list, are left alone.
+pub fn normalize_settings(settings: &mut Value) -> Vec<String> {
+    let mut repairs = Vec::new();
+    let Some(root) = settings.as_object_mut() else {
+        return repairs;
+    };
+
+    if let Some(Value::Object(permissions)) = root.get_mut("permissions") {
+        for list in PERMISSION_LISTS {
+            let Some(value) = permissions.get_mut(*list) else {
+                continue;
+            };
+            if value.is_array() {
+                continue;
+            }
+            if let Some(rules) = as_rule_list(value) {
+                repairs.push(format!(
+                    "permissions.{list} was {}; converted it to a list of {} rules",
+                    shape(value),
+                    rules.len()
+                ));
+                *value = Value::Array(rules);
+            }
+        }
+    }
+
+    // Claude Code reads env values as strings
+    if let Some(Value::Object(env)) = root.get_mut("env") {
+        for (key, value) in env.iter_mut() {
+            if matches!(value, Value::Number(_) | Value::Bool(_)) {
+                repairs.push(format!(
+                    "env.{key} was {}; converted it to a string",
+                    shape(value)
+                ));
+                *value = Value::String(value.to_string());
+            }
+        }
+    }
+
+    repairs
+}
+
+/// Repairs `cctx --doctor --fix` would make, without changing anything
+pub fn shape_problems(settings: &Value) -> Vec<String> {
+    let mut problems = Vec::new();
+    if let Some(count) = bare_permission_rules(settings) {
+        problems.push(format!(
+            "permissions is a list of {count} rules; --doctor --fix asks whether they belong to allow, deny or ask"
+        ));
+    }
+    problems.extend(normalize_settings(&mut settings.clone()));
+    problems
+}
+
+impl ContextManager {
+    /// Refuse to merge settings whose lists are not lists, with a hint instead of
+    /// the opaque error merging would hit halfway through
+    pub(crate) fn ensure_canonical_shape(&self, label: &str, settings: &Value) -> Result<()> {
+        let problems = shape_problems(settings);
+        if problems.is_empty() {
+            return Ok(());
+        }
+        bail!(
+            "error: {} is not in the canonical settings shape:\n  {}\nrun `cctx --doctor --fix` to repair it",
+            label,
+            problems.join("\n  ")
+        );
+    }
+
+    /// Check both sides of a merge before touching either
+    pub(crate) fn ensure_mergeable(
+        &self,
+        target_context: &str,
+        source: &str,
+        target: &Value,
+        incoming: &Value,
+    ) -> Result<()> {
+        self.ensure_canonical_shape(&format!("merge target \"{target_context}\""), target)?;
+        self.ensure_canonical_shape(&format!("merge source \"{source}\""), incoming)
+    }
+
+    /// Ask which list a bare `permissions` list belongs to; `None` to leave it,
+    /// which is the only choice without a terminal
+    fn choose_permission_list(&self, label: &str, count: usize) -> Result<Option<&'static str>> {
+        if !io::stdin().is_terminal() {
+            return Ok(None);
+        }
+        const LISTS: [&str; 3] = ["allow", "deny", "ask"];
+        let choice = Select::new()
+            .with_prompt(format!(
+                "permissions in \"{label}\" is a bare list of {count} rules; move them to"
+            ))
+            .items(&[
+                "permissions.allow",
+                "permissions.deny",
+                "permissions.ask",
+                "Skip",
+            ])
+            .default(3)
+            .interact()?;
+        Ok(LISTS.get(choice).copied())
+    }
+
+    /// Rewrite every context (and this level's live settings file) in the
+    /// canonical shape. Returns the number of files repaired.
+    pub fn repair_shapes(&self) -> Result<usize> {
+        self.ensure_writable("repair contexts")?;
+
+        let mut files: Vec<(String, PathBuf)> = self
+            .list_contexts()?
+            .into_iter()
+            .map(|name| {
+                let path = self.context_path(&name);
+                (name, path)
+            })
+            .collect();
+        if self.claude_settings_path.exists() {
+            files.push((
+                self.claude_settings_path.display().to_string(),
+                self.claude_settings_path.clone(),
+            ));
+        }
+
+        let mut repaired = 0;
+        for (label, path) in files {
+            let Ok(mut settings) = serde_json::from_str::<Value>(&read_settings(&path)?) else {
+                // Invalid JSON is quarantined by --doctor --fix; nothing to normalize here
+                continue;
+            };
+            let mut repairs = Vec::new();
+            if let Some(count) = bare_permission_rules(&settings) {
+                match self.choose_permission_list(&label, count)? {
+                    Some(list) => repairs.extend(move_bare_permissions(&mut settings, list)),
+                    None => println!(
+                        "  {} {}: permissions is a bare list of {} rules; move them to allow, deny or ask by hand, or run `cctx --doctor --fix` in a terminal",
+                        "skipped".yellow(),
+                        label.bold(),
+                        count
+                    ),
+                }
+            }
+            repairs.extend(normalize_settings(&mut settings));
+            if repairs.is_empty() {
+                continue;
+            }
+            if path == self.claude_settings_path {
+                self.backup_settings(self.get_current_context()?.as_deref())?;
+            }
+            write_settings(&path, &serde_json::to_string_pretty(&settings)?)?;
+            repaired += 1;
+            println!("  {} {}", "fixed".green(), label.bold());
+            for repair in repairs {
+                println!("    • {repair}");
+            }
+        }
+        Ok(repaired)
+    }
+}
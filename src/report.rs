@@ -0,0 +1,197 @@
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+
+use crate::compression::read_settings;
+use crate::context::ContextManager;
+use crate::dates;
+use crate::lint::{lint_settings, Severity};
+use crate::metadata::Provenance;
+
+/// Output format of `--report`
+#[derive(Debug, Clone, Copy, Default, clap::ValueEnum)]
+pub enum ReportFormat {
+    #[default]
+    Markdown,
+    Json,
+}
+
+#[derive(Serialize)]
+struct ContextReport {
+    name: String,
+    current: bool,
+    size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    owner: Option<String>,
+    /// Set when the context was captured from live settings rather than authored
+    #[serde(skip_serializing_if = "Option::is_none")]
+    provenance: Option<Provenance>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_used: Option<String>,
+    allow_rules: usize,
+    deny_rules: usize,
+    merges: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_merge: Option<String>,
+    lint_errors: usize,
+    lint_warnings: usize,
+    budget_violations: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Report {
+    generated: String,
+    level: String,
+    contexts_dir: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    current: Option<String>,
+    contexts: Vec<ContextReport>,
+}
+
+fn rule_count(settings: &Value, list: &str) -> usize {
+    settings
+        .pointer(&format!("/permissions/{list}"))
+        .and_then(|rules| rules.as_array())
+        .map_or(0, |rules| rules.len())
+}
+
+impl ContextManager {
+    fn build_report(&self) -> Result<Report> {
+        let state = self.load_state()?;
+        let merge_manager = self.merge_manager();
+
+        let mut contexts = Vec::new();
+        for name in self.list_contexts()? {
+            let path = self.context_path(&name);
+            let settings = read_settings(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<Value>(&content).ok());
+            let (lint_errors, lint_warnings) = match &settings {
+                Some(settings) => {
+                    let issues = lint_settings(settings);
+                    let errors = issues
+                        .iter()
+                        .filter(|issue| issue.severity == Severity::Error)
+                        .count();
+                    (errors, issues.len() - errors)
+                }
+                None => (1, 0),
+            };
+            let history = merge_manager.load_history(&name)?;
+
+            let metadata = self.load_metadata(&name).unwrap_or_default();
+            contexts.push(ContextReport {
+                current: state.current.as_deref() == Some(name.as_str()),
+                size: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+                owner: metadata.owner,
+                provenance: metadata.provenance,
+                last_used: state.last_used.get(&name).cloned(),
+                allow_rules: settings.as_ref().map_or(0, |s| rule_count(s, "allow")),
+                deny_rules: settings.as_ref().map_or(0, |s| rule_count(s, "deny")),
+                merges: history.len(),
+                last_merge: history
+                    .iter()
+                    .map(|entry| entry.timestamp)
+                    .max()
+                    .map(|ts| dates::to_utc_rfc3339(&ts)),
+                lint_errors,
+                lint_warnings,
+                budget_violations: settings
+                    .as_ref()
+                    .map(|s| self.config.budget.violations(s))
+                    .unwrap_or_default(),
+                name,
+            });
+        }
+
+        Ok(Report {
+            generated: dates::now().to_rfc3339(),
+            level: format!("{:?}", self.settings_level).to_lowercase(),
+            contexts_dir: self.contexts_dir.display().to_string(),
+            current: state.current,
+            contexts,
+        })
+    }
+
+    /// Print a local-only report of contexts, usage, merges and compliance for
+    /// access reviews. Nothing is sent anywhere.
+    pub fn print_report(&self, format: ReportFormat) -> Result<()> {
+        let report = self.build_report()?;
+        match format {
+            ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+            ReportFormat::Markdown => print_markdown(&report),
+        }
+        Ok(())
+    }
+}
+
+fn print_markdown(report: &Report) {
+    let never = "never".to_string();
+    let compliant = report
+        .contexts
+        .iter()
+        .filter(|ctx| ctx.lint_errors == 0 && ctx.budget_violations.is_empty())
+        .count();
+
+    println!("# cctx report");
+    println!();
+    println!("- Generated: {}", report.generated);
+    println!("- Level: {} (`{}`)", report.level, report.contexts_dir);
+    println!(
+        "- Current context: {}",
+        report.current.as_deref().unwrap_or("none")
+    );
+    println!(
+        "- Contexts: {} ({} without lint errors or budget violations)",
+        report.contexts.len(),
+        compliant
+    );
+    println!();
+    println!("## Contexts");
+    println!();
+    println!("| Context | Owner | Origin | Size | Last used | Allow | Deny | Merges | Last merge | Lint | Budget |");
+    println!("|---|---|---|---:|---|---:|---:|---:|---|---|---|");
+    for ctx in &report.contexts {
+        let name = if ctx.current {
+            format!("**{}** (current)", ctx.name)
+        } else {
+            ctx.name.clone()
+        };
+        let lint = match (ctx.lint_errors, ctx.lint_warnings) {
+            (0, 0) => "ok".to_string(),
+            (errors, warnings) => format!("{errors} errors, {warnings} warnings"),
+        };
+        let budget = if ctx.budget_violations.is_empty() {
+            "ok".to_string()
+        } else {
+            ctx.budget_violations.join("; ")
+        };
+        let origin = match &ctx.provenance {
+            Some(provenance) => format!(
+                "captured {}{}",
+                provenance.created_at,
+                provenance
+                    .host
+                    .as_ref()
+                    .map(|host| format!(" on {host}"))
+                    .unwrap_or_default()
+            ),
+            None => "-".to_string(),
+        };
+        println!(
+            "| {} | {} | {} | {} | {} | {} | {} | {} | {} | {} | {} |",
+            name.replace('|', "\\|"),
+            ctx.owner.as_deref().unwrap_or("-"),
+            origin.replace('|', "\\|"),
+            ctx.size,
+            ctx.last_used.as_ref().unwrap_or(&never),
+            ctx.allow_rules,
+            ctx.deny_rules,
+            ctx.merges,
+            ctx.last_merge.as_deref().unwrap_or("-"),
+            lint,
+            budget.replace('|', "\\|")
+        );
+    }
+}
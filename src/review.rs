@@ -0,0 +1,182 @@
+use anyhow::{bail, Result};
+use chrono::DateTime;
+use colored::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::process::Command;
+
+use crate::compression::read_settings;
+use crate::context::ContextManager;
+use crate::dates;
+
+/// Label of the snapshot taken when a review starts
+const SNAPSHOT_FORMAT: &str = "review-%Y%m%d-%H%M%S";
+
+/// Change control for shared contexts, kept in the context's metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Review {
+    /// Snapshot holding the settings under review
+    pub snapshot: String,
+    pub requested_by: String,
+    /// RFC 3339 time the review was started
+    pub requested_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approved_by: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approved_at: Option<String>,
+    /// SHA-256 digest of the approved settings; switching warns when it no longer matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approved_hash: Option<String>,
+}
+
+/// Who is acting: `--reviewer`, else git's `user.name`, else the login name
+fn identity(reviewer: Option<&str>) -> String {
+    if let Some(reviewer) = reviewer {
+        return reviewer.to_string();
+    }
+    Command::new("git")
+        .args(["config", "user.name"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| std::env::var("USER").ok())
+        .or_else(|| std::env::var("USERNAME").ok())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// SHA-256 of parsed settings, so formatting and compression do not count as changes
+fn settings_hash(content: &str) -> Result<String> {
+    let settings: Value = serde_json::from_str(content)?;
+    let canonical = serde_json::to_string(&settings)?;
+    Ok(format!("{:x}", Sha256::digest(canonical.as_bytes())))
+}
+
+impl ContextManager {
+    fn context_hash(&self, name: &str) -> Result<String> {
+        settings_hash(&read_settings(&self.context_path(name))?)
+    }
+
+    /// Snapshot a context and record who asked for it to be reviewed; an earlier
+    /// approval is replaced
+    pub fn review_start(&self, name: &str, reviewer: Option<&str>) -> Result<()> {
+        self.ensure_writable("start reviews")?;
+        let name = &self.resolve_alias(name);
+        if !self.context_path(name).exists() {
+            bail!("error: no context exists with the name \"{}\"", name);
+        }
+
+        let now = dates::now();
+        let snapshot = now.format(SNAPSHOT_FORMAT).to_string();
+        self.snapshot_context(name, Some(&snapshot))?;
+
+        let requested_by = identity(reviewer);
+        let mut metadata = self.load_metadata(name)?;
+        metadata.review = Some(Review {
+            snapshot,
+            requested_by: requested_by.clone(),
+            requested_at: now.to_rfc3339(),
+            approved_by: None,
+            approved_at: None,
+            approved_hash: None,
+        });
+        self.save_metadata(name, &metadata)?;
+
+        println!(
+            "Review of context \"{}\" started by {}; approve it with {}",
+            name.green().bold(),
+            requested_by,
+            format!("cctx --review-approve {name}").cyan()
+        );
+        Ok(())
+    }
+
+    /// Mark a context approved at its current content. Settings that changed
+    /// since the review started are only approved with --force.
+    pub fn review_approve(&self, name: &str, reviewer: Option<&str>) -> Result<()> {
+        self.ensure_writable("approve reviews")?;
+        let name = &self.resolve_alias(name);
+        if !self.context_path(name).exists() {
+            bail!("error: no context exists with the name \"{}\"", name);
+        }
+
+        if self.load_metadata(name)?.review.is_none() {
+            self.review_start(name, reviewer)?;
+        }
+        let mut metadata = self.load_metadata(name)?;
+        let Some(review) = metadata.review.as_mut() else {
+            bail!("error: context \"{}\" has no review to approve", name);
+        };
+
+        let hash = self.context_hash(name)?;
+        let snapshot = self.snapshot_path(name, &review.snapshot);
+        let reviewed_hash = read_settings(&snapshot)
+            .ok()
+            .and_then(|content| settings_hash(&content).ok());
+        if reviewed_hash.as_ref() != Some(&hash) && !self.force {
+            bail!(
+                "error: context \"{}\" changed since its review started (snapshot {}); start a new review or pass --force",
+                name,
+                review.snapshot
+            );
+        }
+
+        let approved_by = identity(reviewer);
+        review.approved_by = Some(approved_by.clone());
+        review.approved_at = Some(dates::now().to_rfc3339());
+        review.approved_hash = Some(hash);
+        self.save_metadata(name, &metadata)?;
+
+        println!(
+            "✅ Context \"{}\" approved by {}",
+            name.green().bold(),
+            approved_by
+        );
+        Ok(())
+    }
+
+    /// Warn (or with --strict, refuse) when an approved context no longer has
+    /// the content that was approved
+    pub(crate) fn check_review(&self, name: &str) -> Result<()> {
+        let Some(review) = self.load_metadata(name)?.review else {
+            return Ok(());
+        };
+        let Some(approved_hash) = &review.approved_hash else {
+            return Ok(());
+        };
+        if self
+            .context_hash(name)
+            .is_ok_and(|hash| hash == *approved_hash)
+        {
+            return Ok(());
+        }
+
+        let approved_by = review.approved_by.as_deref().unwrap_or("unknown");
+        let approved_at = review
+            .approved_at
+            .as_deref()
+            .and_then(|at| DateTime::parse_from_rfc3339(at).ok())
+            .map(|at| dates::format_datetime(&at, self.date_style))
+            .unwrap_or_else(|| "an unknown time".to_string());
+        if self.strict {
+            bail!(
+                "error: context \"{}\" changed since {} approved it (--strict); run `cctx --review-start {}`",
+                name,
+                approved_by,
+                name
+            );
+        }
+        eprintln!(
+            "{} Context \"{}\" changed since {} approved it ({}); run `cctx --review-start {}` to have it reviewed again",
+            "⚠️".yellow(),
+            name.yellow().bold(),
+            approved_by,
+            approved_at,
+            name
+        );
+        Ok(())
+    }
+}
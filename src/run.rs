@@ -0,0 +1,187 @@
+use anyhow::{bail, Result};
+use colored::*;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::atomic::write_atomic;
+use crate::context::{ContextManager, PreparedContext};
+use crate::status::Drift;
+
+/// Convert a settings.json `env` value to the string placed in the environment
+pub(crate) fn env_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Puts the settings file back the way it was before `--run`, however the run ends
+struct RestoreSettings<'a> {
+    path: &'a Path,
+    /// The original content, or `None` when there was no settings file;
+    /// taken once restored
+    original: Option<Option<Vec<u8>>>,
+}
+
+impl RestoreSettings<'_> {
+    fn put_back(&mut self) -> Result<()> {
+        match self.original.take() {
+            Some(Some(bytes)) => write_atomic(self.path, &bytes),
+            Some(None) => Ok(fs::remove_file(self.path)?),
+            None => Ok(()),
+        }
+    }
+
+    fn restore(mut self) -> Result<()> {
+        self.put_back()
+    }
+}
+
+impl Drop for RestoreSettings<'_> {
+    fn drop(&mut self) {
+        if let Err(e) = self.put_back() {
+            eprintln!("warning: could not restore {}: {e}", self.path.display());
+        }
+    }
+}
+
+/// Ctrl-C reaches the whole foreground process group. While the child runs,
+/// cctx ignores it so only the child stops and the settings are still restored.
+struct IgnoreInterrupts {
+    #[cfg(unix)]
+    previous: libc::sighandler_t,
+}
+
+impl IgnoreInterrupts {
+    fn new() -> Self {
+        Self {
+            // SAFETY: installing SIG_IGN runs no code in the signal context
+            #[cfg(unix)]
+            previous: unsafe { libc::signal(libc::SIGINT, libc::SIG_IGN) },
+        }
+    }
+}
+
+impl Drop for IgnoreInterrupts {
+    fn drop(&mut self) {
+        // SAFETY: puts back the handler that was installed before
+        #[cfg(unix)]
+        unsafe {
+            libc::signal(libc::SIGINT, self.previous);
+        }
+    }
+}
+
+impl ContextManager {
+    /// Run a command with a context applied, restoring the previous settings afterwards.
+    ///
+    /// The context's `env` block is also exported to the child process unless
+    /// `export_env` is false, so tools that read the environment see it too.
+    /// Returns the command's exit code.
+    pub fn run_with_context(
+        &self,
+        name: &str,
+        command: &[String],
+        export_env: bool,
+    ) -> Result<i32> {
+        self.ensure_writable("run commands under a context")?;
+
+        let Some((program, args)) = command.split_first() else {
+            bail!("error: no command given; usage: cctx --run <context> -- <command> [args...]");
+        };
+
+        let PreparedContext {
+            name,
+            content,
+            settings: json,
+            ..
+        } = self.prepare_context(name)?;
+
+        // Remember what to restore once the command exits
+        let original = if self.claude_settings_path.exists() {
+            Some(fs::read(&self.claude_settings_path)?)
+        } else {
+            None
+        };
+
+        if let Some(parent) = self.claude_settings_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomic(&self.claude_settings_path, content.as_bytes())?;
+        let restore = RestoreSettings {
+            path: &self.claude_settings_path,
+            original: Some(original),
+        };
+
+        let mut cmd = Command::new(program);
+        cmd.args(args);
+        cmd.env("CCTX_CONTEXT", &name);
+        if export_env {
+            if let Some(env) = json.get("env").and_then(|e| e.as_object()) {
+                for (key, value) in env {
+                    cmd.env(key, env_value(value));
+                }
+            }
+        }
+
+        eprintln!("Running with context \"{}\"", name.green().bold());
+        let status = cmd.spawn().and_then(|mut child| {
+            let _interrupts = IgnoreInterrupts::new();
+            child.wait()
+        });
+
+        restore.restore()?;
+        let status = status?;
+        Ok(status.code().unwrap_or(1))
+    }
+
+    /// Run a command only while `name` is the applied context and the live
+    /// settings file still matches it.
+    ///
+    /// Unlike `run_with_context` nothing is restored afterwards: with `switch`
+    /// the context is applied first and stays applied. Returns the command's
+    /// exit code.
+    pub fn exec_requiring(&self, name: &str, command: &[String], switch: bool) -> Result<i32> {
+        let Some((program, args)) = command.split_first() else {
+            bail!(
+                "error: no command given; usage: cctx --require <context> -- <command> [args...]"
+            );
+        };
+
+        let name = &self.resolve_alias(name);
+        if !self.context_path(name).exists() {
+            bail!("error: no context exists with the name \"{}\"", name);
+        }
+
+        let current = self.get_current_context()?;
+        if current.as_deref() != Some(name.as_str()) {
+            if !switch {
+                bail!(
+                    "error: refusing to run {}: context \"{}\" is not applied (current: {}); switch to it or pass --switch",
+                    program,
+                    name,
+                    current.as_deref().unwrap_or("none")
+                );
+            }
+            self.switch_context(name)?;
+        } else if !matches!(self.drift(name), Drift::InSync) {
+            // The state names the context, but the live file may have been edited since
+            if !switch {
+                bail!(
+                    "error: refusing to run {}: the live settings no longer match context \"{}\"; switch to it again or pass --switch",
+                    program,
+                    name
+                );
+            }
+            self.switch_context(name)?;
+        }
+
+        let status = Command::new(program)
+            .args(args)
+            .env("CCTX_CONTEXT", name)
+            .status()?;
+        Ok(status.code().unwrap_or(1))
+    }
+}
@@ -0,0 +1,127 @@
+/// Common settings.json sections offered when editing an empty context:
+/// (key, description, example value)
+const SECTIONS: &[(&str, &str, &str)] = &[
+    (
+        "permissions",
+        "Tool permission rules, e.g. \"Bash(npm run test:*)\" or \"Read(./.env)\"",
+        r#"{
+  "allow": [],
+  "deny": [],
+  "ask": [],
+  "additionalDirectories": [],
+  "defaultMode": "default"
+}"#,
+    ),
+    (
+        "env",
+        "Environment variables set for every session",
+        r#"{
+  "KEY": "value"
+}"#,
+    ),
+    (
+        "hooks",
+        "Commands run around tool use, e.g. formatting after edits",
+        r#"{
+  "PostToolUse": [
+    {
+      "matcher": "Edit|Write",
+      "hooks": [{ "type": "command", "command": "cargo fmt" }]
+    }
+  ]
+}"#,
+    ),
+    (
+        "model",
+        "Model used by default in this context",
+        r#""sonnet""#,
+    ),
+];
+
+/// Whether a context holds no settings yet (empty file or `{}`)
+pub fn is_blank(content: &str) -> bool {
+    let content = content.trim();
+    content.is_empty()
+        || serde_json::from_str::<serde_json::Value>(content)
+            .is_ok_and(|json| json.as_object().is_some_and(|obj| obj.is_empty()))
+}
+
+/// Commented skeleton of the common sections; saving it unchanged yields `{}`
+pub fn scaffold(name: &str) -> String {
+    let mut out = String::from("{\n");
+    out.push_str(&format!(
+        "  // Settings for context \"{name}\". Uncomment the sections you need;\n"
+    ));
+    out.push_str("  // comments and trailing commas are removed when you save.\n");
+
+    for (key, description, example) in SECTIONS {
+        out.push_str("  //\n");
+        out.push_str(&format!("  // {description}\n"));
+        let value = format!("\"{key}\": {example},");
+        for line in value.lines() {
+            out.push_str(&format!("  // {line}\n"));
+        }
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+/// Remove `//` and `/* */` comments and trailing commas so the result parses as JSON
+pub fn strip_jsonc(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut chars = input.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => {
+                    if let Some(escaped) = chars.next() {
+                        out.push(escaped);
+                    }
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = '\0';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            '}' | ']' => {
+                // Drop a trailing comma before the closing bracket
+                let trimmed = out.trim_end().len();
+                if out[..trimmed].ends_with(',') {
+                    out.truncate(trimmed - 1);
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
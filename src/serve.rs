@@ -0,0 +1,552 @@
+use anyhow::{bail, Context, Result};
+use colored::*;
+use serde_json::{json, Value};
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::compression::read_settings;
+use crate::context::{ContextManager, UnknownContext};
+use crate::diff::unified_diff;
+use crate::ignore::IGNORE_FILE;
+use crate::similarity::ConfirmationRequired;
+
+/// Port `--serve` listens on unless `--port` or `CCTX_PORT` is given
+pub const DEFAULT_PORT: u16 = 7878;
+
+/// Port `--serve` listens on without `--port`: `CCTX_PORT`, or the default
+pub fn daemon_port() -> u16 {
+    std::env::var("CCTX_PORT")
+        .ok()
+        .and_then(|port| port.parse().ok())
+        .unwrap_or(DEFAULT_PORT)
+}
+
+/// Hidden file in the contexts directory holding the session token that
+/// every request must send as `X-Cctx-Token`
+pub const TOKEN_FILE: &str = ".cctx-serve-token";
+
+/// Hidden socket in the contexts directory where the daemon hands out its index
+pub const SOCKET_FILE: &str = ".cctx-serve.sock";
+
+/// How long clients wait on the daemon before scanning the directory themselves
+const CLIENT_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Largest request body accepted, far more than any switch request needs
+const MAX_BODY: usize = 64 * 1024;
+
+/// Longest request or header line accepted
+const MAX_LINE: usize = 8 * 1024;
+
+/// Most header lines read from one request
+const MAX_HEADERS: usize = 100;
+
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Context names and the current context, kept in memory by `--serve` and
+/// rescanned only when the contexts directory or state file changes
+#[derive(Default)]
+struct Index {
+    stamp: Option<[Option<SystemTime>; 3]>,
+    contexts: Vec<String>,
+    descriptions: BTreeMap<String, String>,
+    current: Option<String>,
+    previous: Option<String>,
+}
+
+/// What a client learned from the daemon's index
+pub struct DaemonIndex {
+    pub contexts: Vec<String>,
+    pub aliases: Vec<String>,
+    pub descriptions: BTreeMap<String, String>,
+    pub current: Option<String>,
+    pub previous: Option<String>,
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+struct Request {
+    method: String,
+    path: String,
+    host: Option<String>,
+    content_type: Option<String>,
+    token: Option<String>,
+}
+
+/// A fresh token for each `--serve` session, from the OS random source
+fn session_token() -> String {
+    let mut bytes = [0u8; 16];
+    let from_os = fs::File::open("/dev/urandom").and_then(|mut f| f.read_exact(&mut bytes));
+    if from_os.is_err() {
+        // RandomState is seeded from the OS on every platform
+        for chunk in bytes.chunks_mut(8) {
+            let random = RandomState::new().build_hasher().finish();
+            chunk.copy_from_slice(&random.to_le_bytes());
+        }
+    }
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Decode `%XX` escapes in a request path
+fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(byte) = u8::from_str_radix(hex, 16) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Read one line of at most [`MAX_LINE`] bytes, so a client cannot make the
+/// daemon buffer without limit
+fn read_line(reader: &mut impl BufRead) -> Result<String> {
+    let mut line = String::new();
+    reader.take(MAX_LINE as u64).read_line(&mut line)?;
+    if line.len() == MAX_LINE && !line.ends_with('\n') {
+        bail!("request line too long");
+    }
+    Ok(line)
+}
+
+fn read_request(stream: &TcpStream) -> Result<Request> {
+    let mut reader = BufReader::new(stream);
+
+    let request_line = read_line(&mut reader)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        bail!("malformed request line");
+    };
+
+    let mut host = None;
+    let mut content_type = None;
+    let mut token = None;
+    let mut content_length = 0;
+    for _ in 0..=MAX_HEADERS {
+        let line = read_line(&mut reader)?;
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.trim();
+            match name.trim().to_ascii_lowercase().as_str() {
+                "host" => host = Some(value.to_string()),
+                "content-type" => content_type = Some(value.to_string()),
+                "x-cctx-token" => token = Some(value.to_string()),
+                "content-length" => content_length = value.parse().unwrap_or(0),
+                _ => {}
+            }
+        }
+    }
+
+    // Requests carry everything in the path; drain any body so the reply is read
+    if content_length > MAX_BODY {
+        bail!("request body too large");
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    Ok(Request {
+        method: method.to_string(),
+        path: path.split('?').next().unwrap_or(path).to_string(),
+        host,
+        content_type,
+        token,
+    })
+}
+
+fn respond(mut stream: &TcpStream, status: u16, body: &Value) -> Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        415 => "Unsupported Media Type",
+        _ => "Internal Server Error",
+    };
+    let body = serde_json::to_string_pretty(body)?;
+    write!(
+        stream,
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    )?;
+    stream.flush()?;
+    Ok(())
+}
+
+impl ContextManager {
+    /// Serve a small JSON API on localhost for status bars, launchers and GUIs.
+    ///
+    /// `GET /contexts`, `GET /current`, `GET /index`, `GET /diff/<name>` and
+    /// `POST /switch/<name>`.
+    /// Only loopback connections are possible, and requests naming any other
+    /// `Host` are refused so DNS rebinding cannot reach the API from a web page.
+    /// Every request must send the session token in `X-Cctx-Token`, since
+    /// contexts and diffs can hold secrets; the token is written to
+    /// [`TOKEN_FILE`], readable only by the user. POSTs must also be sent as
+    /// `application/json`. A switch that would need a confirmation fails with
+    /// 409 instead of prompting on the daemon's terminal.
+    pub fn serve(&self, port: u16) -> Result<()> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .with_context(|| format!("error: could not listen on 127.0.0.1:{port}"))?;
+        // With port 0 the OS picks one, and clients must name that one in Host
+        let port = listener.local_addr()?.port();
+
+        let token = session_token();
+        let token_path = self.contexts_dir.join(TOKEN_FILE);
+        fs::create_dir_all(&self.contexts_dir)?;
+        let _ = fs::remove_file(&token_path);
+        let mut token_file = fs::OpenOptions::new();
+        token_file.write(true).create_new(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            token_file.mode(0o600);
+        }
+        token_file
+            .open(&token_path)
+            .and_then(|mut file| file.write_all(token.as_bytes()))
+            .with_context(|| format!("error: could not write {:?}", token_path))?;
+
+        eprintln!(
+            "Serving the cctx API on {} (Ctrl-C to stop); session token in {}",
+            format!("http://127.0.0.1:{port}").cyan(),
+            token_path.display()
+        );
+        let allowed_hosts = [format!("127.0.0.1:{port}"), format!("localhost:{port}")];
+
+        let index = Mutex::new(Index::default());
+        thread::scope(|scope| {
+            // Shell completion and --current ask here, never over TCP
+            #[cfg(unix)]
+            match self.bind_index_socket() {
+                Ok(socket) => {
+                    let index = &index;
+                    scope.spawn(move || self.serve_index_socket(socket, index));
+                }
+                Err(e) => eprintln!("warning: {e:#}; completion will read the files"),
+            }
+
+            for stream in listener.incoming() {
+                let stream = match stream {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        eprintln!("warning: connection failed: {e}");
+                        continue;
+                    }
+                };
+
+                // A stalled client must not block everyone else for long
+                stream.set_read_timeout(Some(READ_TIMEOUT))?;
+                let (status, body) = match read_request(&stream) {
+                    Ok(request)
+                        if !request
+                            .host
+                            .as_ref()
+                            .is_some_and(|host| allowed_hosts.contains(host)) =>
+                    {
+                        (403, json!({ "error": "unexpected Host header" }))
+                    }
+                    Ok(request) => {
+                        let mut index = index.lock().unwrap_or_else(PoisonError::into_inner);
+                        self.handle_request(&request, &token, &mut index)
+                    }
+                    Err(e) => (400, json!({ "error": e.to_string() })),
+                };
+                if let Err(e) = respond(&stream, status, &body) {
+                    eprintln!("warning: could not send response: {e}");
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn handle_request(&self, request: &Request, token: &str, index: &mut Index) -> (u16, Value) {
+        // Decoded first, so an escaped `/` cannot smuggle extra segments into a name
+        let path = percent_decode(&request.path);
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+        if request.token.as_deref() != Some(token) {
+            return (
+                403,
+                json!({ "error": "requests must send the session token in X-Cctx-Token" }),
+            );
+        }
+
+        let result = match (request.method.as_str(), segments.as_slice()) {
+            ("GET", ["contexts"]) => self.api_contexts(index),
+            ("GET", ["current"]) => self
+                .refresh_index(index)
+                .map(|_| json!({ "current": index.current })),
+            ("GET", ["index"]) => self.api_index(index),
+            ("GET", ["diff", name]) => self.api_diff(name),
+            ("POST", ["switch", name]) => {
+                let is_json = request
+                    .content_type
+                    .as_deref()
+                    .is_some_and(|t| t.starts_with("application/json"));
+                if !is_json {
+                    return (
+                        415,
+                        json!({ "error": "POST requests must use Content-Type: application/json" }),
+                    );
+                }
+                // Coarse mtimes can hide a switch within the same tick
+                index.stamp = None;
+                self.switch_context(name)
+                    .and_then(|_| self.get_current_context())
+                    .map(|c| json!({ "current": c }))
+            }
+            (_, ["contexts"] | ["current"] | ["index"] | ["diff", _] | ["switch", _]) => {
+                return (405, json!({ "error": "method not allowed" }));
+            }
+            _ => return (404, json!({ "error": "not found" })),
+        };
+
+        match result {
+            Ok(body) => (200, body),
+            Err(e) => {
+                let status = if e.is::<UnknownContext>() {
+                    404
+                } else if e.is::<ConfirmationRequired>() {
+                    409
+                } else {
+                    500
+                };
+                (status, json!({ "error": e.to_string() }))
+            }
+        }
+    }
+
+    /// Rescan only when a directory entry, the state file or `.cctxignore`
+    /// changed since the last request
+    fn refresh_index(&self, index: &mut Index) -> Result<()> {
+        let stamp = [
+            modified(&self.contexts_dir),
+            modified(&self.state_path),
+            modified(&self.contexts_dir.join(IGNORE_FILE)),
+        ];
+        if index.stamp != Some(stamp) {
+            index.contexts = self.list_contexts()?;
+            index.descriptions = self.descriptions(&index.contexts);
+            let state = self.load_state()?;
+            index.current = state.current;
+            index.previous = state.previous;
+            index.stamp = Some(stamp);
+        }
+        Ok(())
+    }
+
+    fn api_contexts(&self, index: &mut Index) -> Result<Value> {
+        self.refresh_index(index)?;
+        let contexts: Vec<Value> = index
+            .contexts
+            .iter()
+            .map(|name| {
+                let is_current = Some(name) == index.current.as_ref();
+                json!({ "name": name, "current": is_current })
+            })
+            .collect();
+        Ok(json!({ "contexts": contexts, "current": index.current }))
+    }
+
+    /// Everything shell completion and `--current` need in one response. The
+    /// state path lets clients check the daemon serves their settings level.
+    fn api_index(&self, index: &mut Index) -> Result<Value> {
+        self.refresh_index(index)?;
+        let aliases: Vec<String> = self.aliases().into_keys().collect();
+        Ok(json!({
+            "state_path": self.state_path,
+            "contexts": index.contexts,
+            "aliases": aliases,
+            "descriptions": index.descriptions,
+            "current": index.current,
+            "previous": index.previous,
+        }))
+    }
+
+    /// Ask a running `--serve` daemon for its index instead of scanning the
+    /// contexts directory. `None` when no daemon listens on the socket in this
+    /// level's contexts directory, or when it serves a different settings level.
+    pub fn query_daemon(&self) -> Option<DaemonIndex> {
+        let index = self.read_daemon_index()?;
+        if index.get("state_path")?.as_str()? != self.state_path.to_str()? {
+            return None;
+        }
+
+        let names = |key: &str| -> Option<Vec<String>> {
+            index
+                .get(key)?
+                .as_array()?
+                .iter()
+                .map(|name| name.as_str().map(str::to_string))
+                .collect()
+        };
+        Some(DaemonIndex {
+            contexts: names("contexts")?,
+            aliases: names("aliases")?,
+            descriptions: index
+                .get("descriptions")
+                .and_then(|d| serde_json::from_value(d.clone()).ok())
+                .unwrap_or_default(),
+            current: index
+                .get("current")
+                .and_then(|c| c.as_str())
+                .map(str::to_string),
+            previous: index
+                .get("previous")
+                .and_then(|p| p.as_str())
+                .map(str::to_string),
+        })
+    }
+
+    /// The index the daemon writes to each connection on its socket. The socket
+    /// lives in the user's own contexts directory, so no other user can stand in
+    /// for the daemon, and a stale socket fails at once instead of timing out.
+    #[cfg(unix)]
+    fn read_daemon_index(&self) -> Option<Value> {
+        let mut stream = UnixStream::connect(self.contexts_dir.join(SOCKET_FILE)).ok()?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+        let mut body = String::new();
+        stream.read_to_string(&mut body).ok()?;
+        serde_json::from_str(&body).ok()
+    }
+
+    /// Without unix sockets there is no daemon clients can trust, so they read
+    /// the files themselves
+    #[cfg(not(unix))]
+    fn read_daemon_index(&self) -> Option<Value> {
+        None
+    }
+
+    /// Answer every connection on [`SOCKET_FILE`] with the current index
+    #[cfg(unix)]
+    fn serve_index_socket(&self, listener: UnixListener, index: &Mutex<Index>) {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else {
+                continue;
+            };
+            let body = {
+                let mut index = index.lock().unwrap_or_else(PoisonError::into_inner);
+                self.api_index(&mut index)
+            };
+            let sent = body
+                .and_then(|body| Ok(serde_json::to_string(&body)?))
+                .and_then(|body| Ok(stream.write_all(body.as_bytes())?));
+            if let Err(e) = sent {
+                eprintln!("warning: could not answer on the index socket: {e}");
+            }
+        }
+    }
+
+    /// Listen on [`SOCKET_FILE`], replacing one left behind by an earlier daemon.
+    /// Only the user can connect.
+    #[cfg(unix)]
+    fn bind_index_socket(&self) -> Result<UnixListener> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let socket_path = self.contexts_dir.join(SOCKET_FILE);
+        let _ = fs::remove_file(&socket_path);
+        let listener = UnixListener::bind(&socket_path)
+            .with_context(|| format!("error: could not listen on {:?}", socket_path))?;
+        fs::set_permissions(&socket_path, fs::Permissions::from_mode(0o600))?;
+        Ok(listener)
+    }
+
+    fn descriptions(&self, contexts: &[String]) -> BTreeMap<String, String> {
+        contexts
+            .iter()
+            .filter_map(|name| Some((name.clone(), self.description(name)?)))
+            .collect()
+    }
+
+    /// Context names and aliases for shell completion, one per line, followed
+    /// by a tab and a description when there is one. `-` comes first when
+    /// there is a previous context to switch back to. `ui.completion_filter`
+    /// narrows the candidates when configured.
+    pub fn print_completion_names(&self) -> Result<()> {
+        let (contexts, aliases, descriptions, previous) = match self.query_daemon() {
+            Some(index) => (
+                index.contexts,
+                index.aliases,
+                index.descriptions,
+                index.previous,
+            ),
+            None => {
+                let contexts = self.list_contexts()?;
+                let descriptions = self.descriptions(&contexts);
+                let aliases = self.aliases().into_keys().collect();
+                (contexts, aliases, descriptions, self.load_state()?.previous)
+            }
+        };
+        // A previous context that was deleted or archived cannot be switched back to
+        if let Some(previous) = previous.filter(|previous| contexts.contains(previous)) {
+            println!("-\tprevious context ({previous})");
+        }
+        let filter = &self.config.ui.completion_filter;
+        let contexts = self.filter_contexts(contexts, filter);
+        for name in &contexts {
+            match descriptions.get(name) {
+                Some(description) => println!("{name}\t{description}"),
+                None => println!("{name}"),
+            }
+        }
+        // Aliases follow the contexts they point to
+        let targets = self.aliases();
+        let aliases = aliases.iter().filter(|alias| {
+            filter.is_empty()
+                || targets
+                    .get(*alias)
+                    .is_some_and(|target| contexts.contains(target))
+        });
+        for alias in aliases {
+            match targets.get(alias) {
+                Some(target) => println!("{alias}\talias for {target}"),
+                None => println!("{alias}"),
+            }
+        }
+        Ok(())
+    }
+
+    /// What switching to `name` would change in the active settings file
+    fn api_diff(&self, name: &str) -> Result<Value> {
+        let name = self.resolve_alias(name);
+        let context_path = self.context_path(&name);
+        if !self.is_listed_context(&name) {
+            return Err(UnknownContext(name).into());
+        }
+
+        let active = if self.claude_settings_path.exists() {
+            read_settings(&self.claude_settings_path)?
+        } else {
+            String::new()
+        };
+        let target = read_settings(&context_path)?;
+        Ok(json!({
+            "context": name,
+            "diff": unified_diff(&active, &target, "settings.json", &name),
+        }))
+    }
+}
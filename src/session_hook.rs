@@ -0,0 +1,76 @@
+use anyhow::{Context, Result};
+use colored::*;
+use serde_json::json;
+use std::fs::OpenOptions;
+use std::io::Write;
+
+use crate::context::{ContextManager, ManagerOptions};
+use crate::integration::shell_quote;
+use crate::prompt::{level_currents, segment};
+use crate::statusline::statusline_command;
+
+/// File Claude Code passes to `SessionStart` hooks; `export` lines written to it
+/// are applied to every command the session runs
+const CLAUDE_ENV_FILE: &str = "CLAUDE_ENV_FILE";
+
+impl ContextManager {
+    /// Print the settings snippet that makes Claude Code sessions aware of the
+    /// active contexts: a `SessionStart` hook running `--session-start` and a
+    /// status line running `--prompt --all-levels`
+    pub fn print_hook_snippet(&self) -> Result<()> {
+        let exe = std::env::current_exe().context("error: could not locate the cctx binary")?;
+        let cctx = shell_quote(&exe.to_string_lossy());
+
+        let snippet = json!({
+            "hooks": {
+                "SessionStart": [{
+                    "hooks": [{
+                        "type": "command",
+                        "command": format!("{cctx} --session-start"),
+                    }]
+                }]
+            },
+            "statusLine": {
+                "type": "command",
+                "command": statusline_command()?,
+            }
+        });
+        println!("{}", serde_json::to_string_pretty(&snippet)?);
+        eprintln!(
+            "💡 Add this to a settings file, or save it and merge it with {}",
+            "cctx --merge-from <file> --merge-full".cyan()
+        );
+        Ok(())
+    }
+
+    /// Run as a `SessionStart` hook: export the active contexts into the session
+    /// as `CCTX_CONTEXT` (the most specific level's) and `CCTX_CONTEXTS` (every
+    /// level), and print them so the transcript records what the session ran under
+    pub fn session_start(&self, options: &ManagerOptions) -> Result<()> {
+        let currents = level_currents(options);
+        let summary = currents
+            .iter()
+            .map(|(level, current)| segment(level, current.as_deref()))
+            .collect::<Vec<_>>()
+            .join(" ");
+        // Local overrides project overrides user, so the last level set wins
+        let effective = currents
+            .iter()
+            .rev()
+            .find_map(|(_, current)| current.clone())
+            .unwrap_or_default();
+
+        if let Some(env_file) = std::env::var_os(CLAUDE_ENV_FILE) {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&env_file)
+                .with_context(|| format!("error: could not open {:?}", env_file))?;
+            writeln!(file, "export CCTX_CONTEXT={}", shell_quote(&effective))?;
+            writeln!(file, "export CCTX_CONTEXTS={}", shell_quote(&summary))?;
+        }
+
+        println!("Claude settings contexts (cctx) for this session: {summary}");
+        Ok(())
+    }
+}
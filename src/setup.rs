@@ -0,0 +1,94 @@
+use anyhow::Result;
+use colored::*;
+use dialoguer::{Confirm, Input, Select};
+use std::io::IsTerminal;
+
+use crate::config::{home_dir, Config, Picker};
+use crate::context::{ContextManager, SettingsLevel};
+
+impl ContextManager {
+    /// True when no config exists yet and a user is at the terminal to answer prompts
+    pub fn is_first_run(&self) -> bool {
+        let Ok(home_dir) = home_dir() else {
+            return false;
+        };
+        !self.read_only
+            && !Config::default_path(&home_dir).exists()
+            && std::io::stdin().is_terminal()
+            && std::io::stderr().is_terminal()
+    }
+
+    /// Short interactive setup that writes the config file
+    pub fn first_run_setup(&self) -> Result<()> {
+        self.ensure_writable("run setup")?;
+
+        let home_dir = home_dir()?;
+        let config_path = Config::default_path(&home_dir);
+        let mut config = self.config.clone();
+
+        println!("👋 Welcome to cctx! Let's set a few preferences (Esc to keep defaults).");
+        println!();
+
+        let levels = ["user (~/.claude/settings.json)", "project", "local"];
+        let level = Select::new()
+            .with_prompt("Default settings level")
+            .items(&levels)
+            .default(0)
+            .interact_opt()?;
+        config.ui.default_level = match level {
+            Some(1) => Some(SettingsLevel::Project),
+            Some(2) => Some(SettingsLevel::Local),
+            _ => None,
+        };
+
+        config.ui.color = Confirm::new()
+            .with_prompt("Use colors?")
+            .default(true)
+            .interact_opt()?
+            .unwrap_or(true);
+        config.ui.emoji = Confirm::new()
+            .with_prompt("Use emoji?")
+            .default(true)
+            .interact_opt()?
+            .unwrap_or(true);
+
+        let pickers = ["auto (fzf if installed)", "fzf", "built-in"];
+        config.ui.picker = match Select::new()
+            .with_prompt("Interactive picker")
+            .items(&pickers)
+            .default(0)
+            .interact_opt()?
+        {
+            Some(1) => Picker::Fzf,
+            Some(2) => Picker::Builtin,
+            _ => Picker::Auto,
+        };
+
+        config.save(&config_path)?;
+        println!("✅ Saved preferences to {}", config_path.display());
+
+        // Offer to keep the settings the user already has
+        if self.claude_settings_path.exists() && self.list_contexts()?.is_empty() {
+            let adopt = Confirm::new()
+                .with_prompt("Save your current settings as a context?")
+                .default(true)
+                .interact_opt()?
+                .unwrap_or(false);
+            if adopt {
+                let name: String = Input::new()
+                    .with_prompt("Context name")
+                    .default("personal".to_string())
+                    .interact_text()?;
+                self.create_context(&name)?;
+            }
+        }
+
+        println!();
+        println!(
+            "Run {} to list contexts or {} to rerun this setup",
+            "cctx".green(),
+            "cctx --setup".green()
+        );
+        Ok(())
+    }
+}
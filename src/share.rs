@@ -0,0 +1,192 @@
+use anyhow::{bail, Context, Result};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use which::which;
+
+use crate::compression::read_settings;
+use crate::context::ContextManager;
+
+/// Placeholder written in place of redacted values
+const REDACTED: &str = "<redacted>";
+
+/// Key fragments whose string values are treated as secrets
+const SECRET_KEY_PARTS: &[&str] = &[
+    "token",
+    "secret",
+    "password",
+    "passwd",
+    "apikey",
+    "api_key",
+    "auth",
+    "credential",
+];
+
+/// Where `--share` uploads contexts, configured under `share` in the config
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ShareConfig {
+    /// Paste service endpoint that accepts a raw POST body and responds with
+    /// the paste URL; GitHub gists are used when unset
+    pub paste_url: Option<String>,
+}
+
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    SECRET_KEY_PARTS.iter().any(|part| key.contains(part))
+}
+
+fn redact_secrets(value: &mut Value, path: &str, redacted: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                let child_path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                if child.as_str().is_some_and(|s| s != REDACTED) && is_secret_key(key) {
+                    *child = Value::String(REDACTED.to_string());
+                    redacted.push(child_path);
+                } else {
+                    redact_secrets(child, &child_path, redacted);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                redact_secrets(item, path, redacted);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Replace every `env` value and secret-looking strings, returning the redacted paths
+pub fn redact(settings: &mut Value) -> Vec<String> {
+    let mut redacted = Vec::new();
+
+    if let Some(env) = settings.get_mut("env").and_then(|env| env.as_object_mut()) {
+        for (key, value) in env.iter_mut() {
+            *value = Value::String(REDACTED.to_string());
+            redacted.push(format!("env.{key}"));
+        }
+    }
+
+    redact_secrets(settings, "", &mut redacted);
+    redacted
+}
+
+/// GitHub token from `GITHUB_TOKEN`, falling back to the gh CLI's keyring entry
+fn github_token() -> Result<String> {
+    if let Ok(token) = std::env::var("GITHUB_TOKEN") {
+        if !token.is_empty() {
+            return Ok(token);
+        }
+    }
+
+    if which("gh").is_ok() {
+        let output = Command::new("gh").args(["auth", "token"]).output()?;
+        let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if output.status.success() && !token.is_empty() {
+            return Ok(token);
+        }
+    }
+
+    bail!("error: no GitHub token found; set GITHUB_TOKEN or log in with `gh auth login`")
+}
+
+/// POST a body with curl, passing extra headers through a curl config on stdin
+/// so tokens never appear in the process list. Returns the response body.
+fn post(url: &str, body: &str, headers: &[String]) -> Result<String> {
+    if which("curl").is_err() {
+        bail!("error: curl is required to share contexts but was not found in PATH");
+    }
+
+    let config: String = headers
+        .iter()
+        .map(|header| format!("header = \"{}\"\n", header.replace('"', "\\\"")))
+        .chain(std::iter::once(format!(
+            "data-binary = \"{}\"\n",
+            body.replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace('\n', "\\n")
+        )))
+        .collect();
+
+    let mut child = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--request", "POST"])
+        .args(["--config", "-"])
+        .arg(url)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(config.as_bytes())?;
+    }
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        bail!(
+            "error: upload to {} failed: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+impl ContextManager {
+    /// Upload a redacted copy of a context and print its URL
+    pub fn share_context(&self, name: &str) -> Result<()> {
+        let name = &self.resolve_alias(name);
+        let context_path = self.context_path(name);
+        if !context_path.exists() {
+            bail!("error: no context exists with the name \"{}\"", name);
+        }
+
+        let mut settings: Value = serde_json::from_str(&read_settings(&context_path)?)?;
+        let redacted = redact(&mut settings);
+        let content = serde_json::to_string_pretty(&settings)?;
+
+        let url = match &self.config.share.paste_url {
+            Some(paste_url) => post(
+                paste_url,
+                &content,
+                &["Content-Type: application/json".to_string()],
+            )?,
+            None => {
+                let mut files = serde_json::Map::new();
+                files.insert(format!("{name}.json"), json!({ "content": content }));
+                let gist = json!({
+                    "description": format!("cctx context \"{name}\""),
+                    "public": false,
+                    "files": files,
+                });
+                let response = post(
+                    "https://api.github.com/gists",
+                    &gist.to_string(),
+                    &[
+                        "Accept: application/vnd.github+json".to_string(),
+                        format!("Authorization: Bearer {}", github_token()?),
+                    ],
+                )?;
+                let response: Value = serde_json::from_str(&response)
+                    .context("error: unexpected response from the GitHub gist API")?;
+                response["html_url"]
+                    .as_str()
+                    .context("error: the GitHub gist API did not return a URL")?
+                    .to_string()
+            }
+        };
+
+        if !redacted.is_empty() {
+            eprintln!("🔒 Redacted: {}", redacted.join(", "));
+        }
+        eprintln!("🔗 Context \"{}\" shared", name.green().bold());
+        println!("{url}");
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use anyhow::{bail, Context, Result};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use which::which;
+
+use crate::compression::read_settings;
+use crate::config::{expand_tilde, home_dir};
+use crate::context::ContextManager;
+
+/// Namespace passed to `ssh-keygen -Y` so cctx signatures can't be reused elsewhere
+const SIGNATURE_NAMESPACE: &str = "cctx";
+
+/// SSH signature settings, configured under `signing` in the config
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SigningConfig {
+    /// Private key used by `--sign` (e.g. `~/.ssh/id_ed25519`)
+    pub key: Option<String>,
+    /// allowed_signers file used for verification (see ssh-keygen(1))
+    pub allowed_signers: Option<String>,
+    /// Verify signatures on every switch, as if `--require-signed` was passed
+    #[serde(default)]
+    pub require_signed: bool,
+}
+
+/// Detached signature stored next to a context (`work.json` -> `work.json.sig`)
+pub fn signature_path(context_path: &Path) -> PathBuf {
+    let mut name = context_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".sig");
+    context_path.with_file_name(name)
+}
+
+fn ssh_keygen() -> Result<()> {
+    if which("ssh-keygen").is_err() {
+        bail!("error: ssh-keygen is required for signing but was not found in PATH");
+    }
+    Ok(())
+}
+
+impl ContextManager {
+    fn configured_path(&self, value: &Option<String>, setting: &str) -> Result<PathBuf> {
+        let home_dir = home_dir()?;
+        match value {
+            Some(path) => Ok(expand_tilde(path, &home_dir)),
+            None => bail!("error: signing.{} is not set in the cctx config", setting),
+        }
+    }
+
+    /// Sign a context with the configured SSH key
+    pub fn sign_context(&self, name: &str) -> Result<()> {
+        self.ensure_writable("sign contexts")?;
+        let name = &self.resolve_alias(name);
+
+        let context_path = self.context_path(name);
+        if !context_path.exists() {
+            bail!("error: no context exists with the name \"{}\"", name);
+        }
+
+        ssh_keygen()?;
+        let key = self.configured_path(&self.config.signing.key, "key")?;
+
+        // The decompressed JSON is signed, so signatures don't depend on
+        // whether the context is stored compressed
+        let content = read_settings(&context_path)?;
+        let mut child = Command::new("ssh-keygen")
+            .args(["-Y", "sign", "-n", SIGNATURE_NAMESPACE, "-f"])
+            .arg(&key)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(content.as_bytes())?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!(
+                "error: failed to sign context \"{}\": {}",
+                name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        fs::write(signature_path(&context_path), &output.stdout)?;
+
+        println!("🔏 Context \"{}\" signed", name.green().bold());
+        Ok(())
+    }
+
+    /// Verify a context's signature, failing if it is missing or invalid
+    pub fn verify_context(&self, name: &str) -> Result<String> {
+        let name = &self.resolve_alias(name);
+        let context_path = self.context_path(name);
+        if !context_path.exists() {
+            bail!("error: no context exists with the name \"{}\"", name);
+        }
+
+        let sig_path = signature_path(&context_path);
+        if !sig_path.exists() {
+            bail!("error: context \"{}\" is not signed", name);
+        }
+
+        let content = read_settings(&context_path)?;
+        self.verify_content(content.as_bytes(), &sig_path)
+            .with_context(|| format!("error: signature check failed for context \"{name}\""))
+    }
+
+    /// Verify content against a detached signature, returning the signer's principal
+    pub(crate) fn verify_content(&self, content: &[u8], sig_path: &Path) -> Result<String> {
+        ssh_keygen()?;
+        let allowed_signers =
+            self.configured_path(&self.config.signing.allowed_signers, "allowed_signers")?;
+
+        let output = Command::new("ssh-keygen")
+            .args(["-Y", "find-principals", "-s"])
+            .arg(sig_path)
+            .arg("-f")
+            .arg(&allowed_signers)
+            .output()?;
+        let principals = String::from_utf8_lossy(&output.stdout);
+        let Some(principal) = principals
+            .lines()
+            .next()
+            .filter(|_| output.status.success())
+        else {
+            bail!("signature was not made by any key in {:?}", allowed_signers);
+        };
+
+        let mut child = Command::new("ssh-keygen")
+            .args(["-Y", "verify", "-n", SIGNATURE_NAMESPACE, "-f"])
+            .arg(&allowed_signers)
+            .arg("-I")
+            .arg(principal)
+            .arg("-s")
+            .arg(sig_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(content)?;
+        }
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+        }
+
+        Ok(principal.to_string())
+    }
+
+    /// Print the result of verifying a context's signature
+    pub fn print_verification(&self, name: &str) -> Result<()> {
+        let principal = self.verify_context(name)?;
+        println!(
+            "✅ Context \"{}\" has a valid signature from {}",
+            name.green().bold(),
+            principal.cyan()
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use anyhow::{bail, Result};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::io::{self, IsTerminal};
+
+use crate::context::ContextManager;
+
+/// Confirmation before a switch replaces the live settings with something very
+/// different, configured under `switch` in the config
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SwitchConfig {
+    /// Ask before switching when the similarity between the live settings and
+    /// the incoming context is below this (0.0 - 1.0); unset never asks
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirm_below: Option<f64>,
+}
+
+/// A switch that needs a confirmation nobody can give: without a terminal, or
+/// through `--serve`
+#[derive(Debug)]
+pub struct ConfirmationRequired(pub String);
+
+impl std::fmt::Display for ConfirmationRequired {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ConfirmationRequired {}
+
+/// Every scalar and list entry of a settings tree as `path=value`, so two
+/// settings can be compared as sets
+fn leaves(value: &Value, path: &str, out: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                leaves(child, &path, out);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                out.insert(format!("{path}[]={item}"));
+            }
+        }
+        scalar => {
+            out.insert(format!("{path}={scalar}"));
+        }
+    }
+}
+
+/// Share of settings entries two contexts have in common (Jaccard index):
+/// 1.0 for identical settings, 0.0 when nothing overlaps
+pub fn similarity(a: &Value, b: &Value) -> f64 {
+    let (mut left, mut right) = (BTreeSet::new(), BTreeSet::new());
+    leaves(a, "", &mut left);
+    leaves(b, "", &mut right);
+    let union = left.union(&right).count();
+    if union == 0 {
+        return 1.0;
+    }
+    left.intersection(&right).count() as f64 / union as f64
+}
+
+impl ContextManager {
+    /// Ask before replacing live settings with a context below the configured
+    /// similarity, which usually means the wrong client's or project's context
+    pub(crate) fn confirm_dissimilar_switch(
+        &self,
+        name: &str,
+        outgoing: &Value,
+        incoming: &Value,
+    ) -> Result<()> {
+        let Some(threshold) = self.config.switch.confirm_below else {
+            return Ok(());
+        };
+        // Nothing is lost when the live settings are empty
+        if self.assume_yes || outgoing.as_object().is_some_and(|map| map.is_empty()) {
+            return Ok(());
+        }
+
+        let score = similarity(outgoing, incoming);
+        if score >= threshold {
+            return Ok(());
+        }
+
+        let percent = (score * 100.0).round();
+        if !self.interactive || !io::stdin().is_terminal() {
+            return Err(ConfirmationRequired(format!(
+                "error: context \"{name}\" shares only {percent}% of the live settings; pass --yes to switch anyway"
+            ))
+            .into());
+        }
+        eprintln!(
+            "{} Context \"{}\" shares only {}% of the live settings",
+            "⚠️".yellow(),
+            name.yellow().bold(),
+            percent
+        );
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt("Switch anyway?")
+            .default(false)
+            .interact_opt()?
+            .unwrap_or(false);
+        if !confirmed {
+            bail!("error: switch cancelled");
+        }
+        Ok(())
+    }
+}
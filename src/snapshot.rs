@@ -0,0 +1,150 @@
+use anyhow::{bail, Result};
+use colored::*;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::compression::{read_settings, write_settings};
+use crate::context::ContextManager;
+use crate::dates;
+
+/// Label used when `--snapshot` is given without `--label`
+const DEFAULT_LABEL_FORMAT: &str = "%Y%m%d-%H%M%S";
+
+fn validate_label(label: &str) -> Result<()> {
+    let valid = !label.is_empty()
+        && !label.starts_with('.')
+        && label
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        bail!(
+            "error: invalid snapshot label \"{}\" (use letters, digits, '-', '_' and '.')",
+            label
+        );
+    }
+    Ok(())
+}
+
+impl ContextManager {
+    /// Directory holding the snapshots of one context
+    pub(crate) fn snapshots_dir(&self, name: &str) -> PathBuf {
+        self.contexts_dir.join(".snapshots").join(name)
+    }
+
+    pub(crate) fn snapshot_path(&self, name: &str, label: &str) -> PathBuf {
+        self.snapshots_dir(name).join(format!("{label}.json"))
+    }
+
+    /// Labels of a context's snapshots, oldest first
+    pub fn list_snapshots(&self, name: &str) -> Vec<String> {
+        let mut snapshots: Vec<(std::time::SystemTime, String)> =
+            fs::read_dir(self.snapshots_dir(name))
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .filter_map(|entry| {
+                            let path = entry.path();
+                            let label = path
+                                .strip_prefix(self.snapshots_dir(name))
+                                .ok()?
+                                .to_str()?
+                                .strip_suffix(".json")?
+                                .to_string();
+                            let modified = entry.metadata().ok()?.modified().ok()?;
+                            Some((modified, label))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+        snapshots.sort();
+        snapshots.into_iter().map(|(_, label)| label).collect()
+    }
+
+    /// Store an immutable copy of a context under `label` (a timestamp by default)
+    pub fn snapshot_context(&self, name: &str, label: Option<&str>) -> Result<()> {
+        self.ensure_writable("snapshot contexts")?;
+
+        let name = &self.resolve_alias(name);
+        let context_path = self.context_path(name);
+        if !context_path.exists() {
+            bail!("error: no context exists with the name \"{}\"", name);
+        }
+
+        let label = match label {
+            Some(label) => label.to_string(),
+            None => dates::now().format(DEFAULT_LABEL_FORMAT).to_string(),
+        };
+        validate_label(&label)?;
+
+        let snapshot_path = self.snapshot_path(name, &label);
+        if snapshot_path.exists() {
+            bail!(
+                "error: context \"{}\" already has a snapshot labelled \"{}\"",
+                name,
+                label
+            );
+        }
+
+        fs::create_dir_all(self.snapshots_dir(name))?;
+        fs::write(&snapshot_path, read_settings(&context_path)?)?;
+
+        // Snapshots are never modified, so keep them from being edited by accident
+        let mut permissions = fs::metadata(&snapshot_path)?.permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&snapshot_path, permissions)?;
+
+        println!(
+            "Snapshot {} of context \"{}\" saved",
+            label.cyan(),
+            name.green().bold()
+        );
+        Ok(())
+    }
+
+    /// Print the snapshots of a context
+    pub fn show_snapshots(&self, name: &str) -> Result<()> {
+        let name = &self.resolve_alias(name);
+        let snapshots = self.list_snapshots(name);
+        if snapshots.is_empty() {
+            println!("No snapshots of context \"{name}\"");
+            return Ok(());
+        }
+
+        println!("📸 Snapshots of context \"{}\":", name.green().bold());
+        for label in snapshots {
+            println!("  {}", label.cyan());
+        }
+        Ok(())
+    }
+
+    /// Replace a context with one of its snapshots; the snapshot itself is kept
+    pub fn restore_snapshot(&self, name: &str, label: &str) -> Result<()> {
+        self.ensure_writable("restore snapshots")?;
+
+        let name = &self.resolve_alias(name);
+        validate_label(label)?;
+        let snapshot_path = self.snapshot_path(name, label);
+        if !snapshot_path.exists() {
+            bail!(
+                "error: context \"{}\" has no snapshot labelled \"{}\"",
+                name,
+                label
+            );
+        }
+
+        let content = fs::read_to_string(&snapshot_path)?;
+        // Snapshots taken before a managed merge lack the required entries
+        self.ensure_protected_kept(name, &serde_json::from_str(&content)?)?;
+        write_settings(&self.context_path(name), &content)?;
+
+        println!(
+            "Context \"{}\" restored from snapshot {}",
+            name.green().bold(),
+            label.cyan()
+        );
+        if self.get_current_context()?.as_deref() == Some(name.as_str()) {
+            println!("Switch to it again to apply the restored settings");
+        }
+        Ok(())
+    }
+}
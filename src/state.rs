@@ -1,27 +1,93 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-#[derive(Serialize, Deserialize, Default)]
+use crate::atomic::write_atomic;
+use crate::dates;
+use crate::quarantine::{quarantine, warn_corrupt};
+
+/// State format written by this version of cctx
+pub const STATE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
 pub struct State {
+    /// Format version; files written before versioning have none and count as 0
+    #[serde(default)]
+    pub version: u32,
     pub current: Option<String>,
     pub previous: Option<String>,
+    /// Last time each context was switched to (RFC 3339)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub last_used: BTreeMap<String, String>,
+    /// Fingerprint of each included fragment when it was last applied, keyed by path
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fragments: BTreeMap<String, String>,
+    /// Fields written by newer cctx versions, kept so a downgrade does not drop them
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+impl Default for State {
+    fn default() -> Self {
+        Self {
+            version: STATE_VERSION,
+            current: None,
+            previous: None,
+            last_used: BTreeMap::new(),
+            fragments: BTreeMap::new(),
+            extra: BTreeMap::new(),
+        }
+    }
 }
 
 impl State {
+    /// Why the state file at `state_path` cannot be loaded, if it exists and is corrupted
+    pub fn parse_error(state_path: &Path) -> Option<String> {
+        let content = fs::read_to_string(state_path).ok()?;
+        serde_json::from_str::<State>(&content)
+            .err()
+            .map(|e| e.to_string())
+    }
+
+    /// Load state, starting fresh if the state file is corrupted
     pub fn load(state_path: &PathBuf) -> Result<Self> {
         if state_path.exists() {
             let content = fs::read_to_string(state_path)?;
-            Ok(serde_json::from_str(&content)?)
+            match serde_json::from_str::<State>(&content) {
+                Ok(mut state) => {
+                    state.migrate();
+                    Ok(state)
+                }
+                Err(e) => {
+                    warn_corrupt(state_path, &e.to_string());
+                    Ok(State::default())
+                }
+            }
         } else {
             Ok(State::default())
         }
     }
 
-    pub fn save(&self, state_path: &PathBuf) -> Result<()> {
+    /// Upgrade state written by older versions, one version at a time.
+    ///
+    /// State from a newer version is left as is: its version is kept so an
+    /// older cctx never claims to have written it.
+    fn migrate(&mut self) {
+        if self.version == 0 {
+            // Unversioned state has the same shape as version 1
+            self.version = 1;
+        }
+    }
+
+    /// Save state; a corrupted state file is moved aside rather than overwritten
+    pub fn save(&self, state_path: &Path) -> Result<()> {
+        if let Some(reason) = Self::parse_error(state_path) {
+            let _ = quarantine(state_path, &reason);
+        }
         let content = serde_json::to_string_pretty(self)?;
-        fs::write(state_path, content)?;
+        write_atomic(state_path, content.as_bytes())?;
         Ok(())
     }
 
@@ -31,9 +97,26 @@ impl State {
                 self.previous = Some(current.clone());
             }
         }
+        self.last_used
+            .insert(context.clone(), dates::now().to_rfc3339());
         self.current = Some(context);
     }
 
+    /// Drop usage data for a context that no longer exists
+    pub fn forget(&mut self, context: &str) {
+        self.last_used.remove(context);
+        if self.previous.as_deref() == Some(context) {
+            self.previous = None;
+        }
+    }
+
+    /// Carry usage data over to a renamed context
+    pub fn rename(&mut self, old_name: &str, new_name: &str) {
+        if let Some(last_used) = self.last_used.remove(old_name) {
+            self.last_used.insert(new_name.to_string(), last_used);
+        }
+    }
+
     pub fn unset_current(&mut self) -> Option<String> {
         let current = self.current.take();
         if let Some(prev) = current.as_ref() {
@@ -0,0 +1,160 @@
+use anyhow::{bail, Result};
+use colored::*;
+use serde_json::Value;
+
+use crate::compression::read_settings;
+use crate::context::{ContextManager, ManagerOptions, SettingsLevel};
+use crate::dates;
+
+/// Whether the live settings file still matches the context it was switched to
+pub(crate) enum Drift {
+    InSync,
+    Modified,
+    /// The context or the settings file is missing or unparsable
+    Unknown,
+}
+
+impl ContextManager {
+    pub(crate) fn drift(&self, current: &str) -> Drift {
+        let parse = |path: &std::path::Path| -> Option<Value> {
+            serde_json::from_str(&read_settings(path).ok()?).ok()
+        };
+        // Compare against what switching wrote, with fragments merged in
+        let context = parse(&self.context_path(current)).and_then(|mut context| {
+            self.resolve_includes(&mut context).ok()?;
+            Some(context)
+        });
+        match (context, parse(&self.claude_settings_path)) {
+            (Some(context), Some(live)) if context == live => Drift::InSync,
+            (Some(_), Some(_)) => Drift::Modified,
+            _ => Drift::Unknown,
+        }
+    }
+
+    /// Print the state of one level: current context, drift, last switch and budget.
+    /// Returns the number of problems (drift and budget violations) shown.
+    fn print_level_status(&self, label: &str) -> Result<usize> {
+        let state = self.load_state()?;
+        let contexts = self.list_contexts()?;
+
+        let Some(current) = state.current.as_deref() else {
+            println!(
+                "  {label:<8} {}  ({} contexts available)",
+                "no context".dimmed(),
+                contexts.len()
+            );
+            return Ok(0);
+        };
+
+        let mut problems = 0;
+        let drift = match self.drift(current) {
+            Drift::InSync => "in sync".green(),
+            Drift::Modified => {
+                problems += 1;
+                "modified since switch".yellow()
+            }
+            Drift::Unknown => {
+                problems += 1;
+                "settings unreadable".red()
+            }
+        };
+        let switched = state
+            .last_used
+            .get(current)
+            .map(|ts| format!(", switched {}", dates::format_rfc3339(ts, self.date_style)))
+            .unwrap_or_default();
+        let previous = state
+            .previous
+            .as_deref()
+            .map(|previous| format!(", previous {previous}"))
+            .unwrap_or_default();
+        println!(
+            "  {label:<8} {}  {drift}{switched}{previous}  ({} contexts)",
+            current.green().bold(),
+            contexts.len()
+        );
+
+        if let Some(live) = read_settings(&self.claude_settings_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        {
+            for violation in self.config.budget.violations(&live) {
+                problems += 1;
+                println!("  {:<8} {} {}", "", "budget:".yellow(), violation);
+            }
+        }
+        Ok(problems)
+    }
+
+    /// `-c --verbose`: the current context with the previous one and when the
+    /// last switch happened, straight from the state file
+    pub fn print_current_verbose(&self) -> Result<()> {
+        let state = self.load_state()?;
+        let none = || "none".dimmed().to_string();
+        let current = state.current.as_deref();
+        println!(
+            "current:  {}",
+            current.map_or_else(none, |c| c.green().bold().to_string())
+        );
+        println!(
+            "previous: {}",
+            state.previous.as_deref().map_or_else(none, str::to_string)
+        );
+        let switched = current.and_then(|current| state.last_used.get(current));
+        println!(
+            "switched: {}",
+            switched.map_or_else(none, |ts| dates::format_rfc3339(ts, self.date_style))
+        );
+        Ok(())
+    }
+
+    /// Dashboard of the whole store: every level's context, drift, budget
+    /// violations, and backups
+    pub fn print_status(&self, options: &ManagerOptions) -> Result<()> {
+        let header = if self.config.ui.emoji {
+            "📍 cctx status"
+        } else {
+            "cctx status"
+        };
+        println!("{}", header.bold());
+
+        let options = ManagerOptions {
+            read_only: true,
+            target: None,
+            ..options.clone()
+        };
+        let levels = [
+            (SettingsLevel::User, "user"),
+            (SettingsLevel::Project, "project"),
+            (SettingsLevel::Local, "local"),
+        ];
+        let mut problems = 0;
+        for (level, label) in levels {
+            match ContextManager::new_with_options(level, &options) {
+                Ok(manager) => problems += manager.print_level_status(label)?,
+                Err(e) => println!("  {label:<8} {}", format!("unavailable: {e}").dimmed()),
+            }
+        }
+
+        let backups = self.list_backups();
+        let newest = backups
+            .last()
+            .map(|backup| format!(", newest {}", backup.timestamp(self.date_style)))
+            .unwrap_or_default();
+        println!();
+        println!("  backups  {}{newest}", backups.len());
+
+        let quarantined = self.quarantined_files().len();
+        if quarantined > 0 {
+            println!(
+                "  {}  {quarantined} quarantined files; run `cctx --doctor`",
+                "doctor".yellow()
+            );
+        }
+
+        if self.strict && problems > 0 {
+            bail!("error: {} drift or budget problems (--strict)", problems);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+use anyhow::{bail, Context, Result};
+use colored::*;
+use serde_json::{json, Map, Value};
+
+use crate::compression::{read_settings, write_settings};
+use crate::context::ContextManager;
+use crate::integration::shell_quote;
+
+/// What `--statusline` does to a context or the live settings
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum StatuslineAction {
+    Install,
+    Uninstall,
+}
+
+/// Arguments of the status line command cctx installs, used to recognize it
+const PROMPT_ARGS: &str = "--prompt --all-levels";
+
+/// Command for Claude Code's status line: the active context of every level
+pub(crate) fn statusline_command() -> Result<String> {
+    let exe = std::env::current_exe().context("error: could not locate the cctx binary")?;
+    Ok(format!(
+        "{} {PROMPT_ARGS}",
+        shell_quote(&exe.to_string_lossy())
+    ))
+}
+
+/// Whether a `statusLine` entry is one cctx installed, wherever the binary lives
+fn is_cctx_statusline(statusline: &Value) -> bool {
+    statusline
+        .get("command")
+        .and_then(Value::as_str)
+        .is_some_and(|command| command.ends_with(PROMPT_ARGS))
+}
+
+impl ContextManager {
+    /// Install or remove a `statusLine` running `cctx --prompt --all-levels` in a
+    /// context, or in the live settings when no context is given. A status line
+    /// cctx did not install is only replaced with --force, and never removed.
+    pub fn statusline(&self, action: StatuslineAction, context: Option<&str>) -> Result<()> {
+        self.ensure_writable("change the status line")?;
+
+        let (path, label) = match context {
+            Some(name) => {
+                if !self.list_contexts()?.iter().any(|ctx| ctx == name) {
+                    bail!("error: no context exists with the name \"{}\"", name);
+                }
+                (self.context_path(name), format!("context \"{name}\""))
+            }
+            None => (
+                self.claude_settings_path.clone(),
+                "the live settings".to_string(),
+            ),
+        };
+
+        let mut settings: Value = if path.exists() {
+            serde_json::from_str(&read_settings(&path)?)
+                .with_context(|| format!("error: {:?} is not valid JSON", path))?
+        } else {
+            Value::Object(Map::new())
+        };
+        let Some(root) = settings.as_object_mut() else {
+            bail!("error: {:?} must be a JSON object", path);
+        };
+
+        match action {
+            StatuslineAction::Install => {
+                if let Some(existing) = root.get("statusLine") {
+                    if !is_cctx_statusline(existing) && !self.force {
+                        bail!(
+                            "error: {} already has a status line not set by cctx; pass --force to replace it",
+                            label
+                        );
+                    }
+                }
+                root.insert(
+                    "statusLine".to_string(),
+                    json!({ "type": "command", "command": statusline_command()? }),
+                );
+            }
+            StatuslineAction::Uninstall => match root.get("statusLine") {
+                None => {
+                    println!("No status line in {label}");
+                    return Ok(());
+                }
+                Some(existing) if !is_cctx_statusline(existing) => {
+                    bail!(
+                        "error: the status line in {} was not set by cctx; remove it by hand",
+                        label
+                    );
+                }
+                Some(_) => {
+                    root.remove("statusLine");
+                }
+            },
+        }
+
+        if context.is_none() {
+            self.backup_settings(self.get_current_context()?.as_deref())?;
+        }
+        write_settings(&path, &serde_json::to_string_pretty(&settings)?)?;
+
+        match action {
+            StatuslineAction::Install => println!(
+                "✅ Claude Code's status line in {} now shows the active contexts",
+                label.green()
+            ),
+            StatuslineAction::Uninstall => {
+                println!("✅ Removed the cctx status line from {}", label.green())
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+use anyhow::{bail, Context, Result};
+use colored::*;
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, IsTerminal};
+
+use crate::compression::{read_settings, write_settings};
+use crate::context::ContextManager;
+use crate::metadata::{ContextMetadata, Provenance};
+
+/// `{{name}}` placeholders, with optional spaces inside the braces
+fn placeholder_pattern() -> Regex {
+    Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").expect("valid placeholder pattern")
+}
+
+/// Parse `--var key=value` assignments
+fn parse_vars(assignments: &[String]) -> Result<BTreeMap<String, String>> {
+    let mut vars = BTreeMap::new();
+    for assignment in assignments {
+        let Some((key, value)) = assignment.split_once('=') else {
+            bail!("error: --var expects key=value, got \"{}\"", assignment);
+        };
+        vars.insert(key.trim().to_string(), value.to_string());
+    }
+    Ok(vars)
+}
+
+fn scan(text: &str, pattern: &Regex, out: &mut BTreeSet<String>) {
+    for captures in pattern.captures_iter(text) {
+        out.insert(captures[1].to_string());
+    }
+}
+
+/// Every placeholder used in string values and object keys
+fn collect_placeholders(value: &Value, pattern: &Regex, out: &mut BTreeSet<String>) {
+    match value {
+        Value::String(text) => scan(text, pattern, out),
+        Value::Object(map) => {
+            for (key, child) in map {
+                scan(key, pattern, out);
+                collect_placeholders(child, pattern, out);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_placeholders(item, pattern, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Substitute placeholders inside strings only, so values never break the JSON
+fn substitute(value: &Value, pattern: &Regex, vars: &BTreeMap<String, String>) -> Value {
+    let fill = |text: &str| {
+        pattern
+            .replace_all(text, |captures: &regex::Captures| {
+                vars.get(&captures[1]).cloned().unwrap_or_default()
+            })
+            .into_owned()
+    };
+    match value {
+        Value::String(text) => Value::String(fill(text)),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, child)| (fill(key), substitute(child, pattern, vars)))
+                .collect::<Map<_, _>>(),
+        ),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| substitute(item, pattern, vars))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+impl ContextManager {
+    /// Values known without asking: `project` is the project directory's name
+    fn builtin_vars(&self) -> BTreeMap<String, String> {
+        let mut vars = BTreeMap::new();
+        if let Some(project) = self.project_root.file_name().and_then(|s| s.to_str()) {
+            vars.insert("project".to_string(), project.to_string());
+        }
+        vars
+    }
+
+    /// Create a context from a template context whose strings contain `{{var}}`
+    /// placeholders. Values come from `--var`, then built-ins (`project`), and
+    /// are prompted for on a terminal; without one, missing values are an error.
+    pub fn create_from_template(
+        &self,
+        name: &str,
+        template: &str,
+        assignments: &[String],
+    ) -> Result<String> {
+        self.ensure_writable("create contexts")?;
+
+        let name = &self.validate_name(name)?;
+        let contexts = self.list_contexts()?;
+        if contexts.contains(name) {
+            bail!("error: context \"{}\" already exists", name);
+        }
+        let template = &self.resolve_alias(template);
+        if !contexts.contains(template) {
+            bail!("error: no context exists with the name \"{}\"", template);
+        }
+
+        let settings: Value =
+            serde_json::from_str(&read_settings(&self.context_path(template))?)
+                .with_context(|| format!("error: template \"{template}\" is not valid JSON"))?;
+
+        let pattern = placeholder_pattern();
+        let mut used = BTreeSet::new();
+        collect_placeholders(&settings, &pattern, &mut used);
+
+        let mut vars = parse_vars(assignments)?;
+        for unused in vars.keys().filter(|key| !used.contains(*key)) {
+            eprintln!(
+                "warning: template \"{}\" has no {{{{{}}}}} placeholder",
+                template, unused
+            );
+        }
+        for (key, value) in self.builtin_vars() {
+            vars.entry(key).or_insert(value);
+        }
+
+        let missing: Vec<&String> = used.iter().filter(|key| !vars.contains_key(*key)).collect();
+        if !missing.is_empty() {
+            if !io::stdin().is_terminal() {
+                bail!(
+                    "error: template \"{}\" needs values for {}; pass them with --var key=value",
+                    template,
+                    missing
+                        .iter()
+                        .map(|key| key.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+            for key in missing {
+                let value = dialoguer::Input::<String>::new()
+                    .with_prompt(key)
+                    .interact_text()?;
+                vars.insert(key.clone(), value);
+            }
+        }
+
+        let settings = substitute(&settings, &pattern, &vars);
+        write_settings(
+            &self.context_path(name),
+            &serde_json::to_string_pretty(&settings)?,
+        )?;
+
+        let metadata = ContextMetadata {
+            provenance: Some(Provenance {
+                created_from: format!("template \"{template}\""),
+                ..Provenance::captured(&self.claude_settings_path)
+            }),
+            ..ContextMetadata::default()
+        };
+        self.save_metadata(name, &metadata)?;
+
+        println!(
+            "Context \"{}\" created from template \"{}\" ({} placeholders filled)",
+            name.green().bold(),
+            template,
+            used.len()
+        );
+        Ok(name.to_string())
+    }
+}
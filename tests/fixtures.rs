@@ -0,0 +1,176 @@
+// Runs cctx against the `--dev-fixtures` sandbox, so the fixtures stay loadable
+// and keep exercising the paths they were written for.
+#![cfg(all(feature = "dev", unix))]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+/// A fresh fixture directory, removed when the test ends
+struct Sandbox {
+    dir: PathBuf,
+}
+
+impl Sandbox {
+    fn generate(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("cctx-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let output = cctx(
+            &dir,
+            Path::new("."),
+            &["--dev-fixtures", dir.to_str().unwrap()],
+        );
+        assert!(output.status.success(), "{}", stderr(&output));
+        Self {
+            dir: dir.canonicalize().unwrap(),
+        }
+    }
+
+    fn home(&self) -> PathBuf {
+        self.dir.join("home")
+    }
+
+    fn project(&self) -> PathBuf {
+        self.dir.join("project")
+    }
+
+    /// Run cctx with the sandbox as home, from `cwd`
+    fn run(&self, cwd: &Path, args: &[&str]) -> Output {
+        cctx(&self.home(), cwd, args)
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn cctx(home: &Path, cwd: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cctx"))
+        .args(args)
+        .current_dir(cwd)
+        .env("HOME", home)
+        .env("RUST_BACKTRACE", "0")
+        .env_remove("CCTX_DIR")
+        .env_remove("CCTX_READONLY")
+        .env_remove("CCTX_STRICT")
+        .env_remove("CCTX_MANAGED_SETTINGS")
+        .stdin(Stdio::null())
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+/// Names from `--porcelain` listing output
+fn names(output: &Output) -> Vec<String> {
+    stdout(output)
+        .lines()
+        .filter_map(|line| line.split('\t').next())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Every file under `dir` with its content, sorted by path
+fn tree(dir: &Path) -> Vec<(PathBuf, Vec<u8>)> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir).unwrap().flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                files.push((path.clone(), fs::read(&path).unwrap()));
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+#[test]
+fn fixtures_are_identical_on_every_run() {
+    let first = Sandbox::generate("fixtures-first");
+    let second = Sandbox::generate("fixtures-second");
+    let relative = |sandbox: &Sandbox| -> Vec<(PathBuf, Vec<u8>)> {
+        tree(&sandbox.dir)
+            .into_iter()
+            .map(|(path, content)| (path.strip_prefix(&sandbox.dir).unwrap().into(), content))
+            .collect()
+    };
+    assert_eq!(relative(&first), relative(&second));
+}
+
+#[test]
+fn user_level_lists_valid_contexts_and_skips_corrupted_ones() {
+    let sandbox = Sandbox::generate("fixtures-user");
+    let output = sandbox.run(&sandbox.home(), &["--porcelain"]);
+    assert!(output.status.success(), "{}", stderr(&output));
+
+    let listed = names(&output);
+    for name in ["work", "personal", "huge", "client acme", "café", "hooks"] {
+        assert!(
+            listed.contains(&name.to_string()),
+            "{name} missing from {listed:?}"
+        );
+    }
+    for corrupted in ["truncated", "empty", "notes"] {
+        assert!(
+            !listed.contains(&corrupted.to_string()),
+            "{corrupted} listed"
+        );
+    }
+    assert!(stderr(&output).contains("truncated.json"));
+
+    // Listing only reports corrupted files; they stay where they are
+    let settings = sandbox.home().join(".claude/settings");
+    assert!(settings.join("truncated.json").exists());
+    assert!(!settings.join("truncated.json.corrupt").exists());
+
+    let current = sandbox.run(&sandbox.home(), &["--current"]);
+    assert_eq!(stdout(&current).trim(), "work");
+}
+
+#[test]
+fn doctor_reports_and_fix_quarantines_corrupted_files() {
+    let sandbox = Sandbox::generate("fixtures-doctor");
+    let settings = sandbox.home().join(".claude/settings");
+
+    let report = sandbox.run(&sandbox.home(), &["--doctor"]);
+    assert!(report.status.success(), "{}", stderr(&report));
+    for file in ["truncated.json", "empty.json", ".huge-merge-history.json"] {
+        assert!(stdout(&report).contains(file), "{file} not reported");
+        assert!(settings.join(file).exists());
+    }
+
+    let fix = sandbox.run(&sandbox.home(), &["--doctor", "--fix"]);
+    assert!(fix.status.success(), "{}", stderr(&fix));
+    for file in ["truncated.json", "empty.json", ".huge-merge-history.json"] {
+        assert!(!settings.join(file).exists(), "{file} not quarantined");
+        assert!(settings.join(format!("{file}.corrupt")).exists());
+    }
+}
+
+#[test]
+fn project_and_local_levels_are_found_from_a_subdirectory() {
+    let sandbox = Sandbox::generate("fixtures-project");
+    let nested = sandbox.project().join("src/nested");
+
+    let project = sandbox.run(&nested, &["--in-project", "--current"]);
+    assert_eq!(stdout(&project).trim(), "frontend", "{}", stderr(&project));
+    let local = sandbox.run(&nested, &["--local", "--current"]);
+    assert_eq!(stdout(&local).trim(), "debug", "{}", stderr(&local));
+
+    let switched = sandbox.run(&nested, &["--in-project", "backend"]);
+    assert!(switched.status.success(), "{}", stderr(&switched));
+    let applied = fs::read_to_string(sandbox.project().join(".claude/settings.json")).unwrap();
+    assert!(applied.contains("Bash(cargo:*)"));
+}